@@ -0,0 +1,256 @@
+//! TypeScript bindings of the tauri commands, events and settings, written to `src/bindings.ts`.
+//!
+//! `test_bindings` regenerates the file and fails when it was stale, so run `cargo test` and commit it
+//! after changing a command below or a type it uses.
+
+use std::{any::TypeId, collections::BTreeMap, path::Path};
+
+use ts_rs::{TypeVisitor, TS};
+
+use crate::{
+  error::Error,
+  logs::{LogLevel, LogLine, LogSession},
+  obs::{collection::{bundle::{ExportReport, ImportReport}, diff::{Change, MergeResult}, validate::{Relinked, ValidationReport}}, output::{replay::ReplayBufferSetting, services::{ServiceIssue, Services}, stream::StreamSetting, virtualcam::VirtualCamSource, OutputEvent, OutputState, RecordingSetting}, remux::{RemuxEvent, RemuxFormat}, settings},
+  profile::{backup::Snapshot, watch::ProfileEvent},
+  ProfileResult,
+};
+
+pub const OUTPUT: &str = "../src/bindings.ts";
+
+/// `new` is a valid rust argument but not a javascript parameter
+const RESERVED: &[&str] = &["new", "default", "delete", "function", "class"];
+
+struct Command {
+  name: &'static str,
+  args: Vec<(&'static str, String)>,
+  output: String,
+}
+
+/// Declarations of every named type reached from the visited ones, by name.
+#[derive(Default)]
+struct Declarations {
+  seen: Vec<TypeId>,
+  decls: BTreeMap<String, String>,
+}
+
+impl TypeVisitor for Declarations {
+  fn visit<T: TS + 'static + ?Sized>(&mut self) {
+    if self.seen.contains(&TypeId::of::<T>()) { return }
+    self.seen.push(TypeId::of::<T>());
+    if T::output_path().is_some() {
+      let docs = T::docs().unwrap_or_default();
+      self.decls.insert(T::ident(), format!("{docs}export {}", T::decl()));
+    } else {
+      // `Vec<T>`, `Option<T>`...
+      T::visit_generics(self);
+    }
+    T::visit_dependencies(self);
+  }
+}
+
+macro_rules! commands {
+  ($declarations:ident, $($name:ident($($arg:ident: $ty:ty),*) -> $output:ty;)*) => {
+    vec![$({
+      $($declarations.visit::<$ty>();)*
+      $declarations.visit::<$output>();
+      Command { name: stringify!($name), args: vec![$((stringify!($arg), ts_name::<$ty>())),*], output: ts_name::<$output>() }
+    }),*]
+  };
+}
+
+/// `"collection-added" => ProfileEvent::CollectionAdded`, the payload is that variant of the tagged enum
+macro_rules! events {
+  ($($name:literal => $ty:ident::$variant:ident,)*) => {
+    [$({
+      // the variant exists
+      let _ = |event: &$ty| matches!(event, $ty::$variant { .. });
+      ($name, format!("Extract<{}, {{ type: \"{}\" }}>", <$ty as TS>::name(), snake_case(stringify!($variant))))
+    }),*]
+  };
+}
+
+/// serde_json writes 64-bit integers as plain numbers, fields of the types say so with `#[ts(type = "number")]`
+fn ts_name<T: TS + 'static + ?Sized>() -> String {
+  if [TypeId::of::<u64>(), TypeId::of::<i64>()].contains(&TypeId::of::<T>()) {
+    return "number".to_string()
+  }
+  T::name()
+}
+
+/// as `#[serde(rename_all = "snake_case")]`
+fn snake_case(name: &str) -> String {
+  let mut result = String::new();
+  for (idx, c) in name.chars().enumerate() {
+    if c.is_uppercase() && idx > 0 {
+      result.push('_');
+    }
+    result.extend(c.to_lowercase());
+  }
+  result
+}
+
+fn camel_case(name: &str) -> String {
+  let mut result = String::new();
+  for (idx, part) in name.split('_').filter(|i| !i.is_empty()).enumerate() {
+    let mut chars = part.chars();
+    match chars.next() {
+      Some(c) if idx > 0 => result.extend(c.to_uppercase().chain(chars)),
+      Some(c) => result.extend(std::iter::once(c).chain(chars)),
+      None => {},
+    }
+  }
+  result
+}
+
+/// as registered in `main`, without the `AppHandle` and `State` arguments injected by tauri
+fn generate_commands(declarations: &mut Declarations) -> Vec<Command> {
+  commands![declarations,
+    greet(name: &str) -> String;
+    list_profile(folder: Option<&str>) -> ProfileResult;
+    validate_scene_collection(file: &str) -> ValidationReport;
+    relink_scene_collection(file: &str, dir: &str) -> Vec<Relinked>;
+    diff_scene_collection(old: &str, new: &str) -> Vec<Change>;
+    merge_scene_collection(base: &str, ours: &str, theirs: &str, output: Option<&str>) -> MergeResult;
+    export_scene_bundle(name: &str, bundle: &str, folder: Option<&str>) -> ExportReport;
+    import_scene_bundle(bundle: &str, target_dir: &str, overwrite: Option<bool>, folder: Option<&str>) -> ImportReport;
+    create_scene_collection(name: &str, folder: Option<&str>) -> std::path::PathBuf;
+    duplicate_scene_collection(name: &str, new_name: &str, folder: Option<&str>) -> std::path::PathBuf;
+    rename_scene_collection(name: &str, new_name: &str, folder: Option<&str>) -> std::path::PathBuf;
+    delete_scene_collection(name: &str, folder: Option<&str>) -> ();
+    create_profile(name: &str, folder: Option<&str>) -> std::path::PathBuf;
+    duplicate_profile(name: &str, new_name: &str, folder: Option<&str>) -> std::path::PathBuf;
+    rename_profile(name: &str, new_name: &str, folder: Option<&str>) -> std::path::PathBuf;
+    delete_profile(name: &str, folder: Option<&str>) -> ();
+    list_scene_backups(name: &str, folder: Option<&str>) -> Vec<Snapshot>;
+    backup_scene_collection(name: &str, folder: Option<&str>) -> u64;
+    save_scene_collection(name: &str, folder: Option<&str>) -> u64;
+    restore_scene_backup(name: &str, id: u64, folder: Option<&str>) -> ();
+    watch_profile(folder: Option<&str>) -> ();
+    unwatch_profile() -> ();
+    list_log_sessions() -> Vec<LogSession>;
+    read_log(name: &str, level: Option<LogLevel>) -> Vec<LogLine>;
+    export_current_log(target: &str) -> std::path::PathBuf;
+    start_recording(setting: RecordingSetting) -> std::path::PathBuf;
+    stop_recording() -> ();
+    pause_recording() -> ();
+    resume_recording() -> ();
+    split_recording() -> ();
+    get_recording_state() -> OutputState;
+    remux_recording(input: &str, output: Option<&str>, format: Option<RemuxFormat>) -> u64;
+    cancel_remux(id: u64) -> bool;
+    start_stream(setting: StreamSetting) -> ();
+    stop_stream() -> ();
+    get_stream_state() -> OutputState;
+    start_replay_buffer(setting: ReplayBufferSetting) -> ();
+    stop_replay_buffer() -> ();
+    save_replay_buffer() -> ();
+    get_replay_buffer_state() -> OutputState;
+    start_virtualcam(source: VirtualCamSource) -> ();
+    stop_virtualcam() -> ();
+    get_virtualcam_state() -> OutputState;
+    list_stream_services() -> Services;
+    validate_profile_service(name: &str, folder: Option<&str>) -> Vec<ServiceIssue>;
+    create_display() -> ();
+  ]
+}
+
+fn generate() -> String {
+  let mut declarations = Declarations::default();
+  let commands = generate_commands(&mut declarations);
+  // see the `name` of `ProfileEvent`, `OutputEvent` and `RemuxEvent`
+  let events = events![
+    "collection-added" => ProfileEvent::CollectionAdded,
+    "collection-changed" => ProfileEvent::CollectionChanged,
+    "collection-removed" => ProfileEvent::CollectionRemoved,
+    "profile-changed" => ProfileEvent::ProfileChanged,
+    "recording-file-finished" => OutputEvent::FileFinished,
+    "output-started" => OutputEvent::Started,
+    "output-stopped" => OutputEvent::Stopped,
+    "output-reconnecting" => OutputEvent::Reconnecting,
+    "output-reconnected" => OutputEvent::Reconnected,
+    "output-stats" => OutputEvent::Stats,
+    "replay-saved" => OutputEvent::ReplaySaved,
+    "remux-progress" => RemuxEvent::Progress,
+    "remux-finished" => RemuxEvent::Finished,
+    "remux-cancelled" => RemuxEvent::Cancelled,
+    "remux-failed" => RemuxEvent::Failed,
+  ];
+  declarations.visit::<ProfileEvent>();
+  declarations.visit::<OutputEvent>();
+  declarations.visit::<RemuxEvent>();
+  // rejected value of every command
+  declarations.visit::<Error>();
+  // sent as `DataObj` json when creating sources
+  declarations.visit::<settings::win_game_capture::Setting>();
+  // superseded by `mac_screen_capture`, but collections saved by older OBS Studio still hold them
+  #[allow(deprecated)]
+  declarations.visit::<settings::mac_display_capture::Setting>();
+  #[allow(deprecated)]
+  declarations.visit::<settings::mac_window_capture::Setting>();
+  declarations.visit::<settings::mac_screen_capture::RawSetting>();
+  declarations.visit::<settings::image_source::ColorSetting>();
+  declarations.visit::<settings::image_source::ImageSetting>();
+  // `recordEncoder.json` and `streamEncoder.json` of a profile
+  declarations.visit::<settings::obs_x264::Setting>();
+  declarations.visit::<settings::ffmpeg_audio::Setting>();
+  declarations.visit::<settings::ffmpeg_av1::Setting>();
+
+  let mut out = String::new();
+  out.push_str("// Generated by `cargo test` from `src-tauri/src/bindings.rs`, do not edit.\n");
+  out.push_str("import { invoke } from \"@tauri-apps/api/core\"\n");
+  out.push_str("import { listen, type UnlistenFn } from \"@tauri-apps/api/event\"\n\n");
+  for decl in declarations.decls.values() {
+    out.push_str(decl);
+    out.push_str("\n\n");
+  }
+
+  for command in &commands {
+    // trailing optional arguments could be omitted
+    let required = command.args.iter().rposition(|(_, ty)| !ty.ends_with(" | null")).map_or(0, |i| i + 1);
+    let params = command.args.iter().enumerate().map(|(idx, (arg, ty))| {
+      let optional = if idx < required { "" } else { "?" };
+      let name = camel_case(arg);
+      let name = if RESERVED.contains(&name.as_str()) { format!("{name}_") } else { name };
+      format!("{name}{optional}: {ty}")
+    }).collect::<Vec<_>>().join(", ");
+    let args = command.args.iter().map(|(arg, _)| {
+      match camel_case(arg) {
+        name if RESERVED.contains(&name.as_str()) => format!("{name}: {name}_"),
+        name => name,
+      }
+    }).collect::<Vec<_>>();
+    let args = if args.is_empty() { String::new() } else { format!(", {{ {} }}", args.join(", ")) };
+    let output = if command.output == "null" { "void" } else { &command.output };
+    out.push_str(&format!("export function {}({params}): Promise<{output}> {{\n", camel_case(command.name)));
+    out.push_str(&format!("  return invoke(\"{}\"{args})\n}}\n\n", command.name));
+  }
+
+  out.push_str("export type Events = {\n");
+  for (name, payload) in &events {
+    out.push_str(&format!("  \"{name}\": {payload}\n"));
+  }
+  out.push_str("}\n\n");
+  out.push_str("export function onEvent<E extends keyof Events>(event: E, handler: (payload: Events[E]) => void): Promise<UnlistenFn> {\n");
+  out.push_str("  return listen<Events[E]>(event, (e) => handler(e.payload))\n}\n");
+  // emitted by the game side instead of calling `save_replay_buffer`
+  out.push_str(&format!("\nexport const SAVE_REPLAY_REQUEST = \"{}\"\n", crate::SAVE_REPLAY_REQUEST));
+  out
+}
+
+#[test]
+fn test_command_names() {
+  let mut declarations = Declarations::default();
+  let names = generate_commands(&mut declarations).iter().map(|i| i.name).collect::<Vec<_>>();
+  assert_eq!(names, crate::COMMANDS);
+  assert_eq!(snake_case("ReplaySaved"), "replay_saved");
+}
+
+#[test]
+fn test_bindings() {
+  let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(OUTPUT);
+  let content = generate();
+  if std::fs::read_to_string(&path).ok().as_ref() != Some(&content) {
+    std::fs::write(&path, &content).unwrap();
+    panic!("{} was stale and has been regenerated, commit it", path.display());
+  }
+}
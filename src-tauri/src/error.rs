@@ -0,0 +1,233 @@
+//! Error returned by the tauri commands, serialized for the frontend as
+//!
+//! ```json
+//! {"code": "not_found", "message": "io error: No such file or directory (os error 2)",
+//!  "details": {"kind": "NotFound"}, "sources": ["No such file or directory (os error 2)"]}
+//! ```
+//! `code` is stable, `message` and `sources` are for humans and logs.
+
+use serde_json::{json, Value};
+
+use crate::{obs::{self, collection::bundle}, profile};
+
+pub type Result<T, E=Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+  /// invalid utf-8 from libobs
+  Utf8,
+  /// a string holding `\0` passed to libobs
+  Nul,
+  Json,
+  Io,
+  NotFound,
+  PermissionDenied,
+  AlreadyExists,
+  Zip,
+  /// libobs returned an error code, in `details.code`
+  ObsCode,
+  /// a libobs function returned null, in `details.function`
+  NulPointer,
+  /// error from obs-wrapper
+  Obs,
+  ObsNotInitialized,
+  /// `obs_reset_audio` failed, e.g. while an output is active
+  ResetAudio,
+  /// a libobs module is missing, its name in `details.module`
+  ModuleNotLoaded,
+  SceneCycle,
+  /// a group was expected, the name of the item in `details.name`
+  NotAGroup,
+  /// an output failed to start, pause or resume, the reason from libobs in `message`
+  Output,
+  /// a recording could not be remuxed, the reason in `message`
+  Remux,
+  Cancelled,
+  /// the virtual camera needs the v4l2loopback module, how to load it in `message`
+  NoLoopbackDevice,
+  /// OBS Studio is running and would overwrite the change
+  ObsRunning,
+  /// the active collection or profile
+  InUse,
+  Window,
+  Tauri,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[ts(rename = "CommandError")]
+pub struct Error {
+  pub code: ErrorCode,
+  pub message: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  #[ts(optional)]
+  pub details: Option<Value>,
+  /// messages of the underlying errors, outermost first
+  #[serde(default)]
+  pub sources: Vec<String>,
+}
+
+impl Error {
+  pub fn new<S: Into<String>>(code: ErrorCode, message: S) -> Self {
+    Self { code, message: message.into(), details: None, sources: Vec::new() }
+  }
+
+  pub fn with_details(mut self, details: Value) -> Self {
+    self.details = Some(details);
+    self
+  }
+
+  fn from_error<E: std::error::Error>(e: &E, (code, details): (ErrorCode, Option<Value>)) -> Self {
+    let mut sources = Vec::new();
+    let mut source = e.source();
+    while let Some(i) = source {
+      sources.push(i.to_string());
+      source = i.source();
+    }
+    let result = Self { code, message: e.to_string(), details, sources };
+    warn!(ty=std::any::type_name::<E>(), code=?result.code, message=%result.message, "error");
+    result
+  }
+}
+
+impl std::fmt::Display for Error {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.message)
+  }
+}
+
+impl std::error::Error for Error {}
+
+fn classify_io(e: &std::io::Error) -> (ErrorCode, Option<Value>) {
+  let code = match e.kind() {
+    std::io::ErrorKind::NotFound => ErrorCode::NotFound,
+    std::io::ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
+    std::io::ErrorKind::AlreadyExists => ErrorCode::AlreadyExists,
+    _ => ErrorCode::Io,
+  };
+  (code, Some(json!({ "kind": format!("{:?}", e.kind()) })))
+}
+
+fn classify_json(e: &serde_json::Error) -> (ErrorCode, Option<Value>) {
+  let details = json!({ "category": format!("{:?}", e.classify()), "line": e.line(), "column": e.column() });
+  (ErrorCode::Json, Some(details))
+}
+
+fn classify_zip(e: &zip::result::ZipError) -> (ErrorCode, Option<Value>) {
+  match e {
+    zip::result::ZipError::Io(e) => classify_io(e),
+    zip::result::ZipError::FileNotFound => (ErrorCode::NotFound, None),
+    _ => (ErrorCode::Zip, None),
+  }
+}
+
+fn classify_obs(e: &obs::Error) -> (ErrorCode, Option<Value>) {
+  match e {
+    obs::Error::String(_) => (ErrorCode::Utf8, None),
+    obs::Error::CString(e) => (ErrorCode::Nul, Some(json!({ "position": e.nul_position() }))),
+    obs::Error::Json(e) => classify_json(e),
+    obs::Error::Io(e) => classify_io(e),
+    obs::Error::Code(code) => (ErrorCode::ObsCode, Some(json!({ "code": code }))),
+    obs::Error::NulPointer(function) => (ErrorCode::NulPointer, Some(json!({ "function": function }))),
+    obs::Error::Obs(_) => (ErrorCode::Obs, None),
+    obs::Error::SceneCycle { parent, child } => (ErrorCode::SceneCycle, Some(json!({ "parent": parent, "child": child }))),
+    obs::Error::NotAGroup(name) => (ErrorCode::NotAGroup, Some(json!({ "name": name }))),
+    obs::Error::NotInitialized => (ErrorCode::ObsNotInitialized, None),
+    obs::Error::ResetAudio => (ErrorCode::ResetAudio, None),
+    obs::Error::ModuleNotLoaded(module) => (ErrorCode::ModuleNotLoaded, Some(json!({ "module": module }))),
+    obs::Error::Output(_) => (ErrorCode::Output, None),
+    obs::Error::Remux(_) => (ErrorCode::Remux, None),
+    obs::Error::Cancelled => (ErrorCode::Cancelled, None),
+    obs::Error::NoLoopbackDevice => (ErrorCode::NoLoopbackDevice, None),
+  }
+}
+
+fn classify_bundle(e: &bundle::Error) -> (ErrorCode, Option<Value>) {
+  match e {
+    bundle::Error::Io(e) => classify_io(e),
+    bundle::Error::Json(e) => classify_json(e),
+    bundle::Error::Zip(e) => classify_zip(e),
+    bundle::Error::AlreadyExists(path) => (ErrorCode::AlreadyExists, Some(json!({ "path": path }))),
+  }
+}
+
+fn classify_profile(e: &profile::Error) -> (ErrorCode, Option<Value>) {
+  match e {
+    profile::Error::Io(e) => classify_io(e),
+    profile::Error::Json(e) => classify_json(e),
+    profile::Error::Obs(e) => classify_obs(e),
+    profile::Error::ObsRunning => (ErrorCode::ObsRunning, None),
+    profile::Error::NotFound(name) => (ErrorCode::NotFound, Some(json!({ "name": name }))),
+    profile::Error::AlreadyExists(name) => (ErrorCode::AlreadyExists, Some(json!({ "name": name }))),
+    profile::Error::InUse(name) => (ErrorCode::InUse, Some(json!({ "name": name }))),
+  }
+}
+
+impl From<obs::Error> for Error {
+  fn from(e: obs::Error) -> Self {
+    Self::from_error(&e, classify_obs(&e))
+  }
+}
+
+impl From<bundle::Error> for Error {
+  fn from(e: bundle::Error) -> Self {
+    Self::from_error(&e, classify_bundle(&e))
+  }
+}
+
+impl From<profile::Error> for Error {
+  fn from(e: profile::Error) -> Self {
+    Self::from_error(&e, classify_profile(&e))
+  }
+}
+
+impl From<std::io::Error> for Error {
+  fn from(e: std::io::Error) -> Self {
+    Self::from_error(&e, classify_io(&e))
+  }
+}
+
+impl From<serde_json::Error> for Error {
+  fn from(e: serde_json::Error) -> Self {
+    Self::from_error(&e, classify_json(&e))
+  }
+}
+
+impl From<tauri::Error> for Error {
+  fn from(e: tauri::Error) -> Self {
+    Self::from_error(&e, (ErrorCode::Tauri, None))
+  }
+}
+
+impl From<raw_window_handle::HandleError> for Error {
+  fn from(e: raw_window_handle::HandleError) -> Self {
+    Self::from_error(&e, (ErrorCode::Window, None))
+  }
+}
+
+#[test]
+fn test_error() {
+  let e = Error::from(obs::Error::Io(std::io::Error::new(std::io::ErrorKind::NotFound, "Untitled.json")));
+  assert_eq!(e.code, ErrorCode::NotFound);
+  assert_eq!(e.message, "io error: Untitled.json");
+  assert_eq!(e.sources, ["Untitled.json"]);
+  assert_eq!(serde_json::to_value(&e).unwrap(), json!({
+    "code": "not_found",
+    "message": "io error: Untitled.json",
+    "details": {"kind": "NotFound"},
+    "sources": ["Untitled.json"],
+  }));
+
+  let e = Error::from(profile::Error::Obs(obs::Error::SceneCycle { parent: "A".into(), child: "B".into() }));
+  assert_eq!(e.code, ErrorCode::SceneCycle);
+  assert_eq!(e.details, Some(json!({ "parent": "A", "child": "B" })));
+  assert_eq!(Error::from(obs::Error::NotInitialized).code, ErrorCode::ObsNotInitialized);
+  assert_eq!(Error::from(obs::Error::ResetAudio).code, ErrorCode::ResetAudio);
+  let e = Error::from(obs::Error::ModuleNotLoaded("rtmp-services".into()));
+  assert_eq!((e.code, e.details), (ErrorCode::ModuleNotLoaded, Some(json!({ "module": "rtmp-services" }))));
+  let e = Error::from(obs::Error::NotAGroup("Camera".into()));
+  assert_eq!((e.code, e.details), (ErrorCode::NotAGroup, Some(json!({ "name": "Camera" }))));
+  assert_eq!(Error::from(profile::Error::InUse("Untitled".into())).code, ErrorCode::InUse);
+  let e = Error::from(serde_json::from_str::<Value>("{").unwrap_err());
+  assert_eq!((e.code, e.details.unwrap()["category"].as_str()), (ErrorCode::Json, Some("Eof")));
+}
@@ -0,0 +1,162 @@
+//! Log files of the app, one per session like the `logs/` folder of OBS Studio,
+//! holding our tracing output and the libobs lines bridged by `obs::log`.
+//!
+//! ```text
+//! {log_dir}/2024-04-13 10-20-30.txt
+//! {log_dir}/2024-04-13 11-02-45.txt    current session
+//! ```
+
+use std::{fs::File, path::{Path, PathBuf}, sync::OnceLock};
+
+pub const IDENTIFIER: &str = "dev.myobs";
+/// `MaxLogs` default of OBS Studio
+pub const MAX_LOGS: usize = 10;
+pub const EXTENSION: &str = "txt";
+const FILE_NAME_FORMAT: &str = "%Y-%m-%d %H-%M-%S";
+
+static CURRENT: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct LogSession {
+  /// file name, also the start time of the session
+  pub name: String,
+  pub path: PathBuf,
+  #[ts(type = "number")]
+  pub size: u64,
+  pub current: bool,
+}
+
+/// Ordered by severity, a filter keeps the lines at or above it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+  Error,
+  Warn,
+  Info,
+  Debug,
+  Trace,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct LogLine {
+  /// `None` before the first leveled line
+  pub level: Option<LogLevel>,
+  pub text: String,
+}
+
+/// Same folder as `app_log_dir` of tauri, which is only known once the app is built.
+pub fn log_dir() -> PathBuf {
+  #[cfg(target_os = "macos")] {
+    dirs::home_dir().unwrap_or_default().join("Library/Logs").join(IDENTIFIER)
+  }
+  #[cfg(not(target_os = "macos"))] {
+    dirs::data_local_dir().unwrap_or_default().join(IDENTIFIER).join("logs")
+  }
+}
+
+/// Log file of this session, if `init` succeeded.
+pub fn current() -> Option<&'static Path> {
+  CURRENT.get().map(PathBuf::as_path)
+}
+
+/// Create the log file of this session in `dir` and drop the oldest ones beyond [`MAX_LOGS`].
+pub fn init(dir: &Path) -> std::io::Result<File> {
+  let (path, file) = create_session(dir)?;
+  let _ = CURRENT.set(path);
+  Ok(file)
+}
+
+/// [`init`] without making the new file the current one.
+pub fn create_session(dir: &Path) -> std::io::Result<(PathBuf, File)> {
+  std::fs::create_dir_all(dir)?;
+  let name = chrono::Local::now().format(FILE_NAME_FORMAT).to_string();
+  let mut path = dir.join(format!("{name}.{EXTENSION}"));
+  let mut idx = 2;
+  while path.exists() {
+    path = dir.join(format!("{name} ({idx}).{EXTENSION}"));
+    idx += 1;
+  }
+  let file = File::create(&path)?;
+  for old in list_sessions(dir)?.into_iter().filter(|i| i.path != path).skip(MAX_LOGS - 1) {
+    std::fs::remove_file(old.path)?;
+  }
+  Ok((path, file))
+}
+
+/// Log files in `dir`, newest first.
+pub fn list_sessions(dir: &Path) -> std::io::Result<Vec<LogSession>> {
+  let mut result = Vec::new();
+  let read_dir = match std::fs::read_dir(dir) {
+    Ok(read_dir) => read_dir,
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(result),
+    Err(e) => return Err(e),
+  };
+  for i in read_dir {
+    let path = i?.path();
+    if path.extension() != Some(EXTENSION.as_ref()) { continue }
+    result.push(LogSession {
+      name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+      size: std::fs::metadata(&path)?.len(),
+      current: current() == Some(path.as_path()),
+      path,
+    });
+  }
+  // names start with the time
+  result.sort_by(|a, b| b.name.cmp(&a.name));
+  Ok(result)
+}
+
+/// Level of a line written by `tracing_subscriber::fmt`, e.g. `2024-04-13T10:20:30.123456Z  INFO libobs: ...`
+pub fn parse_level(line: &str) -> Option<LogLevel> {
+  line.split_whitespace().take(3).find_map(|token| match token {
+    "ERROR" => Some(LogLevel::Error),
+    "WARN" => Some(LogLevel::Warn),
+    "INFO" => Some(LogLevel::Info),
+    "DEBUG" => Some(LogLevel::Debug),
+    "TRACE" => Some(LogLevel::Trace),
+    _ => None,
+  })
+}
+
+/// Lines of `path` at or above `level`, a line without level (e.g. a multi-line message)
+/// belongs to the line before it.
+pub fn read_log(path: &Path, level: Option<LogLevel>) -> std::io::Result<Vec<LogLine>> {
+  let content = std::fs::read(path)?;
+  let content = String::from_utf8_lossy(&content);
+  let mut current = None;
+  Ok(content.lines().filter_map(|text| {
+    current = parse_level(text).or(current);
+    match (level, current) {
+      (Some(level), Some(current)) if current > level => None,
+      _ => Some(LogLine { level: current, text: text.to_string() }),
+    }
+  }).collect())
+}
+
+#[test]
+fn test_read_log() {
+  let dir = crate::testing::TempDir::new("logs");
+  let path = dir.join("2000-01-01 00-00-00.txt");
+  std::fs::write(&path, "\
+2024-04-13T10:20:30.000000Z  INFO libobs: CPU Name: Apple M1
+2024-04-13T10:20:30.000001Z DEBUG myobs: src/main.rs:10: loading
+2024-04-13T10:20:30.000002Z ERROR myobs: src/main.rs:20: failed
+  caused by: not found
+2024-04-13T10:20:30.000003Z  WARN libobs: missing module
+").unwrap();
+  let lines = read_log(&path, Some(LogLevel::Warn)).unwrap();
+  assert_eq!(lines.iter().map(|i| i.level).collect::<Vec<_>>(), [Some(LogLevel::Error), Some(LogLevel::Error), Some(LogLevel::Warn)]);
+  assert_eq!(lines[1].text, "  caused by: not found");
+  assert_eq!(read_log(&path, None).unwrap().len(), 5);
+  assert_eq!(parse_level("plain text"), None);
+
+  for idx in 0..MAX_LOGS + 2 {
+    std::fs::write(dir.join(format!("2024-04-{:02} 10-00-00.txt", idx + 1)), "").unwrap();
+  }
+  let (path, _) = create_session(&dir).unwrap();
+  let sessions = list_sessions(&dir).unwrap();
+  assert_eq!(sessions.len(), MAX_LOGS);
+  assert_eq!(sessions[0].path, path);
+  assert!(current().is_none());
+  assert!(!sessions.iter().any(|i| i.name == "2000-01-01 00-00-00.txt" || i.name == "2024-04-01 10-00-00.txt"));
+}
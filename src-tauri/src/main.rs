@@ -3,29 +3,26 @@
 
 #[macro_use] extern crate tracing;
 
-use std::{fs::DirEntry, path::Path};
+use std::{fs::DirEntry, path::PathBuf};
 
 use obs_wrapper::{data::DataObj, graphics::display::DisplayRef, media::video::VideoFormat};
 use raw_window_handle::HasWindowHandle;
-use tauri::AppHandle;
+use tauri::{AppHandle, Manager as _};
 
-use crate::obs::Obs;
+use crate::obs::{collection::{self, bundle::{self, ExportReport, ImportReport}, diff::{self, Change, MergeResult}, validate::{self, ValidationReport}, SceneCollection}, Obs};
+use crate::profile::config::AppConfig;
 
+#[cfg(test)]
+mod bindings;
+pub mod error;
+pub mod logs;
 pub mod obs;
+pub mod profile;
+#[cfg(test)]
+mod testing;
 pub mod winit;
 
-pub type Result<T, E=Error> = std::result::Result<T, E>;
-
-#[derive(Debug, serde::Serialize)]
-pub struct Error(String);
-
-impl<T: ToString> From<T> for Error {
-  fn from(e: T) -> Self {
-    let message = e.to_string();
-    warn!(ty=std::any::type_name::<T>(), %message, "error");
-    Self(message)
-  }
-}
+pub use crate::error::{Error, Result};
 
 // Learn more about Tauri commands at https://tauri.app/v1/guides/features/command
 #[tauri::command]
@@ -38,33 +35,52 @@ fn greet(_name: &str) -> String {
 
 #[cfg(target_os = "macos")]
 const OBS_SETTING_FOLDER: &str = "$HOME/Library/Application Support/obs-studio";
+#[cfg(target_os = "linux")]
+const OBS_SETTING_FOLDER: &str = "$HOME/.config/obs-studio";
 
 fn obs_setting_folder() -> String {
   OBS_SETTING_FOLDER.replace("$HOME", &dirs::home_dir().unwrap().display().to_string())
 }
 
+fn setting_dir(folder: Option<&str>) -> PathBuf {
+  PathBuf::from(folder.map(str::to_string).unwrap_or_else(||obs_setting_folder()))
+}
+
 fn ignore_file(i: &DirEntry) -> bool {
   i.file_name().eq_ignore_ascii_case(".DS_Store")
 }
 
-#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize, ts_rs::TS)]
 pub struct KV {
   name: String,
   value: serde_json::Value,
 }
 
-#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize, ts_rs::TS)]
 pub struct ProfileResult {
   pub scenes: Vec<KV>,
   pub profiles: Vec<KV>,
+  /// files failed to read or parse, with the error message as value
+  pub errors: Vec<KV>,
+  /// folder name of the active profile, as in `profiles`
+  pub active_profile: Option<String>,
+  /// file name of the active scene collection, as in `scenes`
+  pub active_scene_collection: Option<String>,
 }
 
 #[tauri::command]
 async fn list_profile(folder: Option<&str>) -> Result<ProfileResult> {
-  let setting_dir = folder.map(str::to_string).unwrap_or_else(||obs_setting_folder());
-  info!(setting_dir);
+  let setting_dir = setting_dir(folder);
+  info!(setting_dir=%setting_dir.display());
   let mut result = ProfileResult::default();
-  if let Ok(read_dir) = std::fs::read_dir(Path::new(&setting_dir).join("basic/profiles")) {
+  match AppConfig::load(&setting_dir) {
+    Ok(config) => {
+      result.active_scene_collection = config.scene_collection_file_name();
+      result.active_profile = config.basic.profile_dir;
+    },
+    Err(e) => warn!(error=%e, "read app config"),
+  }
+  if let Ok(read_dir) = std::fs::read_dir(setting_dir.join("basic/profiles")) {
     for i in read_dir {
       let Ok(i) = i else { continue };
       if ignore_file(&i) { continue }
@@ -74,13 +90,21 @@ async fn list_profile(folder: Option<&str>) -> Result<ProfileResult> {
       });
     }
   }
-  if let Ok(read_dir) = std::fs::read_dir(Path::new(&setting_dir).join("basic/scenes")) {
+  if let Ok(read_dir) = std::fs::read_dir(setting_dir.join("basic/scenes")) {
     for i in read_dir {
       let Ok(i) = i else { continue };
       if ignore_file(&i) { continue }
-      if i.file_name().to_string_lossy().ends_with(".json") || i.file_name().to_string_lossy().ends_with(".json.bak") {
-        let Ok(content) = std::fs::read_to_string(i.path()) else { continue };
-        let Ok(data) = serde_json::from_str(&content) else { continue };
+      // `.json.bak` is only the previous save, see `list_scene_backups` for the history
+      if i.file_name().to_string_lossy().ends_with(".json") {
+        let data = match std::fs::read_to_string(i.path()).map_err(|e| e.to_string())
+          .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string())) {
+          Ok(data) => data,
+          Err(e) => {
+            warn!(file=%i.path().display(), error=e, "invalid scene collection");
+            result.errors.push(KV { name: i.file_name().to_string_lossy().to_string(), value: e.into() });
+            continue
+          },
+        };
         result.scenes.push(KV {
           name: i.file_name().to_string_lossy().to_string(),
           value: data,
@@ -91,6 +115,157 @@ async fn list_profile(folder: Option<&str>) -> Result<ProfileResult> {
   Ok(result)
 }
 
+/// Check a scene collection file without changing it.
+/// Source types are only checked once obs is initialized.
+#[tauri::command]
+fn validate_scene_collection(file: &str) -> Result<ValidationReport> {
+  let collection = SceneCollection::from_file(file)?;
+  let available = Obs::new().ready().then(collection::available_source_types);
+  Ok(validate::validate(&collection, available.as_deref()))
+}
+
+/// Point the missing files of a scene collection to files of the same name under `dir`,
+/// and save it if any was found. Refused while OBS Studio runs, it would overwrite the file.
+#[tauri::command]
+fn relink_scene_collection(file: &str, dir: &str) -> Result<Vec<validate::Relinked>> {
+  profile::ensure_obs_not_running()?;
+  let mut collection = SceneCollection::from_file(file)?;
+  let relinked = validate::relink(&mut collection, dir);
+  if !relinked.is_empty() {
+    profile::backup::save_json(std::path::Path::new(file), &serde_json::to_string_pretty(&collection)?, &Default::default())?;
+  }
+  Ok(relinked)
+}
+
+#[tauri::command]
+async fn diff_scene_collection(old: &str, new: &str) -> Result<Vec<Change>> {
+  let old = SceneCollection::from_file(old)?;
+  let new = SceneCollection::from_file(new)?;
+  Ok(diff::diff(&old, &new))
+}
+
+/// Merge `ours` and `theirs` against their common ancestor `base`,
+/// and write the result to `output` if given, even with conflicts (resolved to `ours`).
+#[tauri::command]
+async fn merge_scene_collection(base: &str, ours: &str, theirs: &str, output: Option<&str>) -> Result<MergeResult> {
+  let base = SceneCollection::from_file(base)?;
+  let ours = SceneCollection::from_file(ours)?;
+  let theirs = SceneCollection::from_file(theirs)?;
+  let result = diff::merge(&base, &ours, &theirs)?;
+  if let Some(output) = output {
+    profile::backup::save_json(std::path::Path::new(output), &serde_json::to_string_pretty(&result.merged)?, &Default::default())?;
+  }
+  Ok(result)
+}
+
+/// Pack the scene collection `name` (as listed by `list_profile`) with its files into `bundle`.
+#[tauri::command]
+async fn export_scene_bundle(name: &str, bundle: &str, folder: Option<&str>) -> Result<ExportReport> {
+  let collection = SceneCollection::from_file(setting_dir(folder).join("basic/scenes").join(name))?;
+  Ok(bundle::export_bundle(&collection, bundle)?)
+}
+
+/// Unpack `bundle` into `target_dir`, and add its collection to the OBS setting folder.
+/// Fails with `already_exists` instead of replacing a collection or files unless `overwrite`.
+#[tauri::command]
+async fn import_scene_bundle(bundle: &str, target_dir: &str, overwrite: Option<bool>, folder: Option<&str>) -> Result<ImportReport> {
+  let scenes_dir = setting_dir(folder).join("basic/scenes");
+  let report = bundle::import_bundle(bundle, target_dir, Some(&scenes_dir), overwrite.unwrap_or(false))?;
+  profile::backup::snapshot(&report.collection, &Default::default())?;
+  Ok(report)
+}
+
+#[tauri::command]
+async fn create_scene_collection(name: &str, folder: Option<&str>) -> Result<PathBuf> {
+  Ok(profile::create_scene_collection(&setting_dir(folder), name)?)
+}
+
+#[tauri::command]
+async fn duplicate_scene_collection(name: &str, new_name: &str, folder: Option<&str>) -> Result<PathBuf> {
+  Ok(profile::duplicate_scene_collection(&setting_dir(folder), name, new_name)?)
+}
+
+#[tauri::command]
+async fn rename_scene_collection(name: &str, new_name: &str, folder: Option<&str>) -> Result<PathBuf> {
+  Ok(profile::rename_scene_collection(&setting_dir(folder), name, new_name)?)
+}
+
+#[tauri::command]
+async fn delete_scene_collection(name: &str, folder: Option<&str>) -> Result<()> {
+  Ok(profile::delete_scene_collection(&setting_dir(folder), name)?)
+}
+
+#[tauri::command]
+async fn create_profile(name: &str, folder: Option<&str>) -> Result<PathBuf> {
+  Ok(profile::create_profile(&setting_dir(folder), name)?)
+}
+
+#[tauri::command]
+async fn duplicate_profile(name: &str, new_name: &str, folder: Option<&str>) -> Result<PathBuf> {
+  Ok(profile::duplicate_profile(&setting_dir(folder), name, new_name)?)
+}
+
+#[tauri::command]
+async fn rename_profile(name: &str, new_name: &str, folder: Option<&str>) -> Result<PathBuf> {
+  Ok(profile::rename_profile(&setting_dir(folder), name, new_name)?)
+}
+
+#[tauri::command]
+async fn delete_profile(name: &str, folder: Option<&str>) -> Result<()> {
+  Ok(profile::delete_profile(&setting_dir(folder), name)?)
+}
+
+#[tauri::command]
+async fn list_scene_backups(name: &str, folder: Option<&str>) -> Result<Vec<profile::backup::Snapshot>> {
+  let path = profile::find_scene_collection(&setting_dir(folder), name)?;
+  Ok(profile::backup::list_snapshots(&path)?)
+}
+
+#[tauri::command]
+async fn backup_scene_collection(name: &str, folder: Option<&str>) -> Result<u64> {
+  let path = profile::find_scene_collection(&setting_dir(folder), name)?;
+  Ok(profile::backup::snapshot(&path, &Default::default())?)
+}
+
+/// Save the live scenes of OBS into the collection `name` and snapshot it, returns the id of the snapshot.
+#[tauri::command]
+fn save_scene_collection(name: &str, folder: Option<&str>) -> Result<u64> {
+  let obs = Obs::new();
+  if !obs.ready() {
+    return Err(obs::Error::NotInitialized.into())
+  }
+  let path = profile::find_scene_collection(&setting_dir(folder), name)?;
+  Ok(profile::backup::save(&obs, &path, &Default::default())?)
+}
+
+#[tauri::command]
+async fn restore_scene_backup(name: &str, id: u64, folder: Option<&str>) -> Result<()> {
+  profile::ensure_obs_not_running()?;
+  let path = profile::find_scene_collection(&setting_dir(folder), name)?;
+  Ok(profile::backup::restore(&path, id, &Default::default())?)
+}
+
+#[derive(Default)]
+struct ProfileWatcherState(std::sync::Mutex<Option<profile::watch::ProfileWatcher>>);
+
+/// Emit `collection-added`, `collection-changed`, `collection-removed` and `profile-changed` events
+/// on changes in the OBS setting folder, replacing the previous watcher.
+#[tauri::command]
+fn watch_profile(app: AppHandle, state: tauri::State<'_, ProfileWatcherState>, folder: Option<&str>) -> Result<()> {
+  let watcher = profile::watch::ProfileWatcher::new(setting_dir(folder), move |event| {
+    if let Err(e) = app.emit(event.name(), &event) {
+      warn!(error=%e, "failed to emit profile event");
+    }
+  })?;
+  *state.0.lock().unwrap() = Some(watcher);
+  Ok(())
+}
+
+#[tauri::command]
+fn unwatch_profile(state: tauri::State<'_, ProfileWatcherState>) {
+  state.0.lock().unwrap().take();
+}
+
 fn init_obs() -> Result<Obs, obs::Error> {
   // https://github.com/lulzsun/libobs-sharp/blob/main/libobs-sharp.example/Program.cs
   // https://github.com/eyalcohen4/obs-headless-poc/blob/master/src/main.cpp
@@ -99,13 +274,25 @@ fn init_obs() -> Result<Obs, obs::Error> {
   let mut obs = Obs::new();
   info!(obs_initalized=obs.ready());
   if !obs.ready() {
+    obs::log::set_log_handler();
+    obs::log::set_crash_handler();
     obs.init("en_US")?;
     info!(obs_initalized=obs.ready());
     obs.add_default_module_path("~/Applications/OBS-test.app/Contents")?;
     obs.add_default_module_path("~/Library/Application Support/obs-studio")?;
+    #[cfg(target_os = "linux")]
+    obs.add_default_module_path("/usr")?;
     let module = obs.load_modules([
+      #[cfg(target_os = "macos")]
       "mac-capture",
+      // the virtual camera
+      #[cfg(target_os = "linux")]
+      "linux-v4l2",
       "image-source",
+      "obs-x264",
+      "obs-ffmpeg",
+      "obs-outputs",
+      "rtmp-services",
     ])?;
     //obs_scene_add
     info!(?module, "module loaded");
@@ -121,6 +308,7 @@ fn init_obs() -> Result<Obs, obs::Error> {
     .with_output_format(VideoFormat::I420);
   obs.set_channel_source(0, None);
   obs.reset_video(video_info)?;
+  obs.reset_audio(obs::AudioSetting::new())?;
 
   let scene = obs.create_scene("main")?;
   debug!(?obs, scene=?scene.as_source());
@@ -171,17 +359,384 @@ fn create_display(app: AppHandle) -> Result<()> {
   Ok(())
 }
 
+/// Outputs of the app, they are not `Send` like `Obs`,
+/// but every command using them is sync and so runs on the main thread.
+#[derive(Debug, Default)]
+struct Outputs {
+  recording: Option<obs::output::Recording>,
+  stream: Option<obs::output::stream::Streaming>,
+  replay_buffer: Option<obs::output::replay::ReplayBuffer>,
+  virtualcam: Option<obs::output::virtualcam::VirtualCam>,
+}
+unsafe impl Send for Outputs {}
+
+#[derive(Default)]
+struct OutputsState(std::sync::Mutex<Outputs>);
+
+/// Start a new recording, `recording-file-finished` is emitted for every file it completes,
+/// which is then remuxed if `setting.remux` is set.
+/// Returns the path of the first file.
+#[tauri::command]
+fn start_recording(app: AppHandle, state: tauri::State<'_, OutputsState>, setting: obs::output::RecordingSetting) -> Result<PathBuf> {
+  let mut outputs = state.0.lock().unwrap();
+  if let Some(recording) = outputs.recording.as_ref().filter(|i| i.state() != obs::output::OutputState::Stopped) {
+    return Err(obs::Error::Output(format!("already recording to {}", recording.path().display())).into())
+  }
+  // the previous recording is released before its name is used again
+  outputs.recording = None;
+  let mut recording = Obs::new().create_recording("recording", &setting)?;
+  recording.on_event(move |event| {
+    if let Err(e) = app.emit(event.name(), &event) {
+      warn!(error=%e, "failed to emit output event");
+    }
+    let obs::output::OutputEvent::FileFinished { path, .. } = event else { return };
+    match setting.remux {
+      Some(format) if path.extension() != Some(format.extension().as_ref()) => {
+        let output = obs::remux::remux_path(&path, format);
+        start_remux(&app, path, output);
+      },
+      _ => {},
+    }
+  })?;
+  recording.start()?;
+  let path = recording.path();
+  outputs.recording = Some(recording);
+  Ok(path)
+}
+
+#[tauri::command]
+fn stop_recording(state: tauri::State<'_, OutputsState>) {
+  if let Some(recording) = state.0.lock().unwrap().recording.as_mut() {
+    recording.stop();
+  }
+}
+
+fn with_recording<T, F: FnOnce(&mut obs::output::Recording) -> obs::Result<T>>(state: &OutputsState, f: F) -> Result<T> {
+  let mut outputs = state.0.lock().unwrap();
+  let recording = outputs.recording.as_mut().ok_or_else(|| obs::Error::Output("not recording".to_string()))?;
+  Ok(f(recording)?)
+}
+
+#[tauri::command]
+fn pause_recording(state: tauri::State<'_, OutputsState>) -> Result<()> {
+  with_recording(&state, |i| i.pause())
+}
+
+#[tauri::command]
+fn resume_recording(state: tauri::State<'_, OutputsState>) -> Result<()> {
+  with_recording(&state, |i| i.resume())
+}
+
+/// Finish the current file and continue in a new one, the recording should be started with `split`.
+#[tauri::command]
+fn split_recording(state: tauri::State<'_, OutputsState>) -> Result<()> {
+  with_recording(&state, |i| i.split())
+}
+
+#[tauri::command]
+fn get_recording_state(state: tauri::State<'_, OutputsState>) -> obs::output::OutputState {
+  let outputs = state.0.lock().unwrap();
+  outputs.recording.as_ref().map_or(obs::output::OutputState::Stopped, |i| i.state())
+}
+
+/// Connect to the server of `setting.service` and start streaming,
+/// the connection is reported by `output-*` events.
+#[tauri::command]
+fn start_stream(app: AppHandle, state: tauri::State<'_, OutputsState>, setting: obs::output::stream::StreamSetting) -> Result<()> {
+  let mut outputs = state.0.lock().unwrap();
+  if let Some(stream) = outputs.stream.as_ref().filter(|i| i.state() != obs::output::OutputState::Stopped) {
+    return Err(obs::Error::Output(format!("already streaming to {}", stream.server())).into())
+  }
+  // the previous stream is released before its name is used again
+  outputs.stream = None;
+  let mut stream = Obs::new().create_stream("stream", &setting)?;
+  stream.on_event(move |event| {
+    if let Err(e) = app.emit(event.name(), &event) {
+      warn!(error=%e, "failed to emit output event");
+    }
+  })?;
+  stream.start()?;
+  outputs.stream = Some(stream);
+  Ok(())
+}
+
+#[tauri::command]
+fn stop_stream(state: tauri::State<'_, OutputsState>) {
+  if let Some(stream) = state.0.lock().unwrap().stream.as_mut() {
+    stream.stop();
+  }
+}
+
+#[tauri::command]
+fn get_stream_state(state: tauri::State<'_, OutputsState>) -> obs::output::OutputState {
+  let outputs = state.0.lock().unwrap();
+  outputs.stream.as_ref().map_or(obs::output::OutputState::Stopped, |i| i.state())
+}
+
+/// Tauri event a game client emits to save the replay buffer, see [`ReplayHook`].
+const SAVE_REPLAY_REQUEST: &str = "save-replay-request";
+
+/// Saves the running replay buffer from any thread, unlike `Outputs`.
+#[derive(Default)]
+struct ReplayHook(std::sync::Mutex<Option<obs::output::replay::ReplaySaveHandle>>);
+
+impl ReplayHook {
+  fn save(&self) -> obs::Result<()> {
+    let handle = self.0.lock().unwrap().clone();
+    handle.ok_or_else(|| obs::Error::Output("the replay buffer is not started".to_string()))?.save()
+  }
+}
+
+/// Start buffering the last `setting.max_time_sec`, `replay-saved` is emitted for every saved replay.
+#[tauri::command]
+fn start_replay_buffer(app: AppHandle, state: tauri::State<'_, OutputsState>, hook: tauri::State<'_, ReplayHook>, setting: obs::output::replay::ReplayBufferSetting) -> Result<()> {
+  let mut outputs = state.0.lock().unwrap();
+  if outputs.replay_buffer.as_ref().is_some_and(|i| i.state() != obs::output::OutputState::Stopped) {
+    return Err(obs::Error::Output("the replay buffer is already started".to_string()).into())
+  }
+  outputs.replay_buffer = None;
+  let mut replay_buffer = Obs::new().create_replay_buffer("replay buffer", &setting)?;
+  replay_buffer.on_event(move |event| {
+    if let Err(e) = app.emit(event.name(), &event) {
+      warn!(error=%e, "failed to emit output event");
+    }
+  })?;
+  replay_buffer.start()?;
+  *hook.0.lock().unwrap() = Some(replay_buffer.save_handle());
+  outputs.replay_buffer = Some(replay_buffer);
+  Ok(())
+}
+
+#[tauri::command]
+fn stop_replay_buffer(state: tauri::State<'_, OutputsState>, hook: tauri::State<'_, ReplayHook>) {
+  hook.0.lock().unwrap().take();
+  if let Some(replay_buffer) = state.0.lock().unwrap().replay_buffer.as_mut() {
+    replay_buffer.stop();
+  }
+}
+
+/// Write the buffer into a new file, its path is emitted with `replay-saved`.
+#[tauri::command]
+async fn save_replay_buffer(hook: tauri::State<'_, ReplayHook>) -> Result<()> {
+  Ok(hook.save()?)
+}
+
+#[tauri::command]
+fn get_replay_buffer_state(state: tauri::State<'_, OutputsState>) -> obs::output::OutputState {
+  let outputs = state.0.lock().unwrap();
+  outputs.replay_buffer.as_ref().map_or(obs::output::OutputState::Stopped, |i| i.state())
+}
+
+/// Show `source` in the v4l2loopback camera, replacing the source of a running one.
+#[tauri::command]
+fn start_virtualcam(state: tauri::State<'_, OutputsState>, source: obs::output::virtualcam::VirtualCamSource) -> Result<()> {
+  let mut outputs = state.0.lock().unwrap();
+  if let Some(virtualcam) = outputs.virtualcam.as_mut() {
+    virtualcam.stop();
+  }
+  outputs.virtualcam = None;
+  let mut virtualcam = Obs::new().create_virtualcam("virtualcam", &source)?;
+  virtualcam.start()?;
+  outputs.virtualcam = Some(virtualcam);
+  Ok(())
+}
+
+#[tauri::command]
+fn stop_virtualcam(state: tauri::State<'_, OutputsState>) {
+  if let Some(virtualcam) = state.0.lock().unwrap().virtualcam.as_mut() {
+    virtualcam.stop();
+  }
+}
+
+#[tauri::command]
+fn get_virtualcam_state(state: tauri::State<'_, OutputsState>) -> obs::output::OutputState {
+  let outputs = state.0.lock().unwrap();
+  outputs.virtualcam.as_ref().map_or(obs::output::OutputState::Stopped, |i| i.state())
+}
+
+/// `services.json` of `rtmp-services`, the services and servers of `rtmp_common` with their recommendations.
+#[tauri::command]
+fn list_stream_services() -> Result<obs::output::services::Services> {
+  let obs = Obs::new();
+  if !obs.ready() {
+    return Err(obs::Error::NotInitialized.into())
+  }
+  Ok(obs.stream_services()?)
+}
+
+/// Where the stream of the profile `name` does not follow the recommendations of its service,
+/// empty for a custom server.
+#[tauri::command]
+fn validate_profile_service(name: &str, folder: Option<&str>) -> Result<Vec<obs::output::services::ServiceIssue>> {
+  let obs = Obs::new();
+  if !obs.ready() {
+    return Err(obs::Error::NotInitialized.into())
+  }
+  let check = profile::stream_check(&setting_dir(folder), name)?;
+  Ok(obs.stream_services()?.validate(&check))
+}
+
+#[derive(Default)]
+struct RemuxState(std::sync::Mutex<obs::remux::RemuxJobs>);
+
+fn start_remux(app: &AppHandle, input: PathBuf, output: PathBuf) -> u64 {
+  let emitter = app.clone();
+  app.state::<RemuxState>().0.lock().unwrap().start(input, output, move |event| {
+    if let Err(e) = emitter.emit(event.name(), &event) {
+      warn!(error=%e, "failed to emit remux event");
+    }
+  })
+}
+
+/// Remux the recording `input` into `output`, next to `input` with the extension of `format` by default.
+/// Returns the id of the job, its progress and result are emitted as `remux-*` events.
+#[tauri::command]
+fn remux_recording(app: AppHandle, input: &str, output: Option<&str>, format: Option<obs::remux::RemuxFormat>) -> Result<u64> {
+  let input = PathBuf::from(input);
+  if !input.is_file() {
+    return Err(std::io::Error::new(std::io::ErrorKind::NotFound, input.display().to_string()).into())
+  }
+  let output = match output {
+    Some(output) => PathBuf::from(output),
+    None => obs::remux::remux_path(&input, format.unwrap_or_default()),
+  };
+  Ok(start_remux(&app, input, output))
+}
+
+/// Returns false if the job already finished.
+#[tauri::command]
+fn cancel_remux(state: tauri::State<'_, RemuxState>, id: u64) -> bool {
+  state.0.lock().unwrap().cancel(id)
+}
+
+#[tauri::command]
+async fn list_log_sessions() -> Result<Vec<logs::LogSession>> {
+  Ok(logs::list_sessions(&logs::log_dir())?)
+}
+
+/// Lines of the log file `name` (as listed by `list_log_sessions`) at or above `level`.
+#[tauri::command]
+async fn read_log(name: &str, level: Option<logs::LogLevel>) -> Result<Vec<logs::LogLine>> {
+  // only files of the log folder
+  let file_name = std::path::Path::new(name).file_name().filter(|i| *i == name);
+  let file_name = file_name.ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, name.to_string()))?;
+  Ok(logs::read_log(&logs::log_dir().join(file_name), level)?)
+}
+
+/// Copy the log file of this session to `target`, or into it if it is a folder.
+#[tauri::command]
+async fn export_current_log(target: &str) -> Result<PathBuf> {
+  let current = logs::current().ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no log file for this session"))?;
+  let mut target = PathBuf::from(target);
+  if target.is_dir() {
+    target = target.join(current.file_name().unwrap_or_default());
+  }
+  std::fs::copy(current, &target)?;
+  Ok(target)
+}
+
+/// stdout, and the log file of this session without colors
+fn init_tracing() {
+  use tracing_subscriber::{filter::LevelFilter, fmt, prelude::*};
+  let file = logs::init(&logs::log_dir()).inspect_err(|e| eprintln!("failed to create log file: {e}")).ok();
+  tracing_subscriber::registry()
+    .with(LevelFilter::DEBUG)
+    .with(fmt::layer().with_file(true).with_line_number(true).compact())
+    .with(file.map(|file| fmt::layer().with_ansi(false).with_file(true).with_line_number(true).compact().with_writer(std::sync::Arc::new(file))))
+    .init();
+}
+
+/// Pass the name of every command to the macro `$callback`, the single list behind
+/// the handler of `main` and the checked names of `bindings`.
+macro_rules! with_commands {
+  ($($callback:tt)*) => {
+    $($callback)*![
+      greet,
+      list_profile,
+      validate_scene_collection,
+      relink_scene_collection,
+      diff_scene_collection,
+      merge_scene_collection,
+      export_scene_bundle,
+      import_scene_bundle,
+      create_scene_collection,
+      duplicate_scene_collection,
+      rename_scene_collection,
+      delete_scene_collection,
+      create_profile,
+      duplicate_profile,
+      rename_profile,
+      delete_profile,
+      list_scene_backups,
+      backup_scene_collection,
+      save_scene_collection,
+      restore_scene_backup,
+      watch_profile,
+      unwatch_profile,
+      list_log_sessions,
+      read_log,
+      export_current_log,
+      start_recording,
+      stop_recording,
+      pause_recording,
+      resume_recording,
+      split_recording,
+      get_recording_state,
+      remux_recording,
+      cancel_remux,
+      start_stream,
+      stop_stream,
+      get_stream_state,
+      start_replay_buffer,
+      stop_replay_buffer,
+      save_replay_buffer,
+      get_replay_buffer_state,
+      start_virtualcam,
+      stop_virtualcam,
+      get_virtualcam_state,
+      list_stream_services,
+      validate_profile_service,
+      create_display,
+    ]
+  };
+}
+
+#[cfg(test)]
+macro_rules! command_names {
+  ($($name:ident),* $(,)?) => { &[$(stringify!($name)),*] };
+}
+
+/// as registered in `main`
+#[cfg(test)]
+const COMMANDS: &[&str] = with_commands!(command_names);
+
 fn main() {
-  tracing_subscriber::fmt().with_max_level(tracing::Level::DEBUG).with_file(true).with_line_number(true).compact().init();
+  init_tracing();
 
   tauri::Builder::default()
     .plugin(tauri_plugin_shell::init())
     .plugin(tauri_plugin_store::Builder::default().build())
-    .invoke_handler(tauri::generate_handler![
-      greet,
-      list_profile,
-      create_display,
-    ])
-    .run(tauri::generate_context!())
-    .expect("error while running tauri application");
+    .manage(ProfileWatcherState::default())
+    .manage(OutputsState::default())
+    .manage(RemuxState::default())
+    .manage(ReplayHook::default())
+    .setup(|app| {
+      // the game side asks for a replay without waiting for the result, it gets `replay-saved`
+      let handle = app.handle().clone();
+      app.listen_any(SAVE_REPLAY_REQUEST, move |_| {
+        if let Err(e) = handle.state::<ReplayHook>().save() {
+          warn!(error=%e, "failed to save the replay buffer");
+        }
+      });
+      Ok(())
+    })
+    .invoke_handler(with_commands!(tauri::generate_handler))
+    .build(tauri::generate_context!())
+    .expect("error while building tauri application")
+    .run(|app, event| {
+      // a remux killed halfway leaves a broken output file
+      if let tauri::RunEvent::Exit = event {
+        app.state::<RemuxState>().0.lock().unwrap().cancel_all();
+      }
+    });
 }
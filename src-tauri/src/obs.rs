@@ -1,10 +1,18 @@
 pub mod settings;
 pub mod display;
+pub mod layout;
+pub mod scene;
+pub mod collection;
+pub mod log;
+pub mod output;
+pub mod remux;
+pub mod raw_video;
+pub mod raw_audio;
 
 use std::{ffi::{CStr, CString}, path::Path};
 
 use obs_wrapper::{
-  data::DataObj, graphics::display::{Color, DisplayRef}, media::video::VideoFormat, module::ModuleRef, obs_sys::{obs_add_data_path, obs_add_module_path, obs_add_safe_module, obs_display_create, obs_get_module, obs_get_output_source, obs_get_version_string, obs_initialized, obs_load_all_modules, obs_post_load_modules, obs_reset_video, obs_scene_create, obs_set_output_source, obs_source_create, obs_startup, obs_video_info, MAX_CHANNELS, OBS_VIDEO_SUCCESS}, source::{scene::SceneRef, SourceRef}, string::TryIntoObsString as _, wrapper::PtrWrapper as _
+  data::DataObj, graphics::display::{Color, DisplayRef}, media::video::VideoFormat, module::ModuleRef, obs_sys::{obs_add_data_path, obs_add_module_path, obs_add_safe_module, obs_display_create, obs_get_module, obs_get_output_source, obs_get_version_string, obs_initialized, obs_load_all_modules, obs_post_load_modules, obs_reset_audio, obs_reset_video, obs_scene_create, obs_set_output_source, obs_source_create, obs_source_get_name, obs_startup, obs_audio_info, obs_video_info, speaker_layout, MAX_CHANNELS, OBS_VIDEO_SUCCESS, SPEAKERS_STEREO}, source::{scene::SceneRef, SourceRef}, string::TryIntoObsString as _, wrapper::PtrWrapper as _
 };
 
 use self::display::DisplayInitInfo;
@@ -19,12 +27,32 @@ pub enum Error {
   CString(#[from] std::ffi::NulError),
   #[error("convert json error")]
   Json(#[from] serde_json::Error),
+  #[error("io error: {0}")]
+  Io(#[from] std::io::Error),
   #[error("error code: {0}")]
   Code(i32),
   #[error("ffi error: {0}")]
   NulPointer(&'static str),
   #[error("obs error: {0}")]
   Obs(#[from] obs_wrapper::Error),
+  #[error("nesting scene `{child}` into `{parent}` would create a cycle")]
+  SceneCycle { parent: String, child: String },
+  #[error("scene item `{0}` is not a group")]
+  NotAGroup(String),
+  #[error("obs is not initialized")]
+  NotInitialized,
+  #[error("failed to reset audio, it cannot change while an output is active")]
+  ResetAudio,
+  #[error("module `{0}` is not loaded")]
+  ModuleNotLoaded(String),
+  #[error("output error: {0}")]
+  Output(String),
+  #[error("remux error: {0}")]
+  Remux(String),
+  #[error("cancelled")]
+  Cancelled,
+  #[error("no v4l2loopback device, load it with `sudo modprobe v4l2loopback exclusive_caps=1 card_label=\"OBS Virtual Camera\"`")]
+  NoLoopbackDevice,
 }
 
 macro_rules! try_with {
@@ -42,6 +70,12 @@ macro_rules! try_with {
 #[derive(Debug)]
 pub struct Obs {
   scenes: Vec<SceneRef>,
+  sources: Vec<SourceRef>,
+  transitions: Vec<SourceRef>,
+  /// milliseconds of a scene switch through the program transition
+  transition_duration: u32,
+  /// the loaded scene collection, without sources
+  collection: Option<collection::SceneCollection>,
   marker: std::marker::PhantomData<*mut std::ffi::c_void>,
 }
 
@@ -111,31 +145,71 @@ impl VideoSetting {
     self
   }
 
+  /// canvas size, in which scene items are laid out
+  pub fn base_size(&self) -> (u32, u32) {
+    (self.0.base_width, self.0.base_height)
+  }
+
   pub fn with_output_format(mut self, format: VideoFormat) -> Self {
-    self.0.output_format = match format {
-      VideoFormat::None => 0,
-      VideoFormat::I420 => 1,
-      VideoFormat::NV12 => 2,
-      VideoFormat::YVYU => 3,
-      VideoFormat::YUY2 => 4,
-      VideoFormat::UYVY => 5,
-      VideoFormat::RGBA => 6,
-      VideoFormat::BGRA => 7,
-      VideoFormat::BGRX => 8,
-      VideoFormat::Y800 => 9,
-      VideoFormat::I444 => 10,
-      VideoFormat::BGR3 => 11,
-      VideoFormat::I422 => 12,
-      VideoFormat::I40A => 13,
-      VideoFormat::I42A => 14,
-      VideoFormat::YUVA => 15,
-      VideoFormat::AYUV => 16,
-      VideoFormat::I010 => todo!(),
-      VideoFormat::P010 => todo!(),
-      VideoFormat::I210 => todo!(),
-      VideoFormat::I412 => todo!(),
-      VideoFormat::YA2L => todo!(),
-    };
+    self.0.output_format = raw_video_format(format) as _;
+    self
+  }
+}
+
+/// `enum video_format` of `media-io/video-io.h` by value, with the names of `get_video_format_name`
+pub const VIDEO_FORMATS: [(VideoFormat, &str); 22] = [
+  (VideoFormat::None, "None"),
+  (VideoFormat::I420, "I420"),
+  (VideoFormat::NV12, "NV12"),
+  (VideoFormat::YVYU, "YVYU"),
+  (VideoFormat::YUY2, "YUY2"),
+  (VideoFormat::UYVY, "UYVY"),
+  (VideoFormat::RGBA, "RGBA"),
+  (VideoFormat::BGRA, "BGRA"),
+  (VideoFormat::BGRX, "BGRX"),
+  (VideoFormat::Y800, "Y800"),
+  (VideoFormat::I444, "I444"),
+  (VideoFormat::BGR3, "BGR3"),
+  (VideoFormat::I422, "I422"),
+  (VideoFormat::I40A, "I40A"),
+  (VideoFormat::I42A, "I42A"),
+  (VideoFormat::YUVA, "YUVA"),
+  (VideoFormat::AYUV, "AYUV"),
+  (VideoFormat::I010, "I010"),
+  (VideoFormat::P010, "P010"),
+  (VideoFormat::I210, "I210"),
+  (VideoFormat::I412, "I412"),
+  (VideoFormat::YA2L, "YA2L"),
+];
+
+/// Value of `format` in `enum video_format`.
+pub fn raw_video_format(format: VideoFormat) -> u32 {
+  VIDEO_FORMATS.iter().position(|(i, _)| *i == format).unwrap_or_default() as u32
+}
+
+/// Reverse of [`raw_video_format`], unknown values are [`VideoFormat::None`].
+pub fn video_format_from_raw(value: u32) -> VideoFormat {
+  VIDEO_FORMATS.get(value as usize).map_or(VideoFormat::None, |(i, _)| *i)
+}
+
+pub struct AudioSetting(obs_audio_info);
+
+impl AudioSetting {
+  /// 48kHz stereo, the default of OBS Studio
+  pub fn new() -> Self {
+    Self(obs_audio_info {
+      samples_per_sec: 48000,
+      speakers: SPEAKERS_STEREO,
+    })
+  }
+
+  pub fn with_samples_per_sec(mut self, value: u32) -> Self {
+    self.0.samples_per_sec = value;
+    self
+  }
+
+  pub fn with_speakers(mut self, value: speaker_layout) -> Self {
+    self.0.speakers = value;
     self
   }
 }
@@ -146,6 +220,10 @@ impl Obs {
     // Obs is not `Send`
     Self {
       scenes: Vec::new(),
+      sources: Vec::new(),
+      transitions: Vec::new(),
+      transition_duration: collection::DEFAULT_TRANSITION_DURATION,
+      collection: None,
       marker: std::marker::PhantomData::default(),
     }
   }
@@ -226,10 +304,10 @@ impl Obs {
     }
     #[cfg(target_os = "linux")] {
       let bin_dir = format!("{}/{}", prefix, "lib/obs-plugins");
-      let bin_dir = format!("{}/{}", prefix, "share/obs/obs-plugins/%module%");
+      let data_dir = format!("{}/{}", prefix, "share/obs/obs-plugins/%module%");
       self.add_module_path(bin_dir, data_dir)?;
       let bin_dir = format!("{}/{}", prefix, "obs-plugins/64bit");
-      let bin_dir = format!("{}/{}", prefix, "data/obs-plugins/%module%");
+      let data_dir = format!("{}/{}", prefix, "data/obs-plugins/%module%");
       self.add_module_path(bin_dir, data_dir)?;
     }
     Ok(())
@@ -277,6 +355,15 @@ impl Obs {
     try_with!{ obs_reset_video(&mut info.0) }
   }
 
+  /// Audio has to be reset once before any output could be encoded,
+  /// later calls fail while an output is active.
+  pub fn reset_audio(&mut self, info: AudioSetting) -> Result<()> {
+    match unsafe { obs_reset_audio(&info.0) } {
+      true => Ok(()),
+      false => Err(Error::ResetAudio),
+    }
+  }
+
   pub fn create_scene(&mut self, name: &str) -> Result<SceneRef> {
     let name_c = CString::new(name.to_string()).unwrap();
     let scene = unsafe {
@@ -287,6 +374,14 @@ impl Obs {
     Ok(scene)
   }
 
+  pub fn scenes(&self) -> &[SceneRef] {
+    &self.scenes
+  }
+
+  pub fn find_scene(&self, name: &str) -> Option<SceneRef> {
+    self.scenes.iter().find(|i| source_name(&i.as_source()) == name).cloned()
+  }
+
   pub fn create_source(&mut self, name: &str, type_: &str, settings: DataObj) -> Result<SourceRef> {
     let name = CString::new(name.to_string())?;
     let type_ = CString::new(type_.to_string())?;
@@ -319,6 +414,9 @@ impl Obs {
   }
 
   pub fn create_display(&mut self, info: &DisplayInitInfo, color: Color) -> Result<DisplayRef> {
+    if !self.ready() {
+      return Err(Error::NotInitialized)
+    }
     let color = color.as_format(info.color_format());
     unsafe {
       let ptr = obs_display_create(&info.inner as *const display::sys::gs_init_data as *const _, color);
@@ -326,3 +424,11 @@ impl Obs {
     }
   }
 }
+
+pub fn source_name(source: &SourceRef) -> String {
+  unsafe {
+    let name = obs_source_get_name(source.as_ptr_mut());
+    if name.is_null() { return String::new() }
+    CStr::from_ptr(name).to_string_lossy().to_string()
+  }
+}
@@ -0,0 +1,551 @@
+//! Scene collection, the json files in `basic/scenes` of the OBS setting folder.
+//!
+//! see `UI/window-basic-main.cpp`
+//! ```c
+//! void OBSBasic::Save(const char *file)
+//! void OBSBasic::Load(const char *file, bool remigrate)
+//! ```
+//! Fields not modeled here are kept in `extra`, so a collection survives a round trip.
+
+pub mod validate;
+pub mod diff;
+pub mod bundle;
+
+use std::{ffi::{c_void, CStr, CString}, io::Write as _, path::{Path, PathBuf}};
+
+use obs_wrapper::{data::DataObj, obs_sys::{obs_data_array_count, obs_data_array_item, obs_data_array_release, obs_data_get_array, obs_data_get_json, obs_data_release, obs_data_t, obs_enum_source_types, obs_load_sources, obs_save_sources, obs_scene_from_source, obs_source_create_private, obs_source_get_id, obs_source_get_settings, obs_source_get_type, obs_source_t, obs_transition_get_active_source, obs_transition_set, obs_transition_start, OBS_SOURCE_TYPE_TRANSITION, OBS_TRANSITION_MODE_AUTO}, source::{scene::SceneRef, SourceRef}, wrapper::PtrWrapper as _};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use super::{layout::{Transform, Vec2}, source_name, Error, Obs, Result};
+
+pub const SCENE_ID: &str = "scene";
+pub const GROUP_ID: &str = "group";
+/// `transition_duration` of OBS Studio, in milliseconds
+pub const DEFAULT_TRANSITION_DURATION: u32 = 300;
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[ts(optional_fields)]
+pub struct SceneCollection {
+  pub name: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub current_scene: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub current_program_scene: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub current_transition: Option<String>,
+  /// milliseconds
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  #[ts(type = "number")]
+  pub transition_duration: Option<i64>,
+  #[serde(default)]
+  pub scene_order: Vec<SceneOrder>,
+  #[serde(default)]
+  pub sources: Vec<SourceData>,
+  #[serde(default)]
+  pub groups: Vec<SourceData>,
+  #[serde(default)]
+  pub transitions: Vec<TransitionData>,
+  #[serde(flatten)]
+  pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct SceneOrder {
+  pub name: String,
+}
+
+/// ```c
+/// static obs_data_t *obs_save_source(obs_source_t *source)
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[ts(optional_fields)]
+pub struct SourceData {
+  pub name: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub uuid: Option<String>,
+  /// unversioned id, e.g. `color_source`
+  pub id: String,
+  /// e.g. `color_source_v3`, preferred over `id` when loading
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub versioned_id: Option<String>,
+  #[serde(default)]
+  pub settings: Map<String, Value>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub enabled: Option<bool>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub muted: Option<bool>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub volume: Option<f64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub balance: Option<f64>,
+  /// bit mask of the audio tracks
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub mixers: Option<u32>,
+  /// sync offset in nanoseconds
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  #[ts(type = "number")]
+  pub sync: Option<i64>,
+  /// `enum obs_monitoring_type`
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  #[ts(type = "number")]
+  pub monitoring_type: Option<i64>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub filters: Vec<SourceData>,
+  #[serde(flatten)]
+  pub extra: Map<String, Value>,
+}
+
+impl SourceData {
+  /// The id libobs looks up when loading, see `obs_load_source_type`.
+  pub fn type_id(&self) -> &str {
+    match self.versioned_id.as_deref() {
+      Some(id) if !id.is_empty() => id,
+      _ => &self.id,
+    }
+  }
+
+  pub fn is_scene(&self) -> bool {
+    self.id == SCENE_ID
+  }
+
+  pub fn is_group(&self) -> bool {
+    self.id == GROUP_ID
+  }
+
+  /// Items of a scene or a group, stored in `settings.items`.
+  pub fn scene_items(&self) -> Result<Vec<SceneItemData>> {
+    match self.settings.get("items") {
+      Some(items) => Ok(serde_json::from_value(items.clone())?),
+      None => Ok(Vec::new()),
+    }
+  }
+
+  pub fn set_scene_items(&mut self, items: &[SceneItemData]) -> Result<()> {
+    self.settings.insert("items".to_string(), serde_json::to_value(items)?);
+    Ok(())
+  }
+}
+
+/// ```c
+/// static void scene_save_item(obs_data_array_t *array, struct obs_scene_item *item, struct obs_scene_item *backup_group)
+/// ```
+#[derive(Debug, derivative::Derivative, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[derivative(Default)]
+#[serde(default)]
+pub struct SceneItemData {
+  pub name: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub source_uuid: Option<String>,
+  pub id: i64,
+  #[derivative(Default(value="true"))]
+  pub visible: bool,
+  pub locked: bool,
+  pub rot: f32,
+  pub pos: Vec2,
+  #[derivative(Default(value="Vec2::ONE"))]
+  pub scale: Vec2,
+  /// `OBS_ALIGN_LEFT | OBS_ALIGN_TOP`
+  #[derivative(Default(value="5"))]
+  pub align: u32,
+  /// `enum obs_bounds_type`
+  pub bounds_type: u32,
+  pub bounds_align: u32,
+  pub bounds: Vec2,
+  pub crop_left: i64,
+  pub crop_top: i64,
+  pub crop_right: i64,
+  pub crop_bottom: i64,
+  #[serde(flatten)]
+  pub extra: Map<String, Value>,
+}
+
+impl SceneItemData {
+  pub fn transform(&self) -> Transform {
+    Transform { pos: self.pos, scale: self.scale, rot: self.rot }
+  }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct TransitionData {
+  pub name: String,
+  pub id: String,
+  #[serde(default)]
+  pub settings: Map<String, Value>,
+  #[serde(flatten)]
+  pub extra: Map<String, Value>,
+}
+
+/// A source, filter or transition whose type is not registered by any loaded module.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct MissingSource {
+  pub name: String,
+  pub id: String,
+  /// the source a filter is attached to
+  pub parent: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LoadReport {
+  pub name: String,
+  pub scenes: Vec<String>,
+  pub current_scene: Option<String>,
+  pub missing: Vec<MissingSource>,
+}
+
+impl SceneCollection {
+  pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+  }
+
+  /// Every source and scene, followed by the groups.
+  pub fn all_sources(&self) -> impl Iterator<Item = &SourceData> {
+    self.sources.iter().chain(&self.groups)
+  }
+
+  pub fn find_source(&self, name: &str) -> Option<&SourceData> {
+    self.all_sources().find(|i| i.name == name)
+  }
+
+  pub fn missing_sources<S: AsRef<str>>(&self, available: &[S]) -> Vec<MissingSource> {
+    let is_missing = |id: &str| !available.iter().any(|i| i.as_ref() == id);
+    let mut result = Vec::new();
+    for source in self.all_sources() {
+      if is_missing(source.type_id()) {
+        result.push(MissingSource { name: source.name.clone(), id: source.type_id().to_string(), parent: None });
+      }
+      for filter in &source.filters {
+        if is_missing(filter.type_id()) {
+          result.push(MissingSource { name: filter.name.clone(), id: filter.type_id().to_string(), parent: Some(source.name.clone()) });
+        }
+      }
+    }
+    for transition in &self.transitions {
+      if is_missing(&transition.id) {
+        result.push(MissingSource { name: transition.name.clone(), id: transition.id.clone(), parent: None });
+      }
+    }
+    result
+  }
+}
+
+/// Write `content` to `path` the way `os_safe_replace` does:
+/// first into `{path}.tmp`, then move the previous file to `{path}.bak` and the new one in place,
+/// so a crash never leaves a truncated collection behind.
+pub fn save_json_safe<P: AsRef<Path>>(path: P, content: &str) -> Result<()> {
+  let path = path.as_ref();
+  let tmp_path = with_suffix(path, ".tmp");
+  let backup_path = with_suffix(path, ".bak");
+  {
+    let mut file = std::fs::File::create(&tmp_path)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
+  }
+  if path.exists() {
+    std::fs::rename(path, &backup_path)?;
+  }
+  std::fs::rename(&tmp_path, path)?;
+  Ok(())
+}
+
+/// `Untitled.json` -> `Untitled.json.bak`
+pub fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+  let mut name = path.file_name().unwrap_or_default().to_os_string();
+  name.push(suffix);
+  path.with_file_name(name)
+}
+
+/// Parse the json of an `obs_data_t` and release it.
+unsafe fn take_data<T: DeserializeOwned>(data: *mut obs_data_t) -> Result<T> {
+  if data.is_null() {
+    return Err(Error::NulPointer("obs_data_t"))
+  }
+  let json = obs_data_get_json(data);
+  let result = match json.is_null() {
+    true => Err(Error::NulPointer("obs_data_get_json")),
+    false => serde_json::from_str(CStr::from_ptr(json).to_str()?).map_err(Error::from),
+  };
+  obs_data_release(data);
+  result
+}
+
+unsafe fn c_str_lossy(ptr: *const std::os::raw::c_char) -> String {
+  match ptr.is_null() {
+    true => String::new(),
+    false => CStr::from_ptr(ptr).to_string_lossy().to_string(),
+  }
+}
+
+/// Ids of every registered source type, including filters, transitions, scenes and groups.
+pub fn available_source_types() -> Vec<String> {
+  let mut result = Vec::new();
+  let mut id: *const std::os::raw::c_char = std::ptr::null();
+  let mut idx = 0;
+  while unsafe { obs_enum_source_types(idx, &mut id) } {
+    if !id.is_null() {
+      result.push(unsafe { CStr::from_ptr(id) }.to_string_lossy().to_string());
+    }
+    idx += 1;
+  }
+  result
+}
+
+impl Obs {
+  /// Replace the scenes of `self` with the ones of a scene collection file,
+  /// and show its current scene on channel 0 through its current transition.
+  ///
+  /// Sources with an unknown type are still created by libobs (and kept when saving),
+  /// they are listed in [`LoadReport::missing`].
+  pub fn load_scene_collection<P: AsRef<Path>>(&mut self, path: P) -> Result<LoadReport> {
+    let collection = SceneCollection::from_file(path.as_ref())?;
+    let missing = collection.missing_sources(&available_source_types());
+    for i in &missing {
+      warn!(name=i.name, id=i.id, parent=i.parent, "source type not available");
+    }
+
+    // everything that could fail is done before the current scenes are dropped
+    let transitions = collection.transitions.iter().map(|transition| {
+      let id = CString::new(transition.id.as_str())?;
+      let name = CString::new(transition.name.as_str())?;
+      let settings = DataObj::from_json(serde_json::to_string(&transition.settings)?).ok_or(Error::NulPointer("obs_data_create_from_json"))?;
+      Ok((id, name, settings))
+    }).collect::<Result<Vec<_>>>()?;
+    // groups are loaded along with the sources, like `OBSBasic::Load` does
+    let sources = serde_json::json!({ "sources": collection.all_sources().collect::<Vec<_>>() });
+    let data = DataObj::from_json(serde_json::to_string(&sources)?).ok_or(Error::NulPointer("obs_data_create_from_json"))?;
+    let array = unsafe { obs_data_get_array(data.as_ptr_mut(), "sources\0".as_ptr() as *const _) };
+    if array.is_null() {
+      return Err(Error::NulPointer("obs_data_get_array"))
+    }
+
+    self.set_channel_source(0, None);
+    self.collection = None;
+    self.scenes.clear();
+    self.sources.clear();
+    self.transitions.clear();
+
+    unsafe extern "C" fn callback(param: *mut c_void, source: *mut obs_source_t) {
+      let loaded = &mut *(param as *mut Vec<SourceRef>);
+      if let Ok(source) = SourceRef::from_raw(source) {
+        loaded.push(source);
+      }
+    }
+    let mut loaded = Vec::<SourceRef>::new();
+    unsafe {
+      // creates every source first, then `obs_source_load` them, so scene items could find their sources
+      obs_load_sources(array, Some(callback), &mut loaded as *mut _ as *mut c_void);
+      obs_data_array_release(array);
+    }
+
+    let scene_names = match collection.scene_order.is_empty() {
+      true => collection.sources.iter().filter(|i| i.is_scene()).map(|i| i.name.clone()).collect(),
+      false => collection.scene_order.iter().map(|i| i.name.clone()).collect::<Vec<_>>(),
+    };
+    for name in &scene_names {
+      let Some(source) = loaded.iter().find(|i| &source_name(i) == name) else {
+        warn!(name, "scene not loaded");
+        continue
+      };
+      match SceneRef::from_raw(unsafe { obs_scene_from_source(source.as_ptr_mut()) }) {
+        Ok(scene) => self.scenes.push(scene),
+        Err(_) => warn!(name, "not a scene"),
+      }
+    }
+    self.sources = loaded;
+
+    for (id, name, settings) in transitions {
+      let source = unsafe { SourceRef::from_raw_unchecked(obs_source_create_private(id.as_ptr(), name.as_ptr(), settings.as_ptr_mut())) };
+      match source {
+        Some(source) => self.transitions.push(source),
+        None => warn!(name=%name.to_string_lossy(), "failed to create transition"),
+      }
+    }
+
+    let current_scene = collection.current_scene.clone().filter(|name| scene_names.contains(name)).or_else(|| scene_names.first().cloned());
+    self.transition_duration = transition_duration(collection.transition_duration);
+    // like `OBSBasic::Load`, the transition goes on channel 0 and shows the scene at once
+    let transition = collection.current_transition.as_ref()
+      .and_then(|name| self.transitions.iter().find(|i| &source_name(i) == name))
+      .or(self.transitions.first()).cloned();
+    if let Some(scene) = current_scene.as_ref().and_then(|name| self.find_scene(name)) {
+      match transition {
+        Some(transition) => {
+          self.set_channel_source(0, Some(transition.clone()));
+          unsafe { obs_transition_set(transition.as_ptr_mut(), scene.as_source().as_ptr_mut()) };
+        },
+        None => self.set_channel_source(0, Some(scene.as_source())),
+      }
+    }
+    info!(name=collection.name, scenes=self.scenes.len(), sources=self.sources.len(), transitions=self.transitions.len(), "scene collection loaded");
+    let name = collection.name.clone();
+    // keep the fields we don't touch (modules, projectors, ...) for `save_scene_collection`
+    self.collection = Some(SceneCollection { sources: Vec::new(), groups: Vec::new(), transitions: Vec::new(), ..collection });
+    Ok(LoadReport {
+      name,
+      scenes: scene_names,
+      current_scene,
+      missing,
+    })
+  }
+
+  pub fn transition_duration(&self) -> u32 {
+    self.transition_duration
+  }
+
+  pub fn set_transition_duration(&mut self, duration: u32) {
+    self.transition_duration = duration;
+  }
+
+  /// Show `scene` on channel 0, through the program transition for [`Obs::transition_duration`] if there is one.
+  pub fn set_current_scene(&mut self, scene: &SceneRef) {
+    let transition = self.get_channel_source(0)
+      .filter(|i| unsafe { obs_source_get_type(i.as_ptr_mut()) } == OBS_SOURCE_TYPE_TRANSITION);
+    match transition {
+      Some(transition) => unsafe {
+        obs_transition_start(transition.as_ptr_mut(), OBS_TRANSITION_MODE_AUTO, self.transition_duration, scene.as_source().as_ptr_mut());
+      },
+      None => self.set_channel_source(0, Some(scene.as_source())),
+    }
+  }
+
+  /// Snapshot of every public source, scene and group, in the format of OBS Studio.
+  pub fn to_scene_collection(&self, name: &str) -> Result<SceneCollection> {
+    let mut collection = self.collection.clone().unwrap_or_default();
+    if collection.name.is_empty() {
+      collection.name = name.to_string();
+    }
+
+    let mut sources = Vec::<SourceData>::new();
+    unsafe {
+      let array = obs_save_sources();
+      if array.is_null() {
+        return Err(Error::NulPointer("obs_save_sources"))
+      }
+      for idx in 0..obs_data_array_count(array) {
+        match take_data(obs_data_array_item(array, idx)) {
+          Ok(source) => sources.push(source),
+          Err(e) => warn!(idx, error=%e, "failed to save source"),
+        }
+      }
+      obs_data_array_release(array);
+    }
+    (collection.groups, collection.sources) = sources.into_iter().partition(|i| i.is_group());
+
+    collection.scene_order = self.scenes.iter().map(|i| SceneOrder { name: source_name(&i.as_source()) }).collect();
+
+    collection.transitions = Vec::new();
+    for transition in &self.transitions {
+      unsafe {
+        collection.transitions.push(TransitionData {
+          name: source_name(transition),
+          id: c_str_lossy(obs_source_get_id(transition.as_ptr_mut())),
+          settings: take_data(obs_source_get_settings(transition.as_ptr_mut()))?,
+          extra: Map::new(),
+        });
+      }
+    }
+
+    // channel 0 holds either a scene, or a transition to a scene
+    if let Some(source) = self.get_channel_source(0) {
+      let scene = unsafe {
+        match obs_source_get_type(source.as_ptr_mut()) == OBS_SOURCE_TYPE_TRANSITION {
+          true => {
+            collection.current_transition = Some(source_name(&source));
+            SourceRef::from_raw_unchecked(obs_transition_get_active_source(source.as_ptr_mut()))
+          },
+          false => Some(source),
+        }
+      };
+      collection.current_scene = scene.map(|i| source_name(&i));
+      collection.current_program_scene = collection.current_scene.clone();
+    }
+    if collection.current_transition.is_none() {
+      collection.current_transition = collection.transitions.first().map(|i| i.name.clone());
+    }
+    collection.transition_duration = Some(self.transition_duration as i64);
+    Ok(collection)
+  }
+
+  /// Save the live scene graph to a file OBS Studio could open,
+  /// the previous file is kept as `.json.bak`, see [`profile::backup::save`](crate::profile::backup::save) for snapshots.
+  pub fn save_scene_collection<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+    let path = path.as_ref();
+    let name = path.file_stem().unwrap_or_default().to_string_lossy();
+    let collection = self.to_scene_collection(&name)?;
+    let content = serde_json::to_string_pretty(&collection)?;
+    save_json_safe(path, &content)?;
+    info!(path=%path.display(), sources=collection.sources.len(), groups=collection.groups.len(), "scene collection saved");
+    Ok(())
+  }
+}
+
+/// Duration of the collection, negative or missing ones fall back to the default.
+fn transition_duration(value: Option<i64>) -> u32 {
+  value.and_then(|i| u32::try_from(i).ok()).unwrap_or(DEFAULT_TRANSITION_DURATION)
+}
+
+#[test]
+fn test_serde() {
+  let json = r#"{
+    "current_scene": "Scene",
+    "current_program_scene": "Scene",
+    "scene_order": [{"name": "Scene"}],
+    "name": "Untitled",
+    "sources": [
+      {"prev_ver": 503316482, "name": "Scene", "uuid": "7b6b2e5e-0d5c-4e4d-9f49-6d1d4c7e1b7a", "id": "scene", "versioned_id": "scene",
+        "settings": {"custom_size": false, "id_counter": 1, "items": [
+          {"name": "Color", "source_uuid": "3f1c3c38-1c0c-4b0c-9c1e-8c1c6f0e9b2a", "visible": true, "locked": false, "rot": 0.0,
+            "pos": {"x": 10.0, "y": 20.0}, "scale": {"x": 0.5, "y": 0.5}, "align": 5, "bounds_type": 0, "bounds_align": 0,
+            "bounds": {"x": 0.0, "y": 0.0}, "crop_left": 0, "crop_top": 0, "crop_right": 0, "crop_bottom": 0, "id": 1,
+            "group_item_backup": false, "scale_filter": "disable", "blend_method": "default", "blend_type": "normal"}
+        ]},
+        "mixers": 0, "sync": 0, "flags": 0, "volume": 1.0, "balance": 0.5, "enabled": true, "muted": false, "monitoring_type": 0},
+      {"prev_ver": 503316482, "name": "Color", "uuid": "3f1c3c38-1c0c-4b0c-9c1e-8c1c6f0e9b2a", "id": "color_source", "versioned_id": "color_source_v3",
+        "settings": {"color": 4291940817},
+        "filters": [{"name": "LUT", "id": "clut_filter", "versioned_id": "clut_filter", "settings": {"image_path": "/tmp/lut.png"}}]}
+    ],
+    "groups": [],
+    "transitions": [{"name": "Fade", "id": "fade_transition", "settings": {}}],
+    "current_transition": "Fade",
+    "transition_duration": 300,
+    "preview_locked": false
+  }"#;
+  let collection: SceneCollection = serde_json::from_str(json).unwrap();
+  assert_eq!(collection.sources.len(), 2);
+  assert_eq!(collection.sources[1].type_id(), "color_source_v3");
+  assert_eq!(collection.extra.get("preview_locked"), Some(&Value::Bool(false)));
+  let items = collection.sources[0].scene_items().unwrap();
+  assert_eq!(items[0].transform(), Transform { pos: Vec2::new(10.0, 20.0), scale: Vec2::new(0.5, 0.5), rot: 0.0 });
+  assert_eq!(items[0].extra.get("blend_type"), Some(&Value::String("normal".to_string())));
+
+  let missing = collection.missing_sources(&["scene", "color_source_v3"]);
+  assert_eq!(missing, vec![
+    MissingSource { name: "LUT".to_string(), id: "clut_filter".to_string(), parent: Some("Color".to_string()) },
+    MissingSource { name: "Fade".to_string(), id: "fade_transition".to_string(), parent: None },
+  ]);
+
+  let json2 = serde_json::to_string(&collection).unwrap();
+  let collection2: SceneCollection = serde_json::from_str(&json2).unwrap();
+  assert_eq!(collection, collection2);
+  assert_eq!(serde_json::from_str::<Value>(&json2).unwrap(), serde_json::from_str::<Value>(json).unwrap());
+}
+
+#[test]
+fn test_transition_duration() {
+  assert_eq!(transition_duration(Some(700)), 700);
+  assert_eq!(transition_duration(Some(-1)), DEFAULT_TRANSITION_DURATION);
+  assert_eq!(transition_duration(None), DEFAULT_TRANSITION_DURATION);
+}
+
+#[test]
+fn test_save_json_safe() {
+  let dir = crate::testing::TempDir::new("save");
+  let path = dir.join("Untitled.json");
+  save_json_safe(&path, "{\"name\":\"1\"}").unwrap();
+  assert!(!with_suffix(&path, ".bak").exists());
+  save_json_safe(&path, "{\"name\":\"2\"}").unwrap();
+  assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"name\":\"2\"}");
+  assert_eq!(std::fs::read_to_string(dir.join("Untitled.json.bak")).unwrap(), "{\"name\":\"1\"}");
+  assert!(!dir.join("Untitled.json.tmp").exists());
+}
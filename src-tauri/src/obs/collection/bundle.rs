@@ -0,0 +1,174 @@
+//! Scene collection bundle, a zip holding a collection and the local files it references,
+//! so a setup could be moved to another machine where absolute paths differ.
+//!
+//! ```text
+//! collection.json
+//! assets/0-logo.png
+//! assets/1-intro.mkv
+//! ```
+//! Paths in the bundled `collection.json` are relative to the bundle root.
+//! Fonts are referenced by face name in OBS, so they are not part of a bundle.
+
+use std::{collections::HashMap, fs::File, io::{Read as _, Write as _}, path::{Path, PathBuf}};
+
+use super::{save_json_safe, validate::{for_each_file_mut, FileRef}, SceneCollection};
+
+pub type Result<T, E=Error> = std::result::Result<T, E>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+  #[error("io error: {0}")]
+  Io(#[from] std::io::Error),
+  #[error("convert json error")]
+  Json(#[from] serde_json::Error),
+  #[error("zip error: {0}")]
+  Zip(#[from] zip::result::ZipError),
+  /// an import would replace this file
+  #[error("already exists: {0}")]
+  AlreadyExists(PathBuf),
+}
+
+pub const COLLECTION_FILE: &str = "collection.json";
+pub const ASSETS_DIR: &str = "assets";
+
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct ExportReport {
+  pub bundle: PathBuf,
+  /// number of files packed
+  pub files: usize,
+  /// referenced files that do not exist, kept as is in the bundle
+  pub missing: Vec<FileRef>,
+}
+
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct ImportReport {
+  pub collection: PathBuf,
+  pub files: usize,
+}
+
+/// Pack `collection` and its files into the zip at `bundle`.
+pub fn export_bundle<P: AsRef<Path>>(collection: &SceneCollection, bundle: P) -> Result<ExportReport> {
+  let bundle = bundle.as_ref();
+  let mut collection = collection.clone();
+  let mut report = ExportReport { bundle: bundle.to_path_buf(), ..Default::default() };
+  // a file used by several sources is packed once
+  let mut packed = HashMap::<PathBuf, String>::new();
+  for_each_file_mut(&mut collection, |location, path| {
+    let source = PathBuf::from(path.as_str());
+    if !source.is_file() {
+      report.missing.push(FileRef { location: location.clone(), path: source });
+      return
+    }
+    let index = packed.len();
+    let name = packed.entry(source.clone()).or_insert_with(|| {
+      let file_name = source.file_name().unwrap_or_default().to_string_lossy();
+      format!("{ASSETS_DIR}/{index}-{file_name}")
+    });
+    *path = name.clone();
+  });
+
+  let mut zip = zip::ZipWriter::new(File::create(bundle)?);
+  let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+  zip.start_file(COLLECTION_FILE, options)?;
+  zip.write_all(serde_json::to_string_pretty(&collection)?.as_bytes())?;
+  let mut packed = packed.into_iter().collect::<Vec<_>>();
+  packed.sort_by(|a, b| a.1.cmp(&b.1));
+  for (source, name) in &packed {
+    zip.start_file(name.as_str(), options)?;
+    std::io::copy(&mut File::open(source)?, &mut zip)?;
+  }
+  zip.finish()?;
+  report.files = packed.len();
+  info!(bundle=%bundle.display(), files=report.files, missing=report.missing.len(), "scene collection exported");
+  Ok(report)
+}
+
+/// Unpack the assets of `bundle` under `target_dir` and write its collection
+/// into `scenes_dir` (or `target_dir`), with paths pointing to the unpacked files.
+/// Fails with [`Error::AlreadyExists`] before writing anything if a file is there already, unless `overwrite`.
+pub fn import_bundle<P: AsRef<Path>, Q: AsRef<Path>>(bundle: P, target_dir: Q, scenes_dir: Option<&Path>, overwrite: bool) -> Result<ImportReport> {
+  let target_dir = target_dir.as_ref();
+  let mut archive = zip::ZipArchive::new(File::open(bundle.as_ref())?)?;
+  let mut collection = None;
+  let mut assets = Vec::new();
+  for idx in 0..archive.len() {
+    let mut file = archive.by_index(idx)?;
+    // skip entries escaping the target folder, e.g. `../../.bashrc`
+    let Some(name) = file.enclosed_name().map(Path::to_path_buf) else {
+      warn!(name=file.name(), "unsafe path in bundle");
+      continue
+    };
+    if file.is_dir() { continue }
+    if name == Path::new(COLLECTION_FILE) {
+      let mut content = String::new();
+      file.read_to_string(&mut content)?;
+      collection = Some(serde_json::from_str::<SceneCollection>(&content)?);
+    } else if name.starts_with(ASSETS_DIR) {
+      assets.push((idx, target_dir.join(&name)));
+    }
+  }
+  let mut collection = collection.ok_or(std::io::Error::new(std::io::ErrorKind::NotFound, COLLECTION_FILE))?;
+  let scenes_dir = scenes_dir.unwrap_or(target_dir);
+  let file_name = collection.name.replace(['/', '\\', ':'], "_");
+  let output = scenes_dir.join(format!("{file_name}.json"));
+  if !overwrite {
+    // e.g. the user's own collection of the same name, or the assets of an earlier import
+    if let Some(path) = std::iter::once(&output).chain(assets.iter().map(|(_, i)| i)).find(|i| i.exists()) {
+      return Err(Error::AlreadyExists(path.clone()))
+    }
+  }
+
+  for (idx, path) in &assets {
+    if let Some(parent) = path.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+    std::io::copy(&mut archive.by_index(*idx)?, &mut File::create(path)?)?;
+  }
+  for_each_file_mut(&mut collection, |_, path| {
+    if Path::new(path.as_str()).starts_with(ASSETS_DIR) {
+      *path = target_dir.join(path.as_str()).to_string_lossy().to_string();
+    }
+  });
+  std::fs::create_dir_all(scenes_dir)?;
+  save_json_safe(&output, &serde_json::to_string_pretty(&collection)?)?;
+  info!(collection=%output.display(), files=assets.len(), "scene collection imported");
+  Ok(ImportReport { collection: output, files: assets.len() })
+}
+
+#[test]
+fn test_bundle() {
+  let dir = crate::testing::TempDir::new("bundle");
+  std::fs::create_dir_all(dir.join("src")).unwrap();
+  std::fs::write(dir.join("src/logo.png"), b"png").unwrap();
+  let json = serde_json::json!({
+    "name": "Shared",
+    "sources": [
+      {"name": "Logo", "id": "image_source", "settings": {"file": dir.join("src/logo.png")}},
+      {"name": "Slides", "id": "slideshow", "settings": {"files": [{"value": dir.join("src/logo.png")}, {"value": "/nowhere/gone.png"}]}},
+    ],
+  });
+  let collection: SceneCollection = serde_json::from_value(json).unwrap();
+  let report = export_bundle(&collection, dir.join("shared.zip")).unwrap();
+  assert_eq!(report.files, 1);
+  assert_eq!(report.missing.len(), 1);
+
+  let report = import_bundle(dir.join("shared.zip"), dir.join("dst"), None, false).unwrap();
+  assert_eq!(report.files, 1);
+  assert_eq!(report.collection, dir.join("dst/Shared.json"));
+  let imported = SceneCollection::from_file(&report.collection).unwrap();
+  let unpacked = dir.join("dst/assets/0-logo.png");
+  assert_eq!(std::fs::read(&unpacked).unwrap(), b"png");
+  assert_eq!(imported.sources[0].settings["file"], unpacked.to_string_lossy().as_ref());
+  assert_eq!(imported.sources[1].settings["files"][0]["value"], unpacked.to_string_lossy().as_ref());
+  assert_eq!(imported.sources[1].settings["files"][1]["value"], "/nowhere/gone.png");
+
+  // a second import would replace the collection and the assets of the first one
+  std::fs::write(&unpacked, b"edited").unwrap();
+  let e = import_bundle(dir.join("shared.zip"), dir.join("dst"), None, false).unwrap_err();
+  assert!(matches!(e, Error::AlreadyExists(path) if path == report.collection));
+  std::fs::remove_file(&report.collection).unwrap();
+  assert!(matches!(import_bundle(dir.join("shared.zip"), dir.join("dst"), None, false), Err(Error::AlreadyExists(path)) if path == unpacked));
+  assert_eq!(std::fs::read(&unpacked).unwrap(), b"edited");
+  import_bundle(dir.join("shared.zip"), dir.join("dst"), None, true).unwrap();
+  assert_eq!(std::fs::read(&unpacked).unwrap(), b"png");
+}
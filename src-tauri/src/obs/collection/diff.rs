@@ -0,0 +1,376 @@
+//! Semantic diff and three-way merge of scene collections,
+//! so collections could be shared in git without resolving conflicts in the raw json.
+//!
+//! Sources are matched by `uuid` (falling back to `name`), scene items by `id`
+//! and filters by `name`, so a rename or a reorder is not seen as a remove and an add.
+
+use std::collections::BTreeSet;
+
+use serde_json::{Map, Value};
+
+use super::{super::layout::Transform, SceneCollection, SceneItemData, SourceData};
+use crate::obs::Result;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Change {
+  SourceAdded { name: String },
+  SourceRemoved { name: String },
+  SourceRenamed { from: String, to: String },
+  /// `source` is `None` for fields of the collection itself
+  PropertyChanged { source: Option<String>, key: String, old: Option<Value>, new: Option<Value> },
+  SettingChanged { source: String, filter: Option<String>, key: String, old: Option<Value>, new: Option<Value> },
+  ItemAdded { scene: String, item: String },
+  ItemRemoved { scene: String, item: String },
+  ItemMoved { scene: String, item: String, from: Transform, to: Transform },
+  ItemChanged { scene: String, item: String, key: String, old: Option<Value>, new: Option<Value> },
+  FilterAdded { source: String, filter: String },
+  FilterRemoved { source: String, filter: String },
+  FiltersReordered { source: String, from: Vec<String>, to: Vec<String> },
+  SceneOrderChanged { from: Vec<String>, to: Vec<String> },
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct Conflict {
+  /// e.g. `sources[Color].settings.color`
+  pub path: String,
+  pub base: Option<Value>,
+  pub ours: Option<Value>,
+  pub theirs: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct MergeResult {
+  /// conflicting values are resolved to `ours`
+  pub merged: SceneCollection,
+  pub conflicts: Vec<Conflict>,
+}
+
+fn source_key(source: &SourceData) -> &str {
+  source.uuid.as_deref().unwrap_or(&source.name)
+}
+
+fn to_map<T: serde::Serialize>(value: &T, skip: &[&str]) -> Map<String, Value> {
+  let mut map = match serde_json::to_value(value) {
+    Ok(Value::Object(map)) => map,
+    _ => Map::new(),
+  };
+  for key in skip {
+    map.remove(*key);
+  }
+  map
+}
+
+/// `(key, old, new)` of every key that differs, sorted by key.
+fn diff_maps(old: &Map<String, Value>, new: &Map<String, Value>) -> Vec<(String, Option<Value>, Option<Value>)> {
+  let keys = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+  keys.into_iter().filter_map(|key| {
+    let (a, b) = (old.get(key), new.get(key));
+    (a != b).then(|| (key.clone(), a.cloned(), b.cloned()))
+  }).collect()
+}
+
+fn settings_without_items(source: &SourceData) -> Map<String, Value> {
+  let mut settings = source.settings.clone();
+  if source.is_scene() || source.is_group() {
+    settings.remove("items");
+  }
+  settings
+}
+
+fn diff_items(scene: &str, old: &SourceData, new: &SourceData, changes: &mut Vec<Change>) {
+  let (Ok(old_items), Ok(new_items)) = (old.scene_items(), new.scene_items()) else {
+    changes.push(Change::SettingChanged { source: scene.to_string(), filter: None, key: "items".to_string(), old: old.settings.get("items").cloned(), new: new.settings.get("items").cloned() });
+    return
+  };
+  let find = |items: &[SceneItemData], id: i64| items.iter().find(|i| i.id == id).cloned();
+  for item in &old_items {
+    if find(&new_items, item.id).is_none() {
+      changes.push(Change::ItemRemoved { scene: scene.to_string(), item: item.name.clone() });
+    }
+  }
+  for item in &new_items {
+    let Some(old_item) = find(&old_items, item.id) else {
+      changes.push(Change::ItemAdded { scene: scene.to_string(), item: item.name.clone() });
+      continue
+    };
+    if old_item.transform() != item.transform() {
+      changes.push(Change::ItemMoved { scene: scene.to_string(), item: item.name.clone(), from: old_item.transform(), to: item.transform() });
+    }
+    let skip = ["pos", "scale", "rot"];
+    for (key, old, new) in diff_maps(&to_map(&old_item, &skip), &to_map(item, &skip)) {
+      changes.push(Change::ItemChanged { scene: scene.to_string(), item: item.name.clone(), key, old, new });
+    }
+  }
+}
+
+fn diff_filters(source: &str, old: &SourceData, new: &SourceData, changes: &mut Vec<Change>) {
+  let find = |filters: &[SourceData], name: &str| filters.iter().find(|i| i.name == name).cloned();
+  for filter in &old.filters {
+    if find(&new.filters, &filter.name).is_none() {
+      changes.push(Change::FilterRemoved { source: source.to_string(), filter: filter.name.clone() });
+    }
+  }
+  for filter in &new.filters {
+    let Some(old_filter) = find(&old.filters, &filter.name) else {
+      changes.push(Change::FilterAdded { source: source.to_string(), filter: filter.name.clone() });
+      continue
+    };
+    for (key, old, new) in diff_maps(&old_filter.settings, &filter.settings) {
+      changes.push(Change::SettingChanged { source: source.to_string(), filter: Some(filter.name.clone()), key, old, new });
+    }
+  }
+  // order of the filters present on both sides
+  let from = old.filters.iter().filter(|i| find(&new.filters, &i.name).is_some()).map(|i| i.name.clone()).collect::<Vec<_>>();
+  let to = new.filters.iter().filter(|i| find(&old.filters, &i.name).is_some()).map(|i| i.name.clone()).collect::<Vec<_>>();
+  if from != to {
+    changes.push(Change::FiltersReordered { source: source.to_string(), from, to });
+  }
+}
+
+fn diff_source(old: &SourceData, new: &SourceData, changes: &mut Vec<Change>) {
+  let name = &new.name;
+  if old.name != new.name {
+    changes.push(Change::SourceRenamed { from: old.name.clone(), to: new.name.clone() });
+  }
+  let skip = ["name", "uuid", "settings", "filters"];
+  for (key, old, new) in diff_maps(&to_map(old, &skip), &to_map(new, &skip)) {
+    changes.push(Change::PropertyChanged { source: Some(name.clone()), key, old, new });
+  }
+  for (key, old, new) in diff_maps(&settings_without_items(old), &settings_without_items(new)) {
+    changes.push(Change::SettingChanged { source: name.clone(), filter: None, key, old, new });
+  }
+  if new.is_scene() || new.is_group() {
+    diff_items(name, old, new, changes);
+  }
+  diff_filters(name, old, new, changes);
+}
+
+/// Changes turning `old` into `new`.
+pub fn diff(old: &SceneCollection, new: &SceneCollection) -> Vec<Change> {
+  let mut changes = Vec::new();
+  let skip = ["sources", "groups", "scene_order"];
+  for (key, old, new) in diff_maps(&to_map(old, &skip), &to_map(new, &skip)) {
+    changes.push(Change::PropertyChanged { source: None, key, old, new });
+  }
+  let from = old.scene_order.iter().map(|i| i.name.clone()).collect::<Vec<_>>();
+  let to = new.scene_order.iter().map(|i| i.name.clone()).collect::<Vec<_>>();
+  if from != to {
+    changes.push(Change::SceneOrderChanged { from, to });
+  }
+  for source in old.all_sources() {
+    if !new.all_sources().any(|i| source_key(i) == source_key(source)) {
+      changes.push(Change::SourceRemoved { name: source.name.clone() });
+    }
+  }
+  for source in new.all_sources() {
+    match old.all_sources().find(|i| source_key(i) == source_key(source)) {
+      Some(old_source) => diff_source(old_source, source, &mut changes),
+      None => changes.push(Change::SourceAdded { name: source.name.clone() }),
+    }
+  }
+  changes
+}
+
+/// Identity of the elements of a json array, by the name of the field holding it.
+fn list_keys(field: &str) -> Option<&'static [&'static str]> {
+  match field {
+    "sources" | "groups" | "filters" | "transitions" => Some(&["uuid", "name"][..]),
+    "items" => Some(&["id", "name"][..]),
+    "scene_order" => Some(&["name"][..]),
+    "quick_transitions" => Some(&["id"][..]),
+    _ => None,
+  }
+}
+
+fn element_key(value: &Value, keys: &[&str]) -> Option<String> {
+  keys.iter().find_map(|key| match value.get(key)? {
+    Value::String(s) => Some(s.clone()),
+    Value::Number(n) => Some(n.to_string()),
+    _ => None,
+  })
+}
+
+struct Merger {
+  conflicts: Vec<Conflict>,
+}
+
+impl Merger {
+  fn merge_value(&mut self, path: &str, field: &str, base: Option<&Value>, ours: Option<&Value>, theirs: Option<&Value>) -> Option<Value> {
+    if ours == theirs || theirs == base {
+      return ours.cloned()
+    }
+    if ours == base {
+      return theirs.cloned()
+    }
+    match (ours, theirs) {
+      (Some(Value::Object(o)), Some(Value::Object(t))) => {
+        return Some(Value::Object(self.merge_object(path, base.and_then(Value::as_object), o, t)))
+      },
+      (Some(Value::Array(o)), Some(Value::Array(t))) => if let Some(keys) = list_keys(field) {
+        let empty = Vec::new();
+        let base = base.and_then(Value::as_array).unwrap_or(&empty);
+        if let Some(merged) = self.merge_list(path, keys, base, o, t) {
+          return Some(Value::Array(merged))
+        }
+      },
+      _ => {},
+    }
+    self.conflicts.push(Conflict { path: path.to_string(), base: base.cloned(), ours: ours.cloned(), theirs: theirs.cloned() });
+    ours.cloned()
+  }
+
+  fn merge_object(&mut self, path: &str, base: Option<&Map<String, Value>>, ours: &Map<String, Value>, theirs: &Map<String, Value>) -> Map<String, Value> {
+    let mut result = Map::new();
+    let keys = ours.keys().chain(theirs.keys().filter(|k| !ours.contains_key(*k))).chain(base.into_iter().flat_map(|i| i.keys()));
+    for key in keys {
+      if result.contains_key(key) { continue }
+      let sub_path = if path.is_empty() { key.clone() } else { format!("{path}.{key}") };
+      if let Some(value) = self.merge_value(&sub_path, key, base.and_then(|i| i.get(key)), ours.get(key), theirs.get(key)) {
+        result.insert(key.clone(), value);
+      }
+    }
+    result
+  }
+
+  /// `None` if an element could not be identified, the whole list is then a conflict.
+  fn merge_list(&mut self, path: &str, keys: &[&str], base: &[Value], ours: &[Value], theirs: &[Value]) -> Option<Vec<Value>> {
+    fn index<'a>(list: &'a [Value], keys: &[&str]) -> Option<Vec<(String, &'a Value)>> {
+      list.iter().map(|i| Some((element_key(i, keys)?, i))).collect()
+    }
+    fn get<'a>(list: &[(String, &'a Value)], key: &str) -> Option<&'a Value> {
+      list.iter().find(|(k, _)| k == key).map(|(_, v)| *v)
+    }
+    let (base, ours, theirs) = (index(base, keys)?, index(ours, keys)?, index(theirs, keys)?);
+    let order = |list: &[(String, &Value)]| list.iter().map(|(k, _)| k.clone()).collect::<Vec<_>>();
+    // keep the order of the side that reordered, then append what the other side added
+    let (first, second) = match order(&ours) == order(&base) {
+      true => (&theirs, &ours),
+      false => (&ours, &theirs),
+    };
+    // the keys of every side in their order there, to tell a reorder from an addition or removal
+    let kept = |list: &[(String, &Value)]| order(list).into_iter()
+      .filter(|k| get(&base, k).is_some() && get(&ours, k).is_some() && get(&theirs, k).is_some()).collect::<Vec<_>>();
+    let (kept_base, kept_ours, kept_theirs) = (kept(&base), kept(&ours), kept(&theirs));
+    if kept_ours != kept_base && kept_theirs != kept_base && kept_ours != kept_theirs {
+      // both sides reordered the list, the order of `ours` is kept
+      self.conflicts.push(Conflict {
+        path: path.to_string(),
+        base: Some(kept_base.into()),
+        ours: Some(kept_ours.into()),
+        theirs: Some(kept_theirs.into()),
+      });
+    }
+    let mut all_keys = order(first);
+    for key in order(second).into_iter().chain(order(&base)) {
+      if !all_keys.contains(&key) { all_keys.push(key) }
+    }
+    let mut result = Vec::new();
+    for key in all_keys {
+      let sub_path = format!("{path}[{key}]");
+      if let Some(value) = self.merge_value(&sub_path, "", get(&base, &key), get(&ours, &key), get(&theirs, &key)) {
+        result.push(value);
+      }
+    }
+    Some(result)
+  }
+}
+
+/// Three-way merge of `ours` and `theirs`, both derived from `base`.
+/// Edits of different keys are combined, the same key edited on both sides is a [`Conflict`].
+pub fn merge(base: &SceneCollection, ours: &SceneCollection, theirs: &SceneCollection) -> Result<MergeResult> {
+  let (base, ours, theirs) = (to_map(base, &[]), to_map(ours, &[]), to_map(theirs, &[]));
+  let mut merger = Merger { conflicts: Vec::new() };
+  let merged = merger.merge_object("", Some(&base), &ours, &theirs);
+  Ok(MergeResult {
+    merged: serde_json::from_value(Value::Object(merged))?,
+    conflicts: merger.conflicts,
+  })
+}
+
+#[cfg(test)]
+fn sample() -> SceneCollection {
+  serde_json::from_value(serde_json::json!({
+    "name": "test",
+    "current_scene": "Scene",
+    "scene_order": [{"name": "Scene"}],
+    "sources": [
+      {"name": "Scene", "uuid": "s", "id": "scene", "settings": {"items": [
+        {"name": "Color", "source_uuid": "c", "id": 1},
+        {"name": "Image", "source_uuid": "i", "id": 2},
+      ]}},
+      {"name": "Color", "uuid": "c", "id": "color_source", "settings": {"color": 1, "width": 100},
+        "filters": [{"name": "A", "id": "gain_filter", "settings": {}}, {"name": "B", "id": "gain_filter", "settings": {"db": 1.0}}]},
+      {"name": "Image", "uuid": "i", "id": "image_source", "settings": {"file": "a.png"}},
+    ],
+  })).unwrap()
+}
+
+#[test]
+fn test_diff() {
+  use super::super::layout::Vec2;
+  let old = sample();
+  let mut new = old.clone();
+  new.sources[1].name = "Background".to_string();
+  new.sources[1].settings.insert("color".to_string(), 2.into());
+  new.sources[1].filters.reverse();
+  new.sources.remove(2);
+  let mut items = new.sources[0].scene_items().unwrap();
+  items[0].pos.x = 10.0;
+  items.remove(1);
+  new.sources[0].set_scene_items(&items).unwrap();
+  new.current_scene = None;
+
+  let changes = diff(&old, &new);
+  assert_eq!(changes, vec![
+    Change::PropertyChanged { source: None, key: "current_scene".to_string(), old: Some("Scene".into()), new: None },
+    Change::SourceRemoved { name: "Image".to_string() },
+    Change::ItemRemoved { scene: "Scene".to_string(), item: "Image".to_string() },
+    Change::ItemMoved { scene: "Scene".to_string(), item: "Color".to_string(), from: Transform::default(), to: Transform { pos: Vec2::new(10.0, 0.0), ..Default::default() } },
+    Change::SourceRenamed { from: "Color".to_string(), to: "Background".to_string() },
+    Change::SettingChanged { source: "Background".to_string(), filter: None, key: "color".to_string(), old: Some(1.into()), new: Some(2.into()) },
+    Change::FiltersReordered { source: "Background".to_string(), from: vec!["A".to_string(), "B".to_string()], to: vec!["B".to_string(), "A".to_string()] },
+  ]);
+  assert!(diff(&old, &old).is_empty());
+}
+
+#[test]
+fn test_merge() {
+  let base = sample();
+  let mut ours = base.clone();
+  ours.sources[1].settings.insert("color".to_string(), 2.into());
+  ours.sources[2].settings.insert("file".to_string(), "ours.png".into());
+  let mut theirs = base.clone();
+  theirs.sources[1].settings.insert("width".to_string(), 200.into());
+  theirs.sources[1].filters.reverse();
+  theirs.sources[2].settings.insert("file".to_string(), "theirs.png".into());
+  theirs.sources.push(serde_json::from_value(serde_json::json!({"name": "New", "uuid": "n", "id": "color_source"})).unwrap());
+
+  let result = merge(&base, &ours, &theirs).unwrap();
+  let merged = &result.merged;
+  assert_eq!(merged.sources.iter().map(|i| i.name.as_str()).collect::<Vec<_>>(), ["Scene", "Color", "Image", "New"]);
+  assert_eq!(merged.sources[1].settings.get("color"), Some(&2.into()));
+  assert_eq!(merged.sources[1].settings.get("width"), Some(&200.into()));
+  assert_eq!(merged.sources[1].filters.iter().map(|i| i.name.as_str()).collect::<Vec<_>>(), ["B", "A"]);
+  assert_eq!(merged.sources[2].settings.get("file"), Some(&"ours.png".into()));
+  assert_eq!(result.conflicts, vec![Conflict {
+    path: "sources[i].settings.file".to_string(),
+    base: Some("a.png".into()),
+    ours: Some("ours.png".into()),
+    theirs: Some("theirs.png".into()),
+  }]);
+
+  // a reorder on both sides keeps ours and reports the orders of the keys
+  let mut ours = base.clone();
+  ours.sources.swap(0, 1);
+  let mut theirs = base.clone();
+  theirs.sources.swap(1, 2);
+  let result = merge(&base, &ours, &theirs).unwrap();
+  assert_eq!(result.merged.sources.iter().map(|i| i.name.as_str()).collect::<Vec<_>>(), ["Color", "Scene", "Image"]);
+  assert_eq!(result.conflicts, vec![Conflict {
+    path: "sources".to_string(),
+    base: Some(serde_json::json!(["s", "c", "i"])),
+    ours: Some(serde_json::json!(["c", "s", "i"])),
+    theirs: Some(serde_json::json!(["s", "i", "c"])),
+  }]);
+}
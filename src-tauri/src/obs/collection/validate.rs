@@ -0,0 +1,216 @@
+//! Sanity checks of a scene collection before loading it,
+//! the same problems OBS Studio only reports in its log or the "Missing Files" dialog.
+//!
+//! see `UI/window-missing-files.cpp` and `libobs/obs-missing-files.c`
+
+use std::{collections::HashMap, path::{Path, PathBuf}};
+
+use serde_json::{Map, Value};
+
+use super::{MissingSource, SceneCollection};
+
+/// settings holding a single path
+const FILE_KEYS: &[&str] = &[
+  // image_source, text_gdiplus
+  "file",
+  // ffmpeg_source, browser_source, when `is_local_file`
+  "local_file",
+  // text_ft2_source
+  "text_file",
+  // clut_filter, mask_filter
+  "image_path",
+  // shader_filter
+  "shader_file_name",
+];
+
+/// settings holding a list of `{ "value": path }`
+const FILE_LIST_KEYS: &[&str] = &[
+  // slideshow
+  "files",
+  // vlc_source
+  "playlist",
+];
+
+/// Where a path lives in the collection.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct FileLocation {
+  pub source: String,
+  /// set when the path is in a filter of `source`
+  pub filter: Option<String>,
+  pub key: String,
+  /// position in `FILE_LIST_KEYS` lists
+  pub index: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct FileRef {
+  #[serde(flatten)]
+  pub location: FileLocation,
+  pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct DanglingItem {
+  pub scene: String,
+  pub item: String,
+  pub source_uuid: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct Relinked {
+  #[serde(flatten)]
+  pub location: FileLocation,
+  pub from: PathBuf,
+  pub to: PathBuf,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct ValidationReport {
+  pub missing_files: Vec<FileRef>,
+  /// empty if types were not checked
+  pub missing_types: Vec<MissingSource>,
+  pub duplicate_names: Vec<String>,
+  pub dangling_items: Vec<DanglingItem>,
+}
+
+impl ValidationReport {
+  pub fn is_ok(&self) -> bool {
+    self.missing_files.is_empty() && self.missing_types.is_empty() && self.duplicate_names.is_empty() && self.dangling_items.is_empty()
+  }
+}
+
+fn for_each_file_in_settings<F: FnMut(&str, Option<usize>, &mut String)>(settings: &mut Map<String, Value>, f: &mut F) {
+  let is_local_file = settings.get("is_local_file").and_then(Value::as_bool);
+  for (key, value) in settings.iter_mut() {
+    if key == "local_file" && is_local_file == Some(false) {
+      continue
+    }
+    match value {
+      Value::String(path) if FILE_KEYS.contains(&key.as_str()) && !path.is_empty() => f(key, None, path),
+      Value::Array(list) if FILE_LIST_KEYS.contains(&key.as_str()) => {
+        for (index, item) in list.iter_mut().enumerate() {
+          if let Some(Value::String(path)) = item.get_mut("value") {
+            if !path.is_empty() { f(key, Some(index), path) }
+          }
+        }
+      },
+      _ => {},
+    }
+  }
+}
+
+/// Visit every local path referenced by sources and their filters, the callback may rewrite it.
+pub fn for_each_file_mut<F: FnMut(&FileLocation, &mut String)>(collection: &mut SceneCollection, mut f: F) {
+  for source in collection.sources.iter_mut().chain(collection.groups.iter_mut()) {
+    let name = source.name.clone();
+    for_each_file_in_settings(&mut source.settings, &mut |key, index, path| {
+      f(&FileLocation { source: name.clone(), filter: None, key: key.to_string(), index }, path)
+    });
+    for filter in &mut source.filters {
+      let filter_name = filter.name.clone();
+      for_each_file_in_settings(&mut filter.settings, &mut |key, index, path| {
+        f(&FileLocation { source: name.clone(), filter: Some(filter_name.clone()), key: key.to_string(), index }, path)
+      });
+    }
+  }
+}
+
+pub fn file_refs(collection: &SceneCollection) -> Vec<FileRef> {
+  let mut result = Vec::new();
+  for_each_file_mut(&mut collection.clone(), |location, path| {
+    result.push(FileRef { location: location.clone(), path: PathBuf::from(path.as_str()) })
+  });
+  result
+}
+
+/// Check referenced files, duplicate source names and scene items pointing to nothing.
+/// Source types are checked only if `available_types` is given, as it requires libobs.
+pub fn validate<S: AsRef<str>>(collection: &SceneCollection, available_types: Option<&[S]>) -> ValidationReport {
+  let mut report = ValidationReport::default();
+  report.missing_files = file_refs(collection).into_iter().filter(|i| !i.path.exists()).collect();
+  if let Some(available) = available_types {
+    report.missing_types = collection.missing_sources(available);
+  }
+
+  let mut names = HashMap::<&str, usize>::new();
+  for source in collection.all_sources() {
+    *names.entry(&source.name).or_default() += 1;
+  }
+  report.duplicate_names = names.into_iter().filter(|(_, count)| *count > 1).map(|(name, _)| name.to_string()).collect();
+  report.duplicate_names.sort();
+
+  for scene in collection.all_sources().filter(|i| i.is_scene() || i.is_group()) {
+    let items = match scene.scene_items() {
+      Ok(items) => items,
+      Err(e) => {
+        warn!(scene=scene.name, error=%e, "invalid scene items");
+        continue
+      },
+    };
+    for item in items {
+      // `scene_load_item` looks up by uuid first, then by name
+      let found = collection.all_sources().any(|i| match (&item.source_uuid, &i.uuid) {
+        (Some(a), Some(b)) => a == b,
+        _ => i.name == item.name,
+      });
+      if !found {
+        report.dangling_items.push(DanglingItem { scene: scene.name.clone(), item: item.name, source_uuid: item.source_uuid });
+      }
+    }
+  }
+  report
+}
+
+/// Point missing files to a file of the same name found under `search_dir`.
+/// Returns what was changed, files not found are left untouched.
+pub fn relink<P: AsRef<Path>>(collection: &mut SceneCollection, search_dir: P) -> Vec<Relinked> {
+  let mut found = HashMap::new();
+  for entry in walkdir::WalkDir::new(search_dir).into_iter().filter_map(|i| i.ok()) {
+    if entry.file_type().is_file() {
+      found.entry(entry.file_name().to_os_string()).or_insert_with(|| entry.path().to_path_buf());
+    }
+  }
+  let mut result = Vec::new();
+  for_each_file_mut(collection, |location, path| {
+    let from = PathBuf::from(path.as_str());
+    if from.exists() { return }
+    let Some(to) = from.file_name().and_then(|name| found.get(name)) else { return };
+    *path = to.to_string_lossy().to_string();
+    result.push(Relinked { location: location.clone(), from, to: to.clone() });
+  });
+  result
+}
+
+#[test]
+fn test_validate() {
+  let dir = crate::testing::TempDir::new("validate");
+  std::fs::create_dir_all(dir.join("assets")).unwrap();
+  std::fs::write(dir.join("assets/logo.png"), b"").unwrap();
+  let json = serde_json::json!({
+    "name": "test",
+    "sources": [
+      {"name": "Scene", "id": "scene", "settings": {"items": [
+        {"name": "Logo", "source_uuid": "1"},
+        {"name": "Gone", "source_uuid": "404"},
+      ]}},
+      {"name": "Logo", "uuid": "1", "id": "image_source", "settings": {"file": "/nowhere/logo.png"}},
+      {"name": "Video", "uuid": "2", "id": "ffmpeg_source", "settings": {"is_local_file": false, "local_file": "/nowhere/video.mkv"},
+        "filters": [{"name": "LUT", "id": "clut_filter", "settings": {"image_path": dir.join("assets/logo.png")}}]},
+      {"name": "Logo", "uuid": "3", "id": "slideshow", "settings": {"files": [{"value": "/nowhere/a.png"}, {"value": "/nowhere/logo.png"}]}},
+    ],
+  });
+  let mut collection: SceneCollection = serde_json::from_value(json).unwrap();
+  let report = validate(&collection, Some(&["scene", "image_source", "ffmpeg_source", "clut_filter"]));
+  assert!(!report.is_ok());
+  assert_eq!(report.missing_files.iter().map(|i| i.path.to_string_lossy()).collect::<Vec<_>>(), ["/nowhere/logo.png", "/nowhere/a.png", "/nowhere/logo.png"]);
+  assert_eq!(report.missing_files[2].location, FileLocation { source: "Logo".to_string(), filter: None, key: "files".to_string(), index: Some(1) });
+  assert_eq!(report.missing_types.len(), 1);
+  assert_eq!(report.duplicate_names, ["Logo"]);
+  assert_eq!(report.dangling_items, [DanglingItem { scene: "Scene".to_string(), item: "Gone".to_string(), source_uuid: Some("404".to_string()) }]);
+
+  let relinked = relink(&mut collection, &dir);
+  assert_eq!(relinked.len(), 2);
+  assert!(relinked.iter().all(|i| i.to == dir.join("assets/logo.png")));
+  let report = validate::<&str>(&collection, None);
+  assert_eq!(report.missing_files.len(), 1);
+}
@@ -0,0 +1,293 @@
+//! Pure layout math for scene items, mirroring the "Transform" menu of OBS Studio.
+//!
+//! All transforms produced here use top-left alignment (`OBS_ALIGN_LEFT | OBS_ALIGN_TOP`)
+//! and no bounding box, so `pos` is always the top-left corner of the unflipped item.
+//! Rotation is kept as is but ignored when computing the visual box.
+//!
+//! see `UI/window-basic-main-transform.cpp` and `UI/window-basic-main.cpp`
+//! ```c
+//! void OBSBasic::on_actionFitToScreen_triggered()
+//! void OBSBasic::on_actionStretchToScreen_triggered()
+//! void OBSBasic::on_actionCenterToScreen_triggered()
+//! void OBSBasic::on_actionFlipHorizontal_triggered()
+//! ```
+
+use obs_wrapper::{obs_sys::{obs_sceneitem_set_alignment, obs_sceneitem_set_bounds_type, obs_sceneitem_set_pos, obs_sceneitem_set_rot, obs_sceneitem_set_scale, vec2, OBS_ALIGN_LEFT, OBS_ALIGN_TOP, OBS_BOUNDS_NONE}, source::scene::SceneItemRef, wrapper::PtrWrapper as _};
+
+use super::VideoSetting;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[repr(C)]
+pub struct Vec2 {
+  pub x: f32,
+  pub y: f32,
+}
+
+impl Vec2 {
+  pub const ONE: Vec2 = Vec2 { x: 1.0, y: 1.0 };
+
+  pub fn new(x: f32, y: f32) -> Self {
+    Self { x, y }
+  }
+
+  fn as_ptr(&self) -> *const vec2 {
+    // `struct vec2` is a union of `{ float x, y; }` and `float ptr[2]`
+    self as *const Vec2 as *const vec2
+  }
+}
+
+/// Size in pixels, of either the canvas or a source.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Size {
+  pub width: u32,
+  pub height: u32,
+}
+
+impl Size {
+  pub fn new(width: u32, height: u32) -> Self {
+    Self { width, height }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.width == 0 || self.height == 0
+  }
+
+  fn as_vec2(&self) -> Vec2 {
+    Vec2::new(self.width as f32, self.height as f32)
+  }
+}
+
+impl From<&VideoSetting> for Size {
+  /// canvas is the base size, not the (scaled) output size
+  fn from(setting: &VideoSetting) -> Self {
+    let (width, height) = setting.base_size();
+    Self { width, height }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Axis {
+  Horizontal,
+  Vertical,
+  Both,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Corner {
+  TopLeft,
+  TopRight,
+  BottomLeft,
+  #[default]
+  BottomRight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct Transform {
+  pub pos: Vec2,
+  pub scale: Vec2,
+  /// degrees, clockwise
+  pub rot: f32,
+}
+
+impl Default for Transform {
+  fn default() -> Self {
+    Self { pos: Vec2::default(), scale: Vec2::ONE, rot: 0.0 }
+  }
+}
+
+impl Transform {
+  /// Top-left and bottom-right corners of the item on canvas, flips taken into account.
+  pub fn bounding_box(&self, source: Size) -> (Vec2, Vec2) {
+    let far = Vec2::new(
+      self.pos.x + source.width as f32 * self.scale.x,
+      self.pos.y + source.height as f32 * self.scale.y,
+    );
+    (
+      Vec2::new(self.pos.x.min(far.x), self.pos.y.min(far.y)),
+      Vec2::new(self.pos.x.max(far.x), self.pos.y.max(far.y)),
+    )
+  }
+
+  /// Move the item so its visual box is centered in `canvas` along `axis`.
+  pub fn center(mut self, source: Size, canvas: Size, axis: Axis) -> Self {
+    let (min, max) = self.bounding_box(source);
+    if matches!(axis, Axis::Horizontal | Axis::Both) {
+      self.pos.x += (canvas.width as f32 - (max.x - min.x)) / 2.0 - min.x;
+    }
+    if matches!(axis, Axis::Vertical | Axis::Both) {
+      self.pos.y += (canvas.height as f32 - (max.y - min.y)) / 2.0 - min.y;
+    }
+    self
+  }
+
+  /// Mirror the item along `axis`, keeping its visual box in place.
+  ///
+  /// OBS flips by negating the scale, which would otherwise mirror the item around `pos`.
+  pub fn flip(mut self, source: Size, axis: Axis) -> Self {
+    if matches!(axis, Axis::Horizontal | Axis::Both) {
+      self.pos.x += source.width as f32 * self.scale.x;
+      self.scale.x = -self.scale.x;
+    }
+    if matches!(axis, Axis::Vertical | Axis::Both) {
+      self.pos.y += source.height as f32 * self.scale.y;
+      self.scale.y = -self.scale.y;
+    }
+    self
+  }
+
+  /// Write the transform to a scene item, resetting its alignment and bounds.
+  pub fn apply(&self, item: &SceneItemRef) {
+    let ptr = item.as_ptr_mut();
+    unsafe {
+      obs_sceneitem_set_alignment(ptr, OBS_ALIGN_LEFT | OBS_ALIGN_TOP);
+      obs_sceneitem_set_bounds_type(ptr, OBS_BOUNDS_NONE);
+      obs_sceneitem_set_rot(ptr, self.rot);
+      obs_sceneitem_set_scale(ptr, self.scale.as_ptr());
+      obs_sceneitem_set_pos(ptr, self.pos.as_ptr());
+    }
+  }
+}
+
+/// Scale uniformly so the whole source is visible, centered with letterboxing.
+pub fn fit(source: Size, canvas: Size) -> Transform {
+  fit_in(source, Vec2::default(), canvas.as_vec2())
+}
+
+/// Scale each axis independently so the source covers the canvas exactly.
+pub fn stretch(source: Size, canvas: Size) -> Transform {
+  if source.is_empty() {
+    return Transform::default()
+  }
+  Transform {
+    scale: Vec2::new(
+      canvas.width as f32 / source.width as f32,
+      canvas.height as f32 / source.height as f32,
+    ),
+    ..Default::default()
+  }
+}
+
+/// Arrange `sources` in a grid of `ceil(sqrt(n))` columns, fitting each one in its cell.
+/// `spacing` is the gap in pixels between cells and around the border.
+pub fn grid(sources: &[Size], canvas: Size, spacing: f32) -> Vec<Transform> {
+  if sources.is_empty() {
+    return Vec::new()
+  }
+  let n = sources.len();
+  let cols = (n as f64).sqrt().ceil() as usize;
+  let rows = n.div_ceil(cols);
+  let cell = Vec2::new(
+    ((canvas.width as f32 - spacing * (cols + 1) as f32) / cols as f32).max(0.0),
+    ((canvas.height as f32 - spacing * (rows + 1) as f32) / rows as f32).max(0.0),
+  );
+  sources.iter().enumerate().map(|(i, source)| {
+    let (row, col) = (i / cols, i % cols);
+    let origin = Vec2::new(
+      spacing + col as f32 * (cell.x + spacing),
+      spacing + row as f32 * (cell.y + spacing),
+    );
+    fit_in(*source, origin, cell)
+  }).collect()
+}
+
+/// Fit `main` to the canvas and place `inset` in a `corner`,
+/// sized to `ratio` of the canvas width and `margin` pixels away from the edges.
+pub fn picture_in_picture(main: Size, inset: Size, canvas: Size, corner: Corner, ratio: f32, margin: f32) -> (Transform, Transform) {
+  let area = Vec2::new(canvas.width as f32 * ratio, canvas.height as f32 * ratio);
+  let origin = Vec2::new(
+    match corner {
+      Corner::TopLeft | Corner::BottomLeft => margin,
+      Corner::TopRight | Corner::BottomRight => canvas.width as f32 - margin - area.x,
+    },
+    match corner {
+      Corner::TopLeft | Corner::TopRight => margin,
+      Corner::BottomLeft | Corner::BottomRight => canvas.height as f32 - margin - area.y,
+    },
+  );
+  let mut inset_transform = fit_in(inset, origin, area);
+  // stick to the corner instead of the center of the area
+  let (min, max) = inset_transform.bounding_box(inset);
+  let slack = Vec2::new(area.x - (max.x - min.x), area.y - (max.y - min.y));
+  inset_transform.pos.x += match corner {
+    Corner::TopLeft | Corner::BottomLeft => -slack.x / 2.0,
+    Corner::TopRight | Corner::BottomRight => slack.x / 2.0,
+  };
+  inset_transform.pos.y += match corner {
+    Corner::TopLeft | Corner::TopRight => -slack.y / 2.0,
+    Corner::BottomLeft | Corner::BottomRight => slack.y / 2.0,
+  };
+  (fit(main, canvas), inset_transform)
+}
+
+fn fit_in(source: Size, origin: Vec2, area: Vec2) -> Transform {
+  if source.is_empty() {
+    return Transform { pos: origin, ..Default::default() }
+  }
+  let size = source.as_vec2();
+  let scale = (area.x / size.x).min(area.y / size.y);
+  Transform {
+    pos: Vec2::new(
+      origin.x + (area.x - size.x * scale) / 2.0,
+      origin.y + (area.y - size.y * scale) / 2.0,
+    ),
+    scale: Vec2::new(scale, scale),
+    rot: 0.0,
+  }
+}
+
+#[test]
+fn test_fit_and_stretch() {
+  let canvas = Size::new(1920, 1080);
+  let t = fit(Size::new(1080, 1080), canvas);
+  assert_eq!(t.scale, Vec2::ONE);
+  assert_eq!(t.pos, Vec2::new(420.0, 0.0));
+  let t = fit(Size::new(3840, 1080), canvas);
+  assert_eq!(t.scale, Vec2::new(0.5, 0.5));
+  assert_eq!(t.pos, Vec2::new(0.0, 270.0));
+  let t = stretch(Size::new(960, 270), canvas);
+  assert_eq!(t.scale, Vec2::new(2.0, 4.0));
+  assert_eq!(t.pos, Vec2::default());
+  assert_eq!(fit(Size::default(), canvas), Transform::default());
+}
+
+#[test]
+fn test_center_and_flip() {
+  let canvas = Size::new(1920, 1080);
+  let source = Size::new(100, 50);
+  let t = Transform::default().center(source, canvas, Axis::Horizontal);
+  assert_eq!(t.pos, Vec2::new(910.0, 0.0));
+  let t = t.center(source, canvas, Axis::Vertical);
+  assert_eq!(t.pos, Vec2::new(910.0, 515.0));
+  let flipped = t.flip(source, Axis::Horizontal);
+  assert_eq!(flipped.scale, Vec2::new(-1.0, 1.0));
+  assert_eq!(flipped.bounding_box(source), t.bounding_box(source));
+  assert_eq!(flipped.center(source, canvas, Axis::Both), flipped);
+  assert_eq!(flipped.flip(source, Axis::Horizontal), t);
+}
+
+#[test]
+fn test_grid() {
+  let canvas = Size::new(1920, 1080);
+  let sources = [Size::new(1920, 1080); 3];
+  let transforms = grid(&sources, canvas, 0.0);
+  assert_eq!(transforms.len(), 3);
+  assert_eq!(transforms[0], Transform { pos: Vec2::new(0.0, 0.0), scale: Vec2::new(0.5, 0.5), rot: 0.0 });
+  assert_eq!(transforms[1].pos, Vec2::new(960.0, 0.0));
+  assert_eq!(transforms[2].pos, Vec2::new(0.0, 540.0));
+  let transforms = grid(&sources[..1], canvas, 10.0);
+  let (min, max) = transforms[0].bounding_box(sources[0]);
+  assert!((min.y - 10.0).abs() < 1e-3 && (max.y - 1070.0).abs() < 1e-3);
+  assert!(min.x > 10.0 && max.x < 1910.0);
+  assert!(grid(&[], canvas, 0.0).is_empty());
+}
+
+#[test]
+fn test_picture_in_picture() {
+  let canvas = Size::new(1920, 1080);
+  let (main, inset) = picture_in_picture(canvas, Size::new(1080, 1080), canvas, Corner::BottomRight, 0.25, 20.0);
+  assert_eq!(main, Transform::default());
+  let (min, max) = inset.bounding_box(Size::new(1080, 1080));
+  assert_eq!(max, Vec2::new(1900.0, 1060.0));
+  assert_eq!(max.x - min.x, 270.0);
+  assert_eq!(max.y - min.y, 270.0);
+}
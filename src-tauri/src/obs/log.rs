@@ -0,0 +1,83 @@
+//! Forward the logs of libobs to `tracing` with the `libobs` target, instead of its own stdout printer.
+//!
+//! ```c
+//! typedef void (*log_handler_t)(int lvl, const char *msg, va_list args, void *p);
+//! EXPORT void base_set_log_handler(log_handler_t handler, void *param);
+//! EXPORT void base_set_crash_handler(void (*handler)(const char *, va_list, void *), void *param);
+//! ```
+//! see `libobs/util/base.c`
+
+use std::ffi::{c_char, c_int, c_void, CStr};
+
+use obs_wrapper::obs_sys::{base_set_crash_handler, base_set_log_handler, va_list};
+
+pub const TARGET: &str = "libobs";
+
+/// `enum { LOG_ERROR = 100, LOG_WARNING = 200, LOG_INFO = 300, LOG_DEBUG = 400 }`
+pub const LOG_ERROR: i32 = 100;
+pub const LOG_WARNING: i32 = 200;
+pub const LOG_INFO: i32 = 300;
+pub const LOG_DEBUG: i32 = 400;
+
+/// same size as `def_log_handler`, longer messages are truncated
+const BUFFER_SIZE: usize = 8192;
+
+// `vsnprintf` is an inline function of the headers since the Universal CRT
+#[cfg_attr(target_os = "windows", link(name = "legacy_stdio_definitions"))]
+extern "C" {
+  fn vsnprintf(buf: *mut c_char, size: usize, format: *const c_char, args: va_list) -> c_int;
+}
+
+pub fn level(lvl: i32) -> tracing::Level {
+  match lvl {
+    i32::MIN..=LOG_ERROR => tracing::Level::ERROR,
+    ..=LOG_WARNING => tracing::Level::WARN,
+    ..=LOG_INFO => tracing::Level::INFO,
+    _ => tracing::Level::DEBUG,
+  }
+}
+
+/// `args` is only valid once, so the message is formatted a single time into a fixed buffer.
+unsafe fn format(format: *const c_char, args: va_list) -> String {
+  if format.is_null() { return String::new() }
+  let mut buf = [0 as c_char; BUFFER_SIZE];
+  if vsnprintf(buf.as_mut_ptr(), buf.len(), format, args) < 0 {
+    return CStr::from_ptr(format).to_string_lossy().to_string()
+  }
+  CStr::from_ptr(buf.as_ptr()).to_string_lossy().trim_end().to_string()
+}
+
+unsafe extern "C" fn log_handler(lvl: c_int, msg: *const c_char, args: va_list, _param: *mut c_void) {
+  let message = format(msg, args);
+  match level(lvl) {
+    tracing::Level::ERROR => error!(target: TARGET, "{message}"),
+    tracing::Level::WARN => warn!(target: TARGET, "{message}"),
+    tracing::Level::INFO => info!(target: TARGET, "{message}"),
+    _ => debug!(target: TARGET, "{message}"),
+  }
+}
+
+unsafe extern "C" fn crash_handler(msg: *const c_char, args: va_list, _param: *mut c_void) {
+  let message = format(msg, args);
+  error!(target: TARGET, "crashed: {message}");
+  std::process::abort();
+}
+
+/// Replace the log handler of libobs, could be called before `obs_startup` to catch its first lines.
+pub fn set_log_handler() {
+  unsafe { base_set_log_handler(Some(log_handler), std::ptr::null_mut()) }
+}
+
+/// Log the message of `bcrash` before aborting, instead of printing it to stderr and exiting with 0.
+pub fn set_crash_handler() {
+  unsafe { base_set_crash_handler(Some(crash_handler), std::ptr::null_mut()) }
+}
+
+#[test]
+fn test_level() {
+  assert_eq!(level(LOG_ERROR), tracing::Level::ERROR);
+  assert_eq!(level(LOG_WARNING), tracing::Level::WARN);
+  assert_eq!(level(LOG_INFO), tracing::Level::INFO);
+  assert_eq!(level(LOG_DEBUG), tracing::Level::DEBUG);
+  assert_eq!(level(250), tracing::Level::INFO);
+}
@@ -0,0 +1,711 @@
+//! Outputs fed by the main video and audio, a recording through `ffmpeg_muxer`
+//! and a stream through `rtmp_output`, see [`stream`], and a replay buffer, see [`replay`].
+//!
+//! ```text
+//! obs_get_video() -> video encoder ─┐
+//!                                   ├─> ffmpeg_muxer -> file
+//! obs_get_audio() -> audio encoder ─┘
+//! ```
+//! Every encoder listed here runs on the CPU, so a machine without GPU could still record.
+//!
+//! see `UI/window-basic-main-outputs.cpp`
+
+pub mod filename;
+pub mod stream;
+pub mod services;
+pub mod replay;
+pub mod virtualcam;
+#[cfg(test)]
+mod rtmp_server;
+
+use std::{ffi::{c_char, c_void, CStr, CString}, path::{Path, PathBuf}, sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}};
+
+use obs_wrapper::{data::DataObj, obs_sys::{bfree, calldata_get_data, calldata_get_string, calldata_t, obs_audio_encoder_create, obs_encoder_release, obs_encoder_set_audio, obs_encoder_set_video, obs_encoder_t, obs_enum_encoder_types, obs_get_audio, obs_get_video, obs_get_video_info, obs_output_active, obs_output_can_pause, obs_output_create, obs_output_get_congestion, obs_output_get_connect_time_ms, obs_output_get_frames_dropped, obs_output_get_last_error, obs_output_get_name, obs_output_get_proc_handler, obs_output_get_signal_handler, obs_output_get_total_bytes, obs_output_get_total_frames, obs_output_get_weak_output, obs_output_pause, obs_output_paused, obs_output_reconnecting, obs_output_release, obs_output_set_audio_encoder, obs_output_set_video_encoder, obs_output_start, obs_output_stop, obs_output_t, obs_video_encoder_create, obs_weak_output_get_output, obs_weak_output_release, obs_weak_output_t, proc_handler_call, signal_handler_connect, signal_handler_disconnect, signal_handler_t}, wrapper::PtrWrapper as _};
+use serde_json::{json, Value};
+
+use super::{remux::RemuxFormat, Error, Obs, Result};
+
+pub const RECORDING_OUTPUT_ID: &str = "ffmpeg_muxer";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub enum VideoEncoder {
+  /// `obs-x264`
+  #[default]
+  #[serde(rename = "obs_x264")]
+  X264,
+  /// `obs-ffmpeg`, the ones below as well
+  #[serde(rename = "ffmpeg_openh264")]
+  OpenH264,
+  #[serde(rename = "ffmpeg_aom_av1")]
+  AomAv1,
+  #[serde(rename = "ffmpeg_svt_av1")]
+  SvtAv1,
+}
+
+impl VideoEncoder {
+  pub const ALL: [Self; 4] = [Self::X264, Self::OpenH264, Self::AomAv1, Self::SvtAv1];
+
+  pub fn id(self) -> &'static str {
+    match self {
+      Self::X264 => "obs_x264",
+      Self::OpenH264 => "ffmpeg_openh264",
+      Self::AomAv1 => "ffmpeg_aom_av1",
+      Self::SvtAv1 => "ffmpeg_svt_av1",
+    }
+  }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub enum AudioEncoder {
+  #[default]
+  #[serde(rename = "ffmpeg_aac")]
+  Aac,
+  #[serde(rename = "ffmpeg_opus")]
+  Opus,
+}
+
+impl AudioEncoder {
+  pub const ALL: [Self; 2] = [Self::Aac, Self::Opus];
+
+  pub fn id(self) -> &'static str {
+    match self {
+      Self::Aac => "ffmpeg_aac",
+      Self::Opus => "ffmpeg_opus",
+    }
+  }
+}
+
+/// Ids of every registered encoder type, depends on the loaded modules.
+pub fn available_encoder_types() -> Vec<String> {
+  let mut result = Vec::new();
+  let mut id: *const std::os::raw::c_char = std::ptr::null();
+  let mut idx = 0;
+  while unsafe { obs_enum_encoder_types(idx, &mut id) } {
+    if !id.is_null() {
+      result.push(unsafe { CStr::from_ptr(id) }.to_string_lossy().to_string());
+    }
+    idx += 1;
+  }
+  result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputState {
+  Stopped,
+  /// `obs_output_start` succeeded but no data is captured yet, e.g. a stream still connecting
+  Starting,
+  Active,
+  Paused,
+  /// a stream lost its connection and retries
+  Reconnecting,
+  /// `obs_output_stop` was called, the output flushes its encoders before it stops
+  Stopping,
+}
+
+impl OutputState {
+  pub fn new(active: bool, paused: bool, reconnecting: bool, starting: bool, stopping: bool) -> Self {
+    match (active, reconnecting) {
+      (_, true) => Self::Reconnecting,
+      (false, _) if starting => Self::Starting,
+      (false, _) => Self::Stopped,
+      _ if stopping => Self::Stopping,
+      _ if paused => Self::Paused,
+      _ => Self::Active,
+    }
+  }
+}
+
+/// Counters of an active output, see the stats dock of OBS Studio.
+#[derive(Debug, Default, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct OutputStats {
+  pub total_frames: i32,
+  /// frames skipped because of network congestion
+  pub dropped_frames: i32,
+  #[ts(type = "number")]
+  pub total_bytes: u64,
+  /// 0 to 1, how full the send buffer is
+  pub congestion: f32,
+  pub connect_time_ms: i32,
+}
+
+/// An owned `obs_encoder_t`.
+#[derive(Debug)]
+pub struct Encoder(*mut obs_encoder_t);
+
+impl Encoder {
+  /// A video encoder of the main video.
+  pub fn video(id: &str, name: &str, settings: &DataObj) -> Result<Self> {
+    let video = unsafe { obs_get_video() };
+    if video.is_null() {
+      return Err(Error::NulPointer("obs_get_video"))
+    }
+    let (id, name) = (CString::new(id)?, CString::new(name)?);
+    let ptr = unsafe { obs_video_encoder_create(id.as_ptr(), name.as_ptr(), settings.as_ptr_mut(), std::ptr::null_mut()) };
+    if ptr.is_null() {
+      return Err(Error::NulPointer("obs_video_encoder_create"))
+    }
+    unsafe { obs_encoder_set_video(ptr, video) };
+    Ok(Self(ptr))
+  }
+
+  /// An audio encoder of the audio mix `mixer`.
+  pub fn audio(id: &str, name: &str, settings: &DataObj, mixer: usize) -> Result<Self> {
+    let audio = unsafe { obs_get_audio() };
+    if audio.is_null() {
+      return Err(Error::NulPointer("obs_get_audio"))
+    }
+    let (id, name) = (CString::new(id)?, CString::new(name)?);
+    let ptr = unsafe { obs_audio_encoder_create(id.as_ptr(), name.as_ptr(), settings.as_ptr_mut(), mixer, std::ptr::null_mut()) };
+    if ptr.is_null() {
+      return Err(Error::NulPointer("obs_audio_encoder_create"))
+    }
+    unsafe { obs_encoder_set_audio(ptr, audio) };
+    Ok(Self(ptr))
+  }
+
+  pub fn as_ptr(&self) -> *mut obs_encoder_t {
+    self.0
+  }
+}
+
+impl Drop for Encoder {
+  fn drop(&mut self) {
+    unsafe { obs_encoder_release(self.0) }
+  }
+}
+
+/// A weak reference to an output, usable from any thread without keeping the output alive.
+#[derive(Debug)]
+pub struct WeakOutput(*mut obs_weak_output_t);
+unsafe impl Send for WeakOutput {}
+unsafe impl Sync for WeakOutput {}
+
+impl WeakOutput {
+  /// Call `f` with a strong reference, `None` once the output is released.
+  pub fn with<T, F: FnOnce(*mut obs_output_t) -> T>(&self, f: F) -> Option<T> {
+    let ptr = unsafe { obs_weak_output_get_output(self.0) };
+    if ptr.is_null() { return None }
+    let result = f(ptr);
+    unsafe { obs_output_release(ptr) };
+    Some(result)
+  }
+}
+
+impl Drop for WeakOutput {
+  fn drop(&mut self) {
+    unsafe { obs_weak_output_release(self.0) }
+  }
+}
+
+/// What libobs does not tell, updated by the signals of the output.
+#[derive(Debug, Default)]
+struct OutputFlags {
+  starting: AtomicBool,
+  stopping: AtomicBool,
+}
+
+unsafe fn output_state(ptr: *mut obs_output_t, flags: &OutputFlags) -> OutputState {
+  let (starting, stopping) = (flags.starting.load(Ordering::Relaxed), flags.stopping.load(Ordering::Relaxed));
+  OutputState::new(obs_output_active(ptr), obs_output_paused(ptr), obs_output_reconnecting(ptr), starting, stopping)
+}
+
+unsafe fn output_stats(ptr: *mut obs_output_t) -> OutputStats {
+  OutputStats {
+    total_frames: obs_output_get_total_frames(ptr),
+    dropped_frames: obs_output_get_frames_dropped(ptr),
+    total_bytes: obs_output_get_total_bytes(ptr),
+    congestion: obs_output_get_congestion(ptr),
+    connect_time_ms: obs_output_get_connect_time_ms(ptr),
+  }
+}
+
+/// Description of the `code` of the `stop` signal, `OBS_OUTPUT_*` of `obs-defs.h`.
+pub fn stop_reason(code: i64) -> Option<&'static str> {
+  match code {
+    0 => None,
+    -1 => Some("the output path is invalid"),
+    -2 => Some("failed to connect to the server"),
+    -3 => Some("the stream key or server is invalid"),
+    -5 => Some("disconnected from the server"),
+    -6 => Some("the encoders are not supported by the output"),
+    -7 => Some("not enough disk space"),
+    -8 => Some("the encoder failed"),
+    _ => Some("the output stopped unexpectedly"),
+  }
+}
+
+/// An owned `obs_output_t`, with the signals connected to it.
+#[derive(Debug)]
+pub struct Output {
+  ptr: *mut obs_output_t,
+  flags: Arc<OutputFlags>,
+  /// disconnected before the output is released
+  signals: Vec<Signal>,
+}
+
+impl Output {
+  pub fn create(id: &str, name: &str, settings: &DataObj) -> Result<Self> {
+    let (id, name) = (CString::new(id)?, CString::new(name)?);
+    let ptr = unsafe { obs_output_create(id.as_ptr(), name.as_ptr(), settings.as_ptr_mut(), std::ptr::null_mut()) };
+    if ptr.is_null() {
+      return Err(Error::NulPointer("obs_output_create"))
+    }
+    let mut output = Self { ptr, flags: Default::default(), signals: Vec::new() };
+    let flags = output.flags.clone();
+    output.connect("activate", move |_| flags.starting.store(false, Ordering::Relaxed))?;
+    let flags = output.flags.clone();
+    output.connect("stop", move |_| {
+      flags.starting.store(false, Ordering::Relaxed);
+      flags.stopping.store(false, Ordering::Relaxed);
+    })?;
+    Ok(output)
+  }
+
+  pub fn as_ptr(&self) -> *mut obs_output_t {
+    self.ptr
+  }
+
+  pub fn weak(&self) -> WeakOutput {
+    WeakOutput(unsafe { obs_output_get_weak_output(self.ptr) })
+  }
+
+  /// Call `callback` on the signal `name` until the output is dropped.
+  pub fn connect<F: Fn(*mut calldata_t) + Send + 'static>(&mut self, name: &str, callback: F) -> Result<()> {
+    let signal = Signal::connect(self, name, callback)?;
+    self.signals.push(signal);
+    Ok(())
+  }
+
+  pub fn name(&self) -> String {
+    unsafe {
+      let name = obs_output_get_name(self.ptr);
+      if name.is_null() { return String::new() }
+      CStr::from_ptr(name).to_string_lossy().to_string()
+    }
+  }
+
+  pub fn state(&self) -> OutputState {
+    unsafe { output_state(self.ptr, &self.flags) }
+  }
+
+  pub fn stats(&self) -> OutputStats {
+    unsafe { output_stats(self.ptr) }
+  }
+
+  /// Set by the output when it fails, e.g. the reason a stream could not connect.
+  pub fn last_error(&self) -> Option<String> {
+    unsafe {
+      let error = obs_output_get_last_error(self.ptr);
+      if error.is_null() { None } else { Some(CStr::from_ptr(error).to_string_lossy().to_string()) }
+    }
+  }
+
+  /// The reason of the failure is the last error of the output if it set one.
+  pub fn start(&mut self) -> Result<()> {
+    self.flags.starting.store(true, Ordering::Relaxed);
+    self.flags.stopping.store(false, Ordering::Relaxed);
+    if unsafe { obs_output_start(self.ptr) } {
+      return Ok(())
+    }
+    self.flags.starting.store(false, Ordering::Relaxed);
+    Err(Error::Output(self.last_error().unwrap_or_else(|| format!("failed to start output `{}`", self.name()))))
+  }
+
+  /// Returns at once, the output stays [`OutputState::Stopping`] until the file is finished.
+  pub fn stop(&mut self) {
+    if !unsafe { obs_output_active(self.ptr) } && !self.flags.starting.load(Ordering::Relaxed) { return }
+    self.flags.stopping.store(true, Ordering::Relaxed);
+    unsafe { obs_output_stop(self.ptr) }
+  }
+
+  pub fn pause(&mut self, pause: bool) -> Result<()> {
+    unsafe {
+      if !obs_output_can_pause(self.ptr) {
+        return Err(Error::Output(format!("output `{}` could not be paused", self.name())))
+      }
+      if !obs_output_pause(self.ptr, pause) {
+        return Err(Error::Output(format!("failed to {} output `{}`", if pause { "pause" } else { "resume" }, self.name())))
+      }
+    }
+    Ok(())
+  }
+}
+
+impl Drop for Output {
+  fn drop(&mut self) {
+    self.signals.clear();
+    // an active output is stopped when destroyed
+    unsafe { obs_output_release(self.ptr) }
+  }
+}
+
+/// `RecFormat2` of a profile
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[serde(rename_all = "snake_case")]
+pub enum Container {
+  /// survives a crash, remux it to mp4 afterwards
+  #[default]
+  Mkv,
+  Mp4,
+  /// mp4 that is still readable if the recording is cut short
+  FragmentedMp4,
+  Mov,
+  /// h264 and aac only
+  Flv,
+}
+
+impl Container {
+  pub fn extension(self) -> &'static str {
+    match self {
+      Self::Mkv => "mkv",
+      Self::Mp4 | Self::FragmentedMp4 => "mp4",
+      Self::Mov => "mov",
+      Self::Flv => "flv",
+    }
+  }
+
+  /// options of the ffmpeg muxer, `key=value` separated by spaces
+  pub fn muxer_settings(self) -> &'static str {
+    match self {
+      Self::FragmentedMp4 => "movflags=frag_keyframe+empty_moov+delay_moov",
+      _ => "",
+    }
+  }
+
+  pub fn supports(self, video: VideoEncoder, audio: AudioEncoder) -> bool {
+    match self {
+      Self::Flv => matches!(video, VideoEncoder::X264 | VideoEncoder::OpenH264) && audio == AudioEncoder::Aac,
+      _ => true,
+    }
+  }
+}
+
+/// When `ffmpeg_muxer` starts a new file, the next name comes from the same format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SplitFile {
+  Time { seconds: u32 },
+  Size { megabytes: u32 },
+  /// only by [`Recording::split`]
+  Manual,
+}
+
+#[derive(Debug, derivative::Derivative, Clone, PartialEq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[derivative(Default)]
+#[serde(default)]
+pub struct RecordingSetting {
+  /// folder of the files, created if missing
+  #[derivative(Default(value="dirs::video_dir().unwrap_or_default()"))]
+  pub directory: PathBuf,
+  /// file name without extension, see [`filename`]
+  #[derivative(Default(value="filename::DEFAULT_FORMAT.to_string()"))]
+  pub format: String,
+  pub container: Container,
+  /// otherwise spaces of the name are replaced with `_`
+  #[derivative(Default(value="true"))]
+  pub allow_spaces: bool,
+  /// replace an existing file instead of adding a number to the name
+  pub allow_overwrite: bool,
+  pub split: Option<SplitFile>,
+  /// remux every finished file into this format, see [`remux`](super::remux)
+  pub remux: Option<RemuxFormat>,
+  pub video_encoder: VideoEncoder,
+  pub audio_encoder: AudioEncoder,
+  /// settings of the video encoder, its defaults when empty,
+  /// see [`obs_x264::Setting`](super::settings::obs_x264::Setting) and [`ffmpeg_av1::Setting`](super::settings::ffmpeg_av1::Setting)
+  pub video_settings: Value,
+  /// see [`ffmpeg_audio::Setting`](super::settings::ffmpeg_audio::Setting)
+  pub audio_settings: Value,
+}
+
+impl RecordingSetting {
+  /// Path of the first file, the next ones are named by `ffmpeg_muxer` itself.
+  pub fn first_path(&self, context: &filename::FormatContext) -> PathBuf {
+    let path = self.directory.join(filename::file_name(&self.format, self.container.extension(), self.allow_spaces, context));
+    if self.allow_overwrite { path } else { filename::find_best_path(&path, self.allow_spaces) }
+  }
+
+  /// settings of `ffmpeg_muxer`, see `plugins/obs-ffmpeg/obs-ffmpeg-mux.c`
+  pub fn muxer_settings(&self, path: &Path) -> Value {
+    let (max_time_sec, max_size_mb) = match self.split {
+      Some(SplitFile::Time { seconds }) => (seconds, 0),
+      Some(SplitFile::Size { megabytes }) => (0, megabytes),
+      Some(SplitFile::Manual) | None => (0, 0),
+    };
+    json!({
+      "path": path.to_string_lossy(),
+      "muxer_settings": self.container.muxer_settings(),
+      "split_file": self.split.is_some(),
+      "max_time_sec": max_time_sec,
+      "max_size_mb": max_size_mb,
+      "reset_timestamps": true,
+      "directory": self.directory.to_string_lossy(),
+      "format": self.format,
+      "extension": self.container.extension(),
+      "allow_spaces": self.allow_spaces,
+      "allow_overwrite": self.allow_overwrite,
+    })
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutputEvent {
+  /// a file of `output` is complete, after a split or once the output stopped
+  FileFinished { output: String, path: PathBuf },
+  /// data is sent, e.g. a stream is connected
+  Started { output: String },
+  /// `code` is one of `OBS_OUTPUT_*` of `obs-defs.h`, 0 when stopped by the user
+  Stopped { output: String, #[ts(type = "number")] code: i64, message: Option<String> },
+  /// the connection was lost, the next attempt is in `timeout_sec`
+  Reconnecting { output: String, #[ts(type = "number")] timeout_sec: i64 },
+  Reconnected { output: String },
+  /// sent periodically while the output is active
+  Stats { output: String, state: OutputState, stats: OutputStats },
+  /// a replay buffer wrote its content into `path`
+  ReplaySaved { output: String, path: PathBuf },
+}
+
+impl OutputEvent {
+  /// tauri event name
+  pub fn name(&self) -> &'static str {
+    match self {
+      OutputEvent::FileFinished { .. } => "recording-file-finished",
+      OutputEvent::Started { .. } => "output-started",
+      OutputEvent::Stopped { .. } => "output-stopped",
+      OutputEvent::Reconnecting { .. } => "output-reconnecting",
+      OutputEvent::Reconnected { .. } => "output-reconnected",
+      OutputEvent::Stats { .. } => "output-stats",
+      OutputEvent::ReplaySaved { .. } => "replay-saved",
+    }
+  }
+}
+
+unsafe fn calldata_string(cd: *mut calldata_t, name: &CStr) -> Option<String> {
+  let mut value: *const c_char = std::ptr::null();
+  if !calldata_get_string(cd, name.as_ptr(), &mut value) || value.is_null() { return None }
+  Some(CStr::from_ptr(value).to_string_lossy().to_string())
+}
+
+/// `calldata_int`, stored as `long long`
+unsafe fn calldata_int(cd: *mut calldata_t, name: &CStr) -> Option<i64> {
+  let mut value = 0i64;
+  calldata_get_data(cd, name.as_ptr(), &mut value as *mut i64 as *mut c_void, std::mem::size_of::<i64>()).then_some(value)
+}
+
+/// Call the procedure `name` of `output`, `f` reads its results before the calldata is freed.
+/// `None` if the output has no such procedure.
+unsafe fn call_proc<T, F: FnOnce(*mut calldata_t) -> T>(output: *mut obs_output_t, name: &CStr, f: F) -> Option<T> {
+  let handler = obs_output_get_proc_handler(output);
+  let mut cd: calldata_t = std::mem::zeroed();
+  let result = proc_handler_call(handler, name.as_ptr(), &mut cd).then(|| f(&mut cd));
+  if !cd.fixed { bfree(cd.stack as *mut c_void) }
+  result
+}
+
+type SignalCallback = Box<dyn Fn(*mut calldata_t) + Send>;
+
+/// A callback connected to a signal of an output, disconnected on drop.
+/// It is called from the thread of the output.
+pub struct Signal {
+  handler: *mut signal_handler_t,
+  name: CString,
+  data: *mut SignalCallback,
+}
+
+impl std::fmt::Debug for Signal {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Signal").field("name", &self.name).finish()
+  }
+}
+
+unsafe extern "C" fn signal_callback(data: *mut c_void, cd: *mut calldata_t) {
+  let callback = &*(data as *const SignalCallback);
+  callback(cd);
+}
+
+impl Signal {
+  pub fn connect<F: Fn(*mut calldata_t) + Send + 'static>(output: &Output, name: &str, callback: F) -> Result<Self> {
+    let name = CString::new(name)?;
+    let handler = unsafe { obs_output_get_signal_handler(output.as_ptr()) };
+    if handler.is_null() {
+      return Err(Error::NulPointer("obs_output_get_signal_handler"))
+    }
+    let data = Box::into_raw(Box::new(Box::new(callback) as SignalCallback));
+    unsafe { signal_handler_connect(handler, name.as_ptr(), Some(signal_callback), data as *mut c_void) };
+    Ok(Self { handler, name, data })
+  }
+}
+
+impl Drop for Signal {
+  fn drop(&mut self) {
+    unsafe {
+      signal_handler_disconnect(self.handler, self.name.as_ptr(), Some(signal_callback), self.data as *mut c_void);
+      drop(Box::from_raw(self.data));
+    }
+  }
+}
+
+/// Video settings the file name specifiers refer to, at this moment.
+pub fn format_context() -> filename::FormatContext {
+  let mut info = super::VideoSetting::new();
+  unsafe { obs_get_video_info(&mut info.0) };
+  filename::FormatContext {
+    time: chrono::Local::now(),
+    fps_num: info.0.fps_num,
+    fps_den: info.0.fps_den,
+    base_size: (info.0.base_width, info.0.base_height),
+    output_size: (info.0.output_width, info.0.output_height),
+    video_format: filename::video_format_name(info.0.output_format as u32),
+  }
+}
+
+/// A file output with its own encoders.
+#[derive(Debug)]
+pub struct Recording {
+  output: Output,
+  /// released after the output
+  _video_encoder: Encoder,
+  _audio_encoder: Encoder,
+  /// file being written, changed by `file_changed` when the file is split
+  path: Arc<Mutex<PathBuf>>,
+}
+
+impl Recording {
+  pub fn path(&self) -> PathBuf {
+    self.path.lock().unwrap().clone()
+  }
+
+  pub fn output(&self) -> &Output {
+    &self.output
+  }
+
+  pub fn state(&self) -> OutputState {
+    self.output.state()
+  }
+
+  pub fn start(&mut self) -> Result<()> {
+    self.output.start()
+  }
+
+  pub fn stop(&mut self) {
+    self.output.stop()
+  }
+
+  pub fn pause(&mut self) -> Result<()> {
+    self.output.pause(true)
+  }
+
+  pub fn resume(&mut self) -> Result<()> {
+    self.output.pause(false)
+  }
+
+  /// Finish the current file and continue in a new one, needs [`RecordingSetting::split`].
+  pub fn split(&mut self) -> Result<()> {
+    let enabled = unsafe {
+      call_proc(self.output.as_ptr(), c"split_file", |cd| {
+        let mut enabled = false;
+        calldata_get_data(cd, c"split_file_enabled".as_ptr(), &mut enabled as *mut bool as *mut c_void, std::mem::size_of::<bool>());
+        enabled
+      })
+    };
+    match enabled == Some(true) {
+      true => Ok(()),
+      false => Err(Error::Output(format!("output `{}` is not set to split files", self.output.name()))),
+    }
+  }
+
+  /// Call `callback` with [`OutputEvent::FileFinished`] for every finished file that exists.
+  pub fn on_event<F: Fn(OutputEvent) + Send + Sync + 'static>(&mut self, callback: F) -> Result<()> {
+    let callback = Arc::new(callback);
+    let output = self.output.name();
+    let finished = {
+      let (callback, output) = (callback.clone(), output.clone());
+      move |path: PathBuf| if path.exists() { callback(OutputEvent::FileFinished { output: output.clone(), path }) }
+    };
+    let (path, on_changed) = (self.path.clone(), finished.clone());
+    self.output.connect("file_changed", move |cd| {
+      let Some(next) = (unsafe { calldata_string(cd, c"next_file") }) else { return };
+      let previous = std::mem::replace(&mut *path.lock().unwrap(), PathBuf::from(next));
+      on_changed(previous);
+    })?;
+    let path = self.path.clone();
+    self.output.connect("stop", move |cd| {
+      let code = unsafe { calldata_int(cd, c"code") };
+      debug!(?code, "recording stopped");
+      finished(path.lock().unwrap().clone());
+    })
+  }
+}
+
+/// `null` for the defaults, e.g. of an encoder
+pub fn data(value: &Value) -> Result<DataObj> {
+  let json = match value {
+    Value::Null => "{}".to_string(),
+    _ => serde_json::to_string(value)?,
+  };
+  DataObj::from_json(json).ok_or(Error::NulPointer("obs_data_create_from_json"))
+}
+
+impl Obs {
+  /// Create the encoders of `setting` on the main video and the first audio mix, and a muxer writing into `setting.directory`.
+  /// Needs `reset_video` and `reset_audio` first, the recording is stopped until `start`.
+  /// The name of the first file is formatted now, see [`Recording::path`].
+  pub fn create_recording(&mut self, name: &str, setting: &RecordingSetting) -> Result<Recording> {
+    if !self.ready() {
+      return Err(Error::NotInitialized)
+    }
+    if !setting.container.supports(setting.video_encoder, setting.audio_encoder) {
+      return Err(Error::Output(format!("{:?} and {:?} could not be stored in {}", setting.video_encoder, setting.audio_encoder, setting.container.extension())))
+    }
+    std::fs::create_dir_all(&setting.directory)?;
+    let path = setting.first_path(&format_context());
+    let video_encoder = Encoder::video(setting.video_encoder.id(), &format!("{name} video"), &data(&setting.video_settings)?)?;
+    let audio_encoder = Encoder::audio(setting.audio_encoder.id(), &format!("{name} audio"), &data(&setting.audio_settings)?, 0)?;
+    let output = Output::create(RECORDING_OUTPUT_ID, name, &data(&setting.muxer_settings(&path))?)?;
+    unsafe {
+      obs_output_set_video_encoder(output.as_ptr(), video_encoder.as_ptr());
+      obs_output_set_audio_encoder(output.as_ptr(), audio_encoder.as_ptr(), 0);
+    }
+    info!(name, path=%path.display(), "recording created");
+    Ok(Recording { output, _video_encoder: video_encoder, _audio_encoder: audio_encoder, path: Arc::new(Mutex::new(path)) })
+  }
+}
+
+#[test]
+fn test_output() {
+  assert_eq!(OutputState::new(false, true, false, false, true), OutputState::Stopped);
+  assert_eq!(OutputState::new(false, false, false, true, false), OutputState::Starting);
+  assert_eq!(OutputState::new(true, false, false, false, false), OutputState::Active);
+  assert_eq!(OutputState::new(true, true, false, false, false), OutputState::Paused);
+  assert_eq!(OutputState::new(true, false, true, false, false), OutputState::Reconnecting);
+  assert_eq!(OutputState::new(true, true, false, false, true), OutputState::Stopping);
+
+  assert_eq!(stop_reason(0), None);
+  assert_eq!(stop_reason(-2), Some("failed to connect to the server"));
+
+  for encoder in VideoEncoder::ALL {
+    assert_eq!(serde_json::to_value(encoder).unwrap(), encoder.id());
+  }
+  for encoder in AudioEncoder::ALL {
+    assert_eq!(serde_json::to_value(encoder).unwrap(), encoder.id());
+  }
+  let setting: RecordingSetting = serde_json::from_value(json!({
+    "directory": "/tmp/records", "container": "fragmented_mp4", "split": {"type": "time", "seconds": 900},
+    "video_encoder": "obs_x264", "audio_encoder": "ffmpeg_opus",
+  })).unwrap();
+  assert_eq!((setting.video_encoder, setting.audio_encoder), (VideoEncoder::X264, AudioEncoder::Opus));
+  assert_eq!((setting.format.as_str(), setting.allow_spaces, setting.allow_overwrite), (filename::DEFAULT_FORMAT, true, false));
+  assert_eq!(setting.video_settings, Value::Null);
+  let settings = setting.muxer_settings(Path::new("/tmp/records/a.mp4"));
+  assert_eq!(settings["muxer_settings"], "movflags=frag_keyframe+empty_moov+delay_moov");
+  assert_eq!((&settings["split_file"], &settings["max_time_sec"], &settings["max_size_mb"]), (&json!(true), &json!(900), &json!(0)));
+  assert_eq!(settings["extension"], "mp4");
+
+  assert!(Container::Flv.supports(VideoEncoder::X264, AudioEncoder::Aac));
+  assert!(!Container::Flv.supports(VideoEncoder::SvtAv1, AudioEncoder::Aac));
+  assert!(Container::Mkv.supports(VideoEncoder::AomAv1, AudioEncoder::Opus));
+  assert_eq!(serde_json::to_value(OutputEvent::FileFinished { output: "recording".into(), path: "/tmp/a.mkv".into() }).unwrap(),
+    json!({"type": "file_finished", "output": "recording", "path": "/tmp/a.mkv"}));
+}
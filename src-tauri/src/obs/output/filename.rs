@@ -0,0 +1,151 @@
+//! Recording file names, formatted the same way as OBS Studio.
+//!
+//! ```text
+//! %CCYY-%MM-%DD %hh-%mm-%ss  ->  2024-04-13 10-20-30.mkv
+//! ```
+//! | specifier | value |
+//! |---|---|
+//! | `%CCYY` `%YY` `%MM` `%DD` `%hh` `%mm` `%ss` | year, 2 digit year, month, day, hour, minute, second |
+//! | `%a` `%A` `%b` `%B` `%d` `%H` `%I` `%m` `%j` `%M` `%p` `%s` `%S` `%y` `%Y` `%z` `%Z` `%%` | as `strftime` |
+//! | `%FPS` | frame rate, e.g. `30` or `29.97` |
+//! | `%CRES` `%ORES` | canvas and output resolution, e.g. `1920x1080` |
+//! | `%VF` | video format, e.g. `NV12` |
+//!
+//! Any other `%` is kept as is.
+//!
+//! see `libobs/util/platform.c` `os_generate_formatted_filename`
+//! and `UI/window-basic-main-outputs.cpp` `FindBestFilename`
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+
+use crate::obs::VIDEO_FORMATS;
+
+pub const DEFAULT_FORMAT: &str = "%CCYY-%MM-%DD %hh-%mm-%ss";
+/// `os_generate_formatted_filename` cuts the name at 255 bytes
+pub const MAX_LENGTH: usize = 255;
+
+/// OBS specifiers and the `strftime` ones they stand for, longest first
+const SPECIFIERS: &[(&str, &str)] = &[
+  ("%CCYY", "%Y"), ("%YY", "%y"), ("%MM", "%m"), ("%DD", "%d"),
+  ("%hh", "%H"), ("%mm", "%M"), ("%ss", "%S"), ("%%", "%%"),
+  ("%a", "%a"), ("%A", "%A"), ("%b", "%b"), ("%B", "%B"),
+  ("%d", "%d"), ("%H", "%H"), ("%I", "%I"), ("%m", "%m"),
+  ("%j", "%j"), ("%M", "%M"), ("%p", "%p"), ("%s", "%s"),
+  ("%S", "%S"), ("%y", "%y"), ("%Y", "%Y"), ("%z", "%z"),
+  ("%Z", "%Z"),
+];
+
+/// What the specifiers are replaced with, besides the time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatContext {
+  pub time: DateTime<Local>,
+  pub fps_num: u32,
+  pub fps_den: u32,
+  pub base_size: (u32, u32),
+  pub output_size: (u32, u32),
+  pub video_format: &'static str,
+}
+
+impl FormatContext {
+  fn fps(&self) -> String {
+    if self.fps_den <= 1 {
+      self.fps_num.to_string()
+    } else {
+      format!("{:.2}", self.fps_num as f64 / self.fps_den as f64)
+    }
+  }
+}
+
+/// `format` with every specifier replaced, without extension.
+pub fn format(format: &str, context: &FormatContext) -> String {
+  let mut result = String::new();
+  let mut rest = format;
+  while let Some(idx) = rest.find('%') {
+    result.push_str(&rest[..idx]);
+    rest = &rest[idx..];
+    let time = SPECIFIERS.iter().find(|(spec, _)| rest.starts_with(spec));
+    let (value, len) = if let Some((spec, strftime)) = time {
+      (context.time.format(strftime).to_string(), spec.len())
+    } else if rest.starts_with("%FPS") {
+      (context.fps(), 4)
+    } else if rest.starts_with("%CRES") {
+      (format!("{}x{}", context.base_size.0, context.base_size.1), 5)
+    } else if rest.starts_with("%ORES") {
+      (format!("{}x{}", context.output_size.0, context.output_size.1), 5)
+    } else if rest.starts_with("%VF") {
+      (context.video_format.to_string(), 3)
+    } else {
+      ("%".to_string(), 1)
+    };
+    result.push_str(&value);
+    rest = &rest[len..];
+  }
+  result.push_str(rest);
+  result
+}
+
+/// File name of a recording, spaces replaced with `_` unless `allow_spaces`.
+/// The name is cut to fit [`MAX_LENGTH`] with its extension.
+pub fn file_name(format_: &str, extension: &str, allow_spaces: bool, context: &FormatContext) -> String {
+  let mut name = format(format_, context);
+  if !allow_spaces {
+    name = name.replace(' ', "_");
+  }
+  let max = MAX_LENGTH.saturating_sub(extension.len() + 1);
+  if name.len() > max {
+    let mut end = max;
+    while !name.is_char_boundary(end) { end -= 1 }
+    name.truncate(end);
+  }
+  format!("{name}.{extension}")
+}
+
+/// `path` itself if it does not exist yet, otherwise the first free `name (2).ext`, or `name_2.ext` without spaces.
+pub fn find_best_path(path: &Path, allow_spaces: bool) -> PathBuf {
+  if !path.exists() {
+    return path.to_path_buf()
+  }
+  let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+  let extension = path.extension().map(|i| format!(".{}", i.to_string_lossy())).unwrap_or_default();
+  (2..).map(|num| {
+    let name = if allow_spaces { format!("{stem} ({num}){extension}") } else { format!("{stem}_{num}{extension}") };
+    path.with_file_name(name)
+  }).find(|i| !i.exists()).unwrap()
+}
+
+/// `get_video_format_name` of `media-io/video-io.h`, by `enum video_format`
+pub fn video_format_name(format: u32) -> &'static str {
+  VIDEO_FORMATS.get(format as usize).map_or("None", |(_, name)| name)
+}
+
+#[test]
+fn test_filename() {
+  use chrono::TimeZone as _;
+  let context = FormatContext {
+    time: Local.with_ymd_and_hms(2024, 4, 3, 9, 5, 7).unwrap(),
+    fps_num: 30000,
+    fps_den: 1001,
+    base_size: (2560, 1440),
+    output_size: (1920, 1080),
+    video_format: video_format_name(2),
+  };
+  assert_eq!(format(DEFAULT_FORMAT, &context), "2024-04-03 09-05-07");
+  assert_eq!((video_format_name(17), video_format_name(21), video_format_name(99)), ("I010", "YA2L", "None"));
+  assert_eq!(format("%YY%MM%DD-%H%M%S %p %j", &context), "240403-090507 AM 094");
+  assert_eq!(format("%FPS %CRES->%ORES %VF 100%% %x %", &context), "29.97 2560x1440->1920x1080 NV12 100% %x %");
+  assert_eq!(format("%FPS", &FormatContext { fps_num: 60, fps_den: 1, ..context.clone() }), "60");
+
+  assert_eq!(file_name(DEFAULT_FORMAT, "mkv", false, &context), "2024-04-03_09-05-07.mkv");
+  let long = file_name(&"é".repeat(200), "mp4", true, &context);
+  assert!(long.len() <= MAX_LENGTH && long.ends_with("é.mp4"));
+
+  let dir = crate::testing::TempDir::new("filename");
+  let path = dir.join("2024-04-03 09-05-07.mkv");
+  assert_eq!(find_best_path(&path, true), path);
+  std::fs::write(&path, "").unwrap();
+  std::fs::write(dir.join("2024-04-03 09-05-07 (2).mkv"), "").unwrap();
+  assert_eq!(find_best_path(&path, true), dir.join("2024-04-03 09-05-07 (3).mkv"));
+  assert_eq!(find_best_path(&path, false), dir.join("2024-04-03 09-05-07_2.mkv"));
+}
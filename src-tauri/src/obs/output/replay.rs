@@ -0,0 +1,181 @@
+//! A replay buffer, keeping the last seconds of encoded video and audio in memory
+//! until [`ReplayBuffer::save`] writes them into a file.
+//!
+//! ```text
+//! obs_get_video() -> video encoder ─┐
+//!                                   ├─> replay_buffer -> last max_time_sec in memory -> save -> file
+//! obs_get_audio() -> audio encoder ─┘
+//! ```
+//! The file is written on the thread of the output, `saved` is signaled once it is complete.
+//!
+//! see `plugins/obs-ffmpeg/obs-ffmpeg-mux.c` `replay_buffer_save`
+
+use std::{path::PathBuf, sync::Arc};
+
+use obs_wrapper::obs_sys::{obs_output_active, obs_output_set_audio_encoder, obs_output_set_video_encoder, obs_output_t};
+use serde_json::{json, Value};
+
+use super::{call_proc, calldata_string, data, AudioEncoder, Container, Encoder, Output, OutputEvent, OutputState, VideoEncoder, WeakOutput};
+use crate::obs::{Error, Obs, Result};
+
+pub const REPLAY_BUFFER_OUTPUT_ID: &str = "replay_buffer";
+/// `SimpleOutput.RecRBPrefix` of OBS Studio followed by the default format
+pub const DEFAULT_FORMAT: &str = "Replay %CCYY-%MM-%DD %hh-%mm-%ss";
+
+#[derive(Debug, derivative::Derivative, Clone, PartialEq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[derivative(Default)]
+#[serde(default)]
+pub struct ReplayBufferSetting {
+  /// folder of the saved replays, created if missing
+  #[derivative(Default(value="dirs::video_dir().unwrap_or_default()"))]
+  pub directory: PathBuf,
+  /// file name without extension, formatted when saved, see [`filename`](super::filename)
+  #[derivative(Default(value="DEFAULT_FORMAT.to_string()"))]
+  pub format: String,
+  pub container: Container,
+  #[derivative(Default(value="true"))]
+  pub allow_spaces: bool,
+  /// length of a replay
+  #[derivative(Default(value="20"))]
+  pub max_time_sec: u32,
+  /// memory the buffer could use, older data is dropped first
+  #[derivative(Default(value="512"))]
+  pub max_size_mb: u32,
+  pub video_encoder: VideoEncoder,
+  pub audio_encoder: AudioEncoder,
+  /// as [`RecordingSetting::video_settings`](super::RecordingSetting::video_settings)
+  pub video_settings: Value,
+  pub audio_settings: Value,
+}
+
+impl ReplayBufferSetting {
+  pub fn output_settings(&self) -> Value {
+    json!({
+      "directory": self.directory.to_string_lossy(),
+      "format": self.format,
+      "extension": self.container.extension(),
+      "allow_spaces": self.allow_spaces,
+      "max_time_sec": self.max_time_sec,
+      "max_size_mb": self.max_size_mb,
+      "muxer_settings": self.container.muxer_settings(),
+    })
+  }
+}
+
+/// Path of the last saved replay, by the `get_last_replay` procedure.
+unsafe fn last_replay(output: *mut obs_output_t) -> Option<PathBuf> {
+  call_proc(output, c"get_last_replay", |cd| calldata_string(cd, c"path")).flatten()
+    .filter(|i| !i.is_empty()).map(PathBuf::from)
+}
+
+unsafe fn save(output: *mut obs_output_t) -> Result<()> {
+  if !obs_output_active(output) {
+    return Err(Error::Output("the replay buffer is not active".to_string()))
+  }
+  call_proc(output, c"save", |_| ()).ok_or_else(|| Error::Output("the output could not save a replay".to_string()))
+}
+
+/// Saves the replay buffer from any thread, e.g. when a game requests it.
+/// It does not keep the replay buffer alive.
+#[derive(Debug, Clone)]
+pub struct ReplaySaveHandle(Arc<WeakOutput>);
+
+impl ReplaySaveHandle {
+  /// Returns once the save is requested, [`OutputEvent::ReplaySaved`] is sent when the file is written.
+  pub fn save(&self) -> Result<()> {
+    self.0.with(|ptr| unsafe { save(ptr) })
+      .unwrap_or_else(|| Err(Error::Output("the replay buffer was released".to_string())))
+  }
+}
+
+/// A replay buffer with its own encoders.
+#[derive(Debug)]
+pub struct ReplayBuffer {
+  output: Output,
+  /// released after the output
+  _video_encoder: Encoder,
+  _audio_encoder: Encoder,
+}
+
+impl ReplayBuffer {
+  pub fn output(&self) -> &Output {
+    &self.output
+  }
+
+  pub fn state(&self) -> OutputState {
+    self.output.state()
+  }
+
+  /// Starts buffering, nothing is written until [`ReplayBuffer::save`].
+  pub fn start(&mut self) -> Result<()> {
+    self.output.start()
+  }
+
+  pub fn stop(&mut self) {
+    self.output.stop()
+  }
+
+  /// Write the buffer into a new file, see [`ReplaySaveHandle::save`].
+  pub fn save(&self) -> Result<()> {
+    unsafe { save(self.output.as_ptr()) }
+  }
+
+  pub fn save_handle(&self) -> ReplaySaveHandle {
+    ReplaySaveHandle(Arc::new(self.output.weak()))
+  }
+
+  pub fn last_replay(&self) -> Option<PathBuf> {
+    unsafe { last_replay(self.output.as_ptr()) }
+  }
+
+  /// Call `callback` with [`OutputEvent::ReplaySaved`] for every saved replay.
+  pub fn on_event<F: Fn(OutputEvent) + Send + Sync + 'static>(&mut self, callback: F) -> Result<()> {
+    let output = self.output.name();
+    let weak = self.output.weak();
+    self.output.connect("saved", move |cd| {
+      // newer versions pass the path, the older ones only keep it for `get_last_replay`
+      let path = unsafe { calldata_string(cd, c"path") }.map(PathBuf::from)
+        .or_else(|| weak.with(|ptr| unsafe { last_replay(ptr) }).flatten());
+      match path {
+        Some(path) => callback(OutputEvent::ReplaySaved { output: output.clone(), path }),
+        None => warn!(output, "replay saved without a path"),
+      }
+    })
+  }
+}
+
+impl Obs {
+  /// Create the encoders of `setting` on the main video and the first audio mix, and a replay buffer saving into `setting.directory`.
+  /// Needs `reset_video` and `reset_audio` first, nothing is buffered until `start`.
+  pub fn create_replay_buffer(&mut self, name: &str, setting: &ReplayBufferSetting) -> Result<ReplayBuffer> {
+    if !self.ready() {
+      return Err(Error::NotInitialized)
+    }
+    if !setting.container.supports(setting.video_encoder, setting.audio_encoder) {
+      return Err(Error::Output(format!("{:?} and {:?} could not be stored in {}", setting.video_encoder, setting.audio_encoder, setting.container.extension())))
+    }
+    if setting.max_time_sec == 0 {
+      return Err(Error::Output("the replay buffer needs a length".to_string()))
+    }
+    std::fs::create_dir_all(&setting.directory)?;
+    let video_encoder = Encoder::video(setting.video_encoder.id(), &format!("{name} video"), &data(&setting.video_settings)?)?;
+    let audio_encoder = Encoder::audio(setting.audio_encoder.id(), &format!("{name} audio"), &data(&setting.audio_settings)?, 0)?;
+    let output = Output::create(REPLAY_BUFFER_OUTPUT_ID, name, &data(&setting.output_settings())?)?;
+    unsafe {
+      obs_output_set_video_encoder(output.as_ptr(), video_encoder.as_ptr());
+      obs_output_set_audio_encoder(output.as_ptr(), audio_encoder.as_ptr(), 0);
+    }
+    info!(name, directory=%setting.directory.display(), max_time_sec=setting.max_time_sec, "replay buffer created");
+    Ok(ReplayBuffer { output, _video_encoder: video_encoder, _audio_encoder: audio_encoder })
+  }
+}
+
+#[test]
+fn test_replay() {
+  let setting: ReplayBufferSetting = serde_json::from_value(json!({"directory": "/tmp/replays", "container": "fragmented_mp4", "max_time_sec": 30})).unwrap();
+  assert_eq!((setting.format.as_str(), setting.max_time_sec, setting.max_size_mb), (DEFAULT_FORMAT, 30, 512));
+  assert_eq!(setting.output_settings(), json!({
+    "directory": "/tmp/replays", "format": DEFAULT_FORMAT, "extension": "mp4", "allow_spaces": true,
+    "max_time_sec": 30, "max_size_mb": 512, "muxer_settings": "movflags=frag_keyframe+empty_moov+delay_moov",
+  }));
+}
@@ -0,0 +1,472 @@
+//! A local RTMP receiver standing in for a streaming service in tests.
+//!
+//! It answers just enough of the protocol for `librtmp` of `obs-outputs` to publish:
+//! ```text
+//! C0 C1        ->
+//!              <-  S0 S1 S2
+//! C2           ->
+//! connect      ->
+//!              <-  Window Acknowledgement Size, Set Peer Bandwidth, _result NetConnection.Connect.Success
+//! releaseStream, FCPublish, createStream ->
+//!              <-  _result 1
+//! publish key  ->
+//!              <-  onStatus NetStream.Publish.Start
+//! @setDataFrame, audio, video ->
+//! ```
+//! and counts what is published, see <https://rtmp.veriskope.com/docs/spec/>
+
+use std::{collections::HashMap, io::{self, Read, Write}, net::{SocketAddr, TcpListener, TcpStream}, sync::{Arc, Mutex}};
+
+const HANDSHAKE_SIZE: usize = 1536;
+const DEFAULT_CHUNK_SIZE: usize = 128;
+
+const SET_CHUNK_SIZE: u8 = 1;
+const WINDOW_ACK_SIZE: u8 = 5;
+const SET_PEER_BANDWIDTH: u8 = 6;
+const AUDIO: u8 = 8;
+const VIDEO: u8 = 9;
+const DATA: u8 = 18;
+const COMMAND: u8 = 20;
+
+/// AMF0 values, the ones used by commands
+#[derive(Debug, Clone, PartialEq)]
+pub enum Amf {
+  Number(f64),
+  Bool(bool),
+  String(String),
+  Object(Vec<(String, Amf)>),
+  Null,
+  Undefined,
+  Array(Vec<Amf>),
+}
+
+impl Amf {
+  fn object(props: &[(&str, Amf)]) -> Self {
+    Self::Object(props.iter().map(|(k, v)| (k.to_string(), v.clone())).collect())
+  }
+
+  fn str(value: &str) -> Self {
+    Self::String(value.to_string())
+  }
+
+  pub fn as_str(&self) -> Option<&str> {
+    match self {
+      Self::String(value) => Some(value),
+      _ => None,
+    }
+  }
+
+  pub fn as_number(&self) -> Option<f64> {
+    match self {
+      Self::Number(value) => Some(*value),
+      _ => None,
+    }
+  }
+
+  fn encode_key(key: &str, out: &mut Vec<u8>) {
+    out.extend((key.len() as u16).to_be_bytes());
+    out.extend(key.as_bytes());
+  }
+
+  pub fn encode(&self, out: &mut Vec<u8>) {
+    match self {
+      Self::Number(value) => { out.push(0); out.extend(value.to_be_bytes()) },
+      Self::Bool(value) => out.extend([1, *value as u8]),
+      Self::String(value) => { out.push(2); Self::encode_key(value, out) },
+      Self::Object(props) => {
+        out.push(3);
+        for (key, value) in props {
+          Self::encode_key(key, out);
+          value.encode(out);
+        }
+        out.extend([0, 0, 9]);
+      },
+      Self::Null => out.push(5),
+      Self::Undefined => out.push(6),
+      Self::Array(values) => {
+        out.push(10);
+        out.extend((values.len() as u32).to_be_bytes());
+        values.iter().for_each(|i| i.encode(out));
+      },
+    }
+  }
+
+  /// Every value of `data`, e.g. the name, transaction id and arguments of a command.
+  pub fn decode_all(mut data: &[u8]) -> io::Result<Vec<Self>> {
+    let mut values = Vec::new();
+    while !data.is_empty() {
+      values.push(Self::decode(&mut data)?);
+    }
+    Ok(values)
+  }
+
+  fn take<'a>(data: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    if data.len() < len {
+      return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated amf"))
+    }
+    let (value, rest) = data.split_at(len);
+    *data = rest;
+    Ok(value)
+  }
+
+  fn decode_string(data: &mut &[u8], long: bool) -> io::Result<String> {
+    let len = match long {
+      true => u32::from_be_bytes(Self::take(data, 4)?.try_into().unwrap()) as usize,
+      false => u16::from_be_bytes(Self::take(data, 2)?.try_into().unwrap()) as usize,
+    };
+    Ok(String::from_utf8_lossy(Self::take(data, len)?).to_string())
+  }
+
+  fn decode_props(data: &mut &[u8]) -> io::Result<Vec<(String, Self)>> {
+    let mut props = Vec::new();
+    loop {
+      let key = Self::decode_string(data, false)?;
+      if key.is_empty() && data.first() == Some(&9) {
+        *data = &data[1..];
+        return Ok(props)
+      }
+      props.push((key, Self::decode(data)?));
+    }
+  }
+
+  pub fn decode(data: &mut &[u8]) -> io::Result<Self> {
+    let marker = Self::take(data, 1)?[0];
+    Ok(match marker {
+      0 => Self::Number(f64::from_be_bytes(Self::take(data, 8)?.try_into().unwrap())),
+      1 => Self::Bool(Self::take(data, 1)?[0] != 0),
+      2 => Self::String(Self::decode_string(data, false)?),
+      3 => Self::Object(Self::decode_props(data)?),
+      5 => Self::Null,
+      6 => Self::Undefined,
+      // ECMA array, the count is a hint only
+      8 => { Self::take(data, 4)?; Self::Object(Self::decode_props(data)?) },
+      10 => {
+        let len = u32::from_be_bytes(Self::take(data, 4)?.try_into().unwrap());
+        Self::Array((0..len).map(|_| Self::decode(data)).collect::<io::Result<_>>()?)
+      },
+      12 => Self::String(Self::decode_string(data, true)?),
+      _ => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unsupported amf marker {marker}"))),
+    })
+  }
+}
+
+/// A complete message, reassembled from its chunks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+  pub type_id: u8,
+  pub stream_id: u32,
+  pub timestamp: u32,
+  pub payload: Vec<u8>,
+}
+
+/// Header of the last chunk of a chunk stream, the next ones only send what changed.
+#[derive(Debug, Default, Clone)]
+struct ChunkStream {
+  timestamp: u32,
+  delta: u32,
+  length: usize,
+  type_id: u8,
+  stream_id: u32,
+  extended: bool,
+  payload: Vec<u8>,
+}
+
+/// Both directions of a connection, with the chunk size of each.
+pub struct Connection {
+  stream: TcpStream,
+  read_chunk_size: usize,
+  write_chunk_size: usize,
+  streams: HashMap<u32, ChunkStream>,
+}
+
+impl Connection {
+  pub fn new(stream: TcpStream) -> Self {
+    Self { stream, read_chunk_size: DEFAULT_CHUNK_SIZE, write_chunk_size: DEFAULT_CHUNK_SIZE, streams: HashMap::new() }
+  }
+
+  fn read_array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+    let mut buf = [0; N];
+    self.stream.read_exact(&mut buf)?;
+    Ok(buf)
+  }
+
+  fn read_u24(&mut self) -> io::Result<u32> {
+    let [a, b, c] = self.read_array()?;
+    Ok(u32::from_be_bytes([0, a, b, c]))
+  }
+
+  /// Simple handshake, S1 has no digest so the client does not check it.
+  pub fn accept_handshake(&mut self) -> io::Result<()> {
+    let [version] = self.read_array()?;
+    if version != 3 {
+      return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unsupported rtmp version {version}")))
+    }
+    let c1: [u8; HANDSHAKE_SIZE] = self.read_array()?;
+    let mut s1 = [0; HANDSHAKE_SIZE];
+    s1.iter_mut().enumerate().skip(8).for_each(|(idx, i)| *i = idx as u8);
+    self.stream.write_all(&[3])?;
+    self.stream.write_all(&s1)?;
+    self.stream.write_all(&c1)?;
+    let _c2: [u8; HANDSHAKE_SIZE] = self.read_array()?;
+    Ok(())
+  }
+
+  pub fn handshake(&mut self) -> io::Result<()> {
+    let c1 = [0; HANDSHAKE_SIZE];
+    self.stream.write_all(&[3])?;
+    self.stream.write_all(&c1)?;
+    let [_version] = self.read_array()?;
+    let s1: [u8; HANDSHAKE_SIZE] = self.read_array()?;
+    let _s2: [u8; HANDSHAKE_SIZE] = self.read_array()?;
+    self.stream.write_all(&s1)
+  }
+
+  pub fn read_message(&mut self) -> io::Result<Message> {
+    loop {
+      let [first] = self.read_array()?;
+      let fmt = first >> 6;
+      let csid = match first & 0x3f {
+        0 => self.read_array::<1>()?[0] as u32 + 64,
+        1 => { let [a, b] = self.read_array()?; a as u32 + b as u32 * 256 + 64 },
+        id => id as u32,
+      };
+      let mut chunk = self.streams.remove(&csid).unwrap_or_default();
+      if fmt < 3 {
+        let timestamp = self.read_u24()?;
+        if fmt < 2 {
+          chunk.length = self.read_u24()? as usize;
+          chunk.type_id = self.read_array::<1>()?[0];
+        }
+        if fmt == 0 {
+          chunk.stream_id = u32::from_le_bytes(self.read_array()?);
+        }
+        chunk.extended = timestamp == 0xffffff;
+        let timestamp = if chunk.extended { u32::from_be_bytes(self.read_array()?) } else { timestamp };
+        if fmt == 0 {
+          (chunk.timestamp, chunk.delta) = (timestamp, 0);
+        } else {
+          chunk.delta = timestamp;
+        }
+      } else if chunk.extended {
+        self.read_array::<4>()?;
+      }
+      if chunk.payload.is_empty() && fmt != 0 {
+        // first chunk of a message reusing the previous header
+        chunk.timestamp = chunk.timestamp.wrapping_add(chunk.delta);
+      }
+      let len = (chunk.length - chunk.payload.len()).min(self.read_chunk_size);
+      let start = chunk.payload.len();
+      chunk.payload.resize(start + len, 0);
+      self.stream.read_exact(&mut chunk.payload[start..])?;
+      if chunk.payload.len() < chunk.length {
+        self.streams.insert(csid, chunk);
+        continue
+      }
+      let payload = std::mem::take(&mut chunk.payload);
+      let message = Message { type_id: chunk.type_id, stream_id: chunk.stream_id, timestamp: chunk.timestamp, payload };
+      self.streams.insert(csid, chunk);
+      if message.type_id == SET_CHUNK_SIZE && message.payload.len() >= 4 {
+        self.read_chunk_size = (u32::from_be_bytes(message.payload[..4].try_into().unwrap()) & 0x7fffffff) as usize;
+      }
+      return Ok(message)
+    }
+  }
+
+  /// Every message with a full header on chunk stream `csid`.
+  pub fn write_message(&mut self, csid: u8, message: &Message) -> io::Result<()> {
+    let mut out = vec![csid];
+    out.extend(&message.timestamp.min(0xfffffe).to_be_bytes()[1..]);
+    out.extend(&(message.payload.len() as u32).to_be_bytes()[1..]);
+    out.push(message.type_id);
+    out.extend(message.stream_id.to_le_bytes());
+    for (idx, chunk) in message.payload.chunks(self.write_chunk_size).enumerate() {
+      if idx > 0 {
+        out.push(0xc0 | csid);
+      }
+      out.extend(chunk);
+    }
+    self.stream.write_all(&out)
+  }
+
+  pub fn write_command(&mut self, stream_id: u32, values: &[Amf]) -> io::Result<()> {
+    let mut payload = Vec::new();
+    values.iter().for_each(|i| i.encode(&mut payload));
+    self.write_message(3, &Message { type_id: COMMAND, stream_id, timestamp: 0, payload })
+  }
+
+  fn write_control(&mut self, type_id: u8, payload: Vec<u8>) -> io::Result<()> {
+    self.write_message(2, &Message { type_id, stream_id: 0, timestamp: 0, payload })
+  }
+}
+
+/// What was published so far.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Received {
+  pub app: Option<String>,
+  /// stream key
+  pub published: Option<String>,
+  pub metadata: usize,
+  pub audio_bytes: usize,
+  pub video_bytes: usize,
+  pub video_messages: usize,
+}
+
+fn serve(stream: TcpStream, received: Arc<Mutex<Received>>) -> io::Result<()> {
+  let mut conn = Connection::new(stream);
+  conn.accept_handshake()?;
+  loop {
+    let message = conn.read_message()?;
+    let mut received = received.lock().unwrap();
+    match message.type_id {
+      AUDIO => received.audio_bytes += message.payload.len(),
+      VIDEO => { received.video_bytes += message.payload.len(); received.video_messages += 1 },
+      DATA => received.metadata += 1,
+      COMMAND => {
+        let values = Amf::decode_all(&message.payload)?;
+        let name = values.first().and_then(Amf::as_str).unwrap_or_default();
+        let txn = values.get(1).and_then(Amf::as_number).unwrap_or_default();
+        debug!(name, txn, "rtmp command");
+        match name {
+          "connect" => {
+            if let Some(Amf::Object(props)) = values.get(2) {
+              received.app = props.iter().find(|(k, _)| k == "app").and_then(|(_, v)| v.as_str()).map(str::to_string);
+            }
+            conn.write_control(WINDOW_ACK_SIZE, 2_500_000u32.to_be_bytes().to_vec())?;
+            conn.write_control(SET_PEER_BANDWIDTH, [&2_500_000u32.to_be_bytes()[..], &[2][..]].concat())?;
+            conn.write_command(0, &[
+              Amf::str("_result"), Amf::Number(txn),
+              Amf::object(&[("fmsVer", Amf::str("FMS/3,0,1,123")), ("capabilities", Amf::Number(31.))]),
+              Amf::object(&[
+                ("level", Amf::str("status")), ("code", Amf::str("NetConnection.Connect.Success")),
+                ("description", Amf::str("Connection succeeded.")), ("objectEncoding", Amf::Number(0.)),
+              ]),
+            ])?;
+          },
+          "createStream" => conn.write_command(0, &[Amf::str("_result"), Amf::Number(txn), Amf::Null, Amf::Number(1.)])?,
+          "publish" => {
+            received.published = values.get(3).and_then(Amf::as_str).map(str::to_string);
+            conn.write_command(1, &[
+              Amf::str("onStatus"), Amf::Number(0.), Amf::Null,
+              Amf::object(&[("level", Amf::str("status")), ("code", Amf::str("NetStream.Publish.Start")), ("description", Amf::str("Publishing."))]),
+            ])?;
+          },
+          // releaseStream, FCPublish, FCUnpublish, deleteStream...
+          _ => {},
+        }
+      },
+      _ => {},
+    }
+  }
+}
+
+/// Accepts connections on a free local port until the test ends.
+pub struct RtmpServer {
+  addr: SocketAddr,
+  received: Arc<Mutex<Received>>,
+}
+
+impl RtmpServer {
+  pub fn bind() -> io::Result<Self> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let received = Arc::new(Mutex::new(Received::default()));
+    {
+      let received = received.clone();
+      std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+          let received = received.clone();
+          std::thread::spawn(move || {
+            if let Err(e) = serve(stream, received) {
+              debug!(error=%e, "rtmp connection closed");
+            }
+          });
+        }
+      });
+    }
+    Ok(Self { addr, received })
+  }
+
+  pub fn addr(&self) -> SocketAddr {
+    self.addr
+  }
+
+  /// `rtmp://127.0.0.1:port/live`, the server of a custom service
+  pub fn url(&self) -> String {
+    format!("rtmp://{}/live", self.addr)
+  }
+
+  pub fn received(&self) -> Received {
+    self.received.lock().unwrap().clone()
+  }
+
+  /// Poll until `f` is true or `timeout` passed.
+  pub fn wait_for<F: Fn(&Received) -> bool>(&self, timeout: std::time::Duration, f: F) -> bool {
+    let start = std::time::Instant::now();
+    while start.elapsed() < timeout {
+      if f(&self.received()) { return true }
+      std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    f(&self.received())
+  }
+}
+
+#[test]
+fn test_rtmp_server() {
+  let mut value = Vec::new();
+  let object = Amf::object(&[("app", Amf::str("live")), ("tcUrl", Amf::str("rtmp://127.0.0.1/live")), ("audioCodecs", Amf::Number(3191.))]);
+  for i in [Amf::str("connect"), Amf::Number(1.), object.clone(), Amf::Null, Amf::Array(vec![Amf::Bool(true), Amf::Undefined])] {
+    i.encode(&mut value);
+  }
+  assert_eq!(Amf::decode_all(&value).unwrap(), vec![Amf::str("connect"), Amf::Number(1.), object.clone(), Amf::Null, Amf::Array(vec![Amf::Bool(true), Amf::Undefined])]);
+  assert!(Amf::decode_all(&value[..value.len() - 1]).is_err());
+
+  let server = RtmpServer::bind().unwrap();
+  let mut client = Connection::new(TcpStream::connect(server.addr()).unwrap());
+  client.handshake().unwrap();
+  // longer than a chunk of 128 bytes
+  client.write_command(0, &[Amf::str("connect"), Amf::Number(1.), Amf::Object(vec![("app".to_string(), Amf::str("live")), ("flashVer".to_string(), Amf::str(&"x".repeat(200)))])]).unwrap();
+  let result = loop {
+    let message = client.read_message().unwrap();
+    if message.type_id == COMMAND { break Amf::decode_all(&message.payload).unwrap() }
+  };
+  assert_eq!(result[..2], [Amf::str("_result"), Amf::Number(1.)]);
+  client.write_command(0, &[Amf::str("createStream"), Amf::Number(2.), Amf::Null]).unwrap();
+  assert_eq!(Amf::decode_all(&client.read_message().unwrap().payload).unwrap(), [Amf::str("_result"), Amf::Number(2.), Amf::Null, Amf::Number(1.)]);
+  client.write_command(1, &[Amf::str("publish"), Amf::Number(3.), Amf::Null, Amf::str("key"), Amf::str("live")]).unwrap();
+  assert_eq!(client.read_message().unwrap().stream_id, 1);
+
+  client.write_control(SET_CHUNK_SIZE, 4096u32.to_be_bytes().to_vec()).unwrap();
+  client.write_chunk_size = 4096;
+  client.write_message(4, &Message { type_id: VIDEO, stream_id: 1, timestamp: 33, payload: vec![0x17; 1000] }).unwrap();
+  assert!(server.wait_for(std::time::Duration::from_secs(5), |i| i.video_bytes == 1000));
+  assert_eq!(server.received(), Received { app: Some("live".to_string()), published: Some("key".to_string()), video_bytes: 1000, video_messages: 1, ..Default::default() });
+}
+
+/// Needs libobs with `obs-outputs`, `rtmp-services` and `obs-x264`.
+#[test]
+#[ignore]
+fn test_stream_to_local_server() {
+  use super::{stream::{StreamService, StreamSetting}, OutputEvent, OutputState};
+
+  let server = RtmpServer::bind().unwrap();
+  let mut obs = crate::init_obs().unwrap();
+  let setting = StreamSetting {
+    service: StreamService::Custom { server: server.url(), key: "test".to_string(), use_auth: false, username: String::new(), password: String::new() },
+    video_bitrate: 1000,
+    ..Default::default()
+  };
+  let mut stream = obs.create_stream("stream", &setting).unwrap();
+  let (sender, events) = std::sync::mpsc::channel();
+  stream.on_event(move |event| { let _ = sender.send(event); }).unwrap();
+  stream.start().unwrap();
+  assert!(server.wait_for(std::time::Duration::from_secs(20), |i| i.video_bytes > 0 && i.audio_bytes > 0));
+  assert!(events.try_iter().any(|i| matches!(i, OutputEvent::Started { .. })));
+  let received = server.received();
+  assert_eq!((received.app.as_deref(), received.published.as_deref()), (Some("live"), Some("test")));
+  assert_eq!(stream.state(), OutputState::Active);
+
+  stream.stop();
+  let stopped = events.iter().find(|i| matches!(i, OutputEvent::Stopped { .. })).unwrap();
+  assert_eq!(stopped, OutputEvent::Stopped { output: "stream".to_string(), code: 0, message: None });
+  assert_eq!(stream.state(), OutputState::Stopped);
+}
@@ -0,0 +1,363 @@
+//! Streaming services of `rtmp_common`, as listed in `services.json` of the `rtmp-services` plugin.
+//!
+//! ```json
+//! {
+//!   "format_version": 5,
+//!   "services": [{
+//!     "name": "Twitch",
+//!     "servers": [{ "name": "Default", "url": "rtmp://live.twitch.tv/app" }],
+//!     "supported video codecs": ["h264", "hevc", "av1"],
+//!     "recommended": { "keyint": 2, "max video bitrate": 6000, "max audio bitrate": 320, "max fps": 60 }
+//!   }]
+//! }
+//! ```
+//! The plugin updates its copy in `plugin_config/rtmp-services` from the network,
+//! that one is used when present like the plugin does.
+//!
+//! see `plugins/rtmp-services/rtmp-common.c` and `plugins/rtmp-services/data/schema/service-schema-v5.json`
+
+use std::{ffi::{c_void, CStr, CString}, path::{Path, PathBuf}};
+
+use obs_wrapper::obs_sys::{bfree, obs_find_module_file, obs_get_module, obs_module_get_config_path};
+
+use super::stream::{StreamService, STREAM_OUTPUT_ID};
+use crate::obs::{Error, Obs, Result};
+
+pub const MODULE: &str = "rtmp-services";
+pub const FILE_NAME: &str = "services.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct Server {
+  pub name: String,
+  pub url: String,
+}
+
+/// A resolution and frame rate with a lower bitrate limit than the service.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct BitrateLimit {
+  /// e.g. `1280x720`
+  pub res: String,
+  pub fps: u32,
+  #[serde(rename = "max bitrate")]
+  pub max_bitrate: u32,
+}
+
+/// Settings OBS Studio applies or enforces when "Ignore streaming service setting recommendations" is off.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct Recommended {
+  /// keyframe interval in seconds
+  pub keyint: Option<u32>,
+  pub profile: Option<String>,
+  pub bframes: Option<u32>,
+  /// kbps
+  #[serde(rename = "max video bitrate")]
+  pub max_video_bitrate: Option<u32>,
+  #[serde(rename = "max audio bitrate")]
+  pub max_audio_bitrate: Option<u32>,
+  pub x264opts: Option<String>,
+  /// output type, `rtmp_output` by default
+  pub output: Option<String>,
+  /// e.g. `1920x1080`, any when empty
+  #[serde(rename = "supported resolutions", default)]
+  pub supported_resolutions: Vec<String>,
+  #[serde(rename = "max fps")]
+  pub max_fps: Option<u32>,
+  #[serde(rename = "bitrate matrix", default)]
+  pub bitrate_matrix: Vec<BitrateLimit>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct Service {
+  pub name: String,
+  /// listed first by OBS Studio, the others are behind "Show all"
+  #[serde(default)]
+  pub common: bool,
+  /// former names, a profile could still refer to
+  #[serde(default)]
+  pub alt_names: Vec<String>,
+  /// `RTMP`, `RTMPS`, `HLS`, `SRT`, `RIST` or `WHIP`, guessed from the server urls when missing
+  pub protocol: Option<String>,
+  pub servers: Vec<Server>,
+  /// `h264` only when missing
+  #[serde(rename = "supported video codecs", default)]
+  pub supported_video_codecs: Vec<String>,
+  /// `aac` only when missing
+  #[serde(rename = "supported audio codecs", default)]
+  pub supported_audio_codecs: Vec<String>,
+  #[serde(default)]
+  pub recommended: Recommended,
+  pub stream_key_link: Option<String>,
+  pub more_info_link: Option<String>,
+}
+
+impl Service {
+  /// `protocol`, or the scheme of the first server
+  pub fn protocol(&self) -> String {
+    if let Some(protocol) = &self.protocol {
+      return protocol.to_uppercase()
+    }
+    let url = self.servers.first().map(|i| i.url.as_str()).unwrap_or_default();
+    match url.split_once("://") {
+      Some((scheme, _)) => scheme.to_uppercase(),
+      None => "RTMP".to_string(),
+    }
+  }
+
+  /// Output type the service is streamed with, see `rtmp_common_get_output_type`.
+  pub fn output_type(&self) -> String {
+    if let Some(output) = &self.recommended.output {
+      return output.clone()
+    }
+    match self.protocol().as_str() {
+      "HLS" => "ffmpeg_hls_muxer",
+      "SRT" | "RIST" => "ffmpeg_mpegts_muxer",
+      "WHIP" => "whip_output",
+      _ => STREAM_OUTPUT_ID,
+    }.to_string()
+  }
+
+  pub fn video_codecs(&self) -> Vec<&str> {
+    match self.supported_video_codecs.is_empty() {
+      true => vec!["h264"],
+      false => self.supported_video_codecs.iter().map(String::as_str).collect(),
+    }
+  }
+
+  pub fn audio_codecs(&self) -> Vec<&str> {
+    match self.supported_audio_codecs.is_empty() {
+      true => vec!["aac"],
+      false => self.supported_audio_codecs.iter().map(String::as_str).collect(),
+    }
+  }
+
+  /// `1920x1080`, also matching the `1920x1080@60` variants some services list
+  pub fn supports_resolution(&self, (width, height): (u32, u32)) -> bool {
+    let resolution = format!("{width}x{height}");
+    let resolutions = &self.recommended.supported_resolutions;
+    resolutions.is_empty() || resolutions.iter().any(|i| i.split('@').next() == Some(resolution.as_str()))
+  }
+
+  /// The bitrate matrix entry of this resolution and frame rate, or the maximum of the service.
+  pub fn max_video_bitrate(&self, (width, height): (u32, u32), fps: f64) -> Option<u32> {
+    let resolution = format!("{width}x{height}");
+    let limit = self.recommended.bitrate_matrix.iter()
+      .find(|i| i.res == resolution && i.fps as f64 == fps.round())
+      .map(|i| i.max_bitrate);
+    limit.or(self.recommended.max_video_bitrate)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct Services {
+  pub format_version: u32,
+  pub services: Vec<Service>,
+}
+
+impl Services {
+  pub fn parse(content: &str) -> Result<Self> {
+    Ok(serde_json::from_str(content)?)
+  }
+
+  pub fn load(path: &Path) -> Result<Self> {
+    Self::parse(&std::fs::read_to_string(path)?)
+  }
+
+  /// by name, or a former name of a service
+  pub fn find(&self, name: &str) -> Option<&Service> {
+    self.services.iter().find(|i| i.name == name)
+      .or_else(|| self.services.iter().find(|i| i.alt_names.iter().any(|alt| alt == name)))
+  }
+
+  /// Where `stream` does not follow the recommendations of its service, only for `rtmp_common`.
+  pub fn validate(&self, stream: &StreamCheck) -> Vec<ServiceIssue> {
+    let StreamService::Common { service, server, .. } = &stream.service else { return Vec::new() };
+    let Some(found) = self.find(service) else {
+      return vec![ServiceIssue::UnknownService { service: service.clone() }]
+    };
+    let mut issues = Vec::new();
+    // resolved by `rtmp-services` itself, e.g. the closest ingest of Twitch
+    if server != "auto" && !found.servers.iter().any(|i| &i.url == server) {
+      issues.push(ServiceIssue::UnknownServer { server: server.clone() });
+    }
+    let output = found.output_type();
+    if output != STREAM_OUTPUT_ID {
+      issues.push(ServiceIssue::UnsupportedOutput { output });
+    }
+    let codecs = found.video_codecs();
+    if !codecs.contains(&stream.video_codec.as_str()) {
+      issues.push(ServiceIssue::UnsupportedVideoCodec { codec: stream.video_codec.clone(), supported: codecs.iter().map(|i| i.to_string()).collect() });
+    }
+    let codecs = found.audio_codecs();
+    if !codecs.contains(&stream.audio_codec.as_str()) {
+      issues.push(ServiceIssue::UnsupportedAudioCodec { codec: stream.audio_codec.clone(), supported: codecs.iter().map(|i| i.to_string()).collect() });
+    }
+    match found.max_video_bitrate(stream.output_size, stream.fps) {
+      Some(max) if stream.video_bitrate > max => issues.push(ServiceIssue::VideoBitrateTooHigh { bitrate: stream.video_bitrate, max }),
+      _ => {},
+    }
+    match found.recommended.max_audio_bitrate {
+      Some(max) if stream.audio_bitrate > max => issues.push(ServiceIssue::AudioBitrateTooHigh { bitrate: stream.audio_bitrate, max }),
+      _ => {},
+    }
+    if !found.supports_resolution(stream.output_size) {
+      let (width, height) = stream.output_size;
+      issues.push(ServiceIssue::UnsupportedResolution { resolution: format!("{width}x{height}"), supported: found.recommended.supported_resolutions.clone() });
+    }
+    match found.recommended.max_fps {
+      Some(max) if stream.fps > max as f64 => issues.push(ServiceIssue::FpsTooHigh { fps: stream.fps, max }),
+      _ => {},
+    }
+    issues
+  }
+}
+
+/// What a stream sends, checked by [`Services::validate`].
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct StreamCheck {
+  pub service: StreamService,
+  /// as in `supported video codecs`, e.g. `h264`
+  pub video_codec: String,
+  pub audio_codec: String,
+  /// kbps
+  pub video_bitrate: u32,
+  pub audio_bitrate: u32,
+  pub output_size: (u32, u32),
+  pub fps: f64,
+}
+
+/// Codec of a video encoder id, including the hardware ones of OBS Studio, e.g. `jim_av1_nvenc` is `av1`.
+pub fn video_codec(id: &str) -> &'static str {
+  let id = id.to_lowercase();
+  if id.contains("av1") {
+    "av1"
+  } else if id.contains("hevc") || id.contains("h265") {
+    "hevc"
+  } else {
+    "h264"
+  }
+}
+
+pub fn audio_codec(id: &str) -> &'static str {
+  match id.to_lowercase().contains("opus") {
+    true => "opus",
+    false => "aac",
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServiceIssue {
+  /// not in `services.json`, the stream could not start
+  UnknownService { service: String },
+  /// not one of the servers of the service, it could have been removed
+  UnknownServer { server: String },
+  /// the service is not streamed over RTMP
+  UnsupportedOutput { output: String },
+  UnsupportedVideoCodec { codec: String, supported: Vec<String> },
+  UnsupportedAudioCodec { codec: String, supported: Vec<String> },
+  /// OBS Studio lowers it to `max` unless recommendations are ignored
+  VideoBitrateTooHigh { bitrate: u32, max: u32 },
+  AudioBitrateTooHigh { bitrate: u32, max: u32 },
+  UnsupportedResolution { resolution: String, supported: Vec<String> },
+  FpsTooHigh { fps: f64, max: u32 },
+}
+
+/// `bfree` the path returned by `obs_find_module_file` or `obs_module_get_config_path`.
+unsafe fn take_path(ptr: *mut std::os::raw::c_char) -> Option<PathBuf> {
+  if ptr.is_null() { return None }
+  let path = PathBuf::from(CStr::from_ptr(ptr).to_string_lossy().to_string());
+  bfree(ptr as *mut c_void);
+  Some(path)
+}
+
+impl Obs {
+  /// `services.json` of the loaded `rtmp-services`, its updated copy in the module config folder first.
+  pub fn services_file(&self) -> Result<PathBuf> {
+    if !self.ready() {
+      return Err(Error::NotInitialized)
+    }
+    let (module, file) = (CString::new(MODULE)?, CString::new(FILE_NAME)?);
+    let module = unsafe { obs_get_module(module.as_ptr()) };
+    if module.is_null() {
+      return Err(Error::ModuleNotLoaded(MODULE.to_string()))
+    }
+    let config = unsafe { take_path(obs_module_get_config_path(module, file.as_ptr())) };
+    if let Some(config) = config.filter(|i| i.is_file()) {
+      return Ok(config)
+    }
+    unsafe { take_path(obs_find_module_file(module, file.as_ptr())) }
+      .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, format!("{MODULE}/{FILE_NAME}")).into())
+  }
+
+  pub fn stream_services(&self) -> Result<Services> {
+    Services::load(&self.services_file()?)
+  }
+}
+
+#[test]
+fn test_services() {
+  let services = Services::parse(r#"{
+    "format_version": 5,
+    "services": [{
+      "name": "Twitch",
+      "common": true,
+      "alt_names": ["Twitch.tv"],
+      "servers": [{"name": "Default", "url": "rtmp://live.twitch.tv/app"}],
+      "supported video codecs": ["h264", "hevc", "av1"],
+      "recommended": {
+        "keyint": 2, "max video bitrate": 6000, "max audio bitrate": 320, "max fps": 60,
+        "bitrate matrix": [{"res": "1280x720", "fps": 30, "max bitrate": 3000}]
+      }
+    }, {
+      "name": "HLS Service",
+      "protocol": "HLS",
+      "servers": [{"name": "Default", "url": "https://example.com/hls"}],
+      "recommended": {"supported resolutions": ["1920x1080", "1280x720@60"]}
+    }]
+  }"#).unwrap();
+  let twitch = services.find("Twitch.tv").unwrap();
+  assert_eq!((twitch.name.as_str(), twitch.protocol(), twitch.output_type()), ("Twitch", "RTMP".to_string(), "rtmp_output".to_string()));
+  assert_eq!((twitch.video_codecs(), twitch.audio_codecs()), (vec!["h264", "hevc", "av1"], vec!["aac"]));
+  assert_eq!(twitch.max_video_bitrate((1280, 720), 29.97), Some(3000));
+  assert_eq!(twitch.max_video_bitrate((1920, 1080), 60.), Some(6000));
+  let hls = services.find("HLS Service").unwrap();
+  assert_eq!(hls.output_type(), "ffmpeg_hls_muxer");
+  assert!(hls.supports_resolution((1280, 720)) && !hls.supports_resolution((2560, 1440)));
+  assert_eq!(services.find("Unknown"), None);
+
+  let check = StreamCheck {
+    service: StreamService::Common { service: "Twitch".to_string(), server: "auto".to_string(), key: String::new() },
+    video_codec: "h264".to_string(),
+    audio_codec: "aac".to_string(),
+    video_bitrate: 6000,
+    audio_bitrate: 160,
+    output_size: (1920, 1080),
+    fps: 60.,
+  };
+  assert!(services.validate(&check).is_empty());
+  let check = StreamCheck {
+    service: StreamService::Common { service: "Twitch".to_string(), server: "rtmp://old.twitch.tv/app".to_string(), key: String::new() },
+    audio_codec: "opus".to_string(),
+    video_bitrate: 8000,
+    fps: 120.,
+    ..check
+  };
+  assert_eq!(services.validate(&check), vec![
+    ServiceIssue::UnknownServer { server: "rtmp://old.twitch.tv/app".to_string() },
+    ServiceIssue::UnsupportedAudioCodec { codec: "opus".to_string(), supported: vec!["aac".to_string()] },
+    ServiceIssue::VideoBitrateTooHigh { bitrate: 8000, max: 6000 },
+    ServiceIssue::FpsTooHigh { fps: 120., max: 60 },
+  ]);
+  let check = StreamCheck { service: StreamService::Common { service: "HLS Service".to_string(), server: "auto".to_string(), key: String::new() }, output_size: (2560, 1440), fps: 30., ..check };
+  assert_eq!(services.validate(&check), vec![
+    ServiceIssue::UnsupportedOutput { output: "ffmpeg_hls_muxer".to_string() },
+    ServiceIssue::UnsupportedAudioCodec { codec: "opus".to_string(), supported: vec!["aac".to_string()] },
+    ServiceIssue::UnsupportedResolution { resolution: "2560x1440".to_string(), supported: vec!["1920x1080".to_string(), "1280x720@60".to_string()] },
+  ]);
+  let check = StreamCheck { service: StreamService::Common { service: "Gone".to_string(), server: "auto".to_string(), key: String::new() }, ..check };
+  assert_eq!(services.validate(&check), vec![ServiceIssue::UnknownService { service: "Gone".to_string() }]);
+  assert!(services.validate(&StreamCheck { service: StreamService::default(), ..check }).is_empty());
+
+  assert_eq!((video_codec("jim_av1_nvenc"), video_codec("com.apple.videotoolbox.videoencoder.ave.hevc"), video_codec("x264")), ("av1", "hevc", "h264"));
+  assert_eq!((audio_codec("ffmpeg_opus"), audio_codec("CoreAudio_AAC")), ("opus", "aac"));
+}
@@ -0,0 +1,360 @@
+//! A stream through `rtmp_output`, sent to the server of a `rtmp_custom` or `rtmp_common` service.
+//!
+//! ```text
+//! obs_get_video() -> video encoder ─┐
+//!                                   ├─> rtmp_output -> rtmp://server/app/key
+//! obs_get_audio() -> audio encoder ─┘        ^
+//!                                   rtmp_custom / rtmp_common
+//! ```
+//! The service gives the server and the key to the output and clamps the encoder settings to its limits.
+//! `rtmp-services` and `obs-outputs` should be loaded.
+//!
+//! see `UI/window-basic-main-outputs.cpp` `SimpleOutput::StartStreaming`
+
+use std::{ffi::CString, sync::{mpsc, Arc}, thread::JoinHandle, time::Duration};
+
+use obs_wrapper::{obs_sys::{obs_output_active, obs_output_set_audio_encoder, obs_output_set_reconnect_settings, obs_output_set_service, obs_output_set_video_encoder, obs_service_apply_encoder_settings, obs_service_create, obs_service_release, obs_service_t}, wrapper::PtrWrapper as _};
+use serde_json::{json, Value};
+
+use super::{calldata_int, calldata_string, data, output_state, output_stats, stop_reason, AudioEncoder, Encoder, Output, OutputEvent, OutputState, OutputStats, VideoEncoder};
+use crate::obs::{Error, Obs, Result};
+
+pub const STREAM_OUTPUT_ID: &str = "rtmp_output";
+/// how often [`OutputEvent::Stats`] is sent while streaming
+pub const STATS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// `service.json` of a profile
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamService {
+  /// `rtmp_custom`, any server
+  Custom {
+    /// e.g. `rtmp://127.0.0.1/live`
+    server: String,
+    key: String,
+    #[serde(default)]
+    use_auth: bool,
+    #[serde(default)]
+    username: String,
+    #[serde(default)]
+    password: String,
+  },
+  /// `rtmp_common`, a service listed in `services.json` of `rtmp-services`
+  Common {
+    /// name of the service, e.g. `Twitch`
+    service: String,
+    /// url of one of its servers, `auto` for the ones picking the closest
+    server: String,
+    key: String,
+  },
+}
+
+impl Default for StreamService {
+  fn default() -> Self {
+    Self::Custom { server: String::new(), key: String::new(), use_auth: false, username: String::new(), password: String::new() }
+  }
+}
+
+impl StreamService {
+  pub fn id(&self) -> &'static str {
+    match self {
+      Self::Custom { .. } => "rtmp_custom",
+      Self::Common { .. } => "rtmp_common",
+    }
+  }
+
+  pub fn server(&self) -> &str {
+    match self {
+      Self::Custom { server, .. } | Self::Common { server, .. } => server,
+    }
+  }
+
+  /// `service.json` of a profile, `{"type": "rtmp_common", "settings": {"service": "Twitch", ...}}`.
+  /// `None` for the other types, e.g. `whip_custom`.
+  pub fn from_profile(value: &Value) -> Option<Self> {
+    let settings = value.get("settings").cloned().unwrap_or_default();
+    let string = |key: &str| settings.get(key).and_then(Value::as_str).unwrap_or_default().to_string();
+    match value.get("type")?.as_str()? {
+      "rtmp_custom" => Some(Self::Custom {
+        server: string("server"),
+        key: string("key"),
+        use_auth: settings.get("use_auth").and_then(Value::as_bool).unwrap_or_default(),
+        username: string("username"),
+        password: string("password"),
+      }),
+      "rtmp_common" => Some(Self::Common { service: string("service"), server: string("server"), key: string("key") }),
+      _ => None,
+    }
+  }
+
+  /// Settings of the service, `bwtest` appends `?bandwidthtest` to the key so the server drops the stream.
+  pub fn settings(&self, bandwidth_test: bool) -> Value {
+    match self {
+      Self::Custom { server, key, use_auth, username, password } => json!({
+        "server": server, "key": key, "use_auth": use_auth, "username": username, "password": password, "bwtest": bandwidth_test,
+      }),
+      Self::Common { service, server, key } => json!({
+        "service": service, "server": server, "key": key, "bwtest": bandwidth_test,
+      }),
+    }
+  }
+}
+
+/// `Output.Reconnect`, `Output.RetryDelay` and `Output.MaxRetries` of `basic.ini`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS, derivative::Derivative)]
+#[derivative(Default)]
+#[serde(default)]
+pub struct ReconnectSetting {
+  #[derivative(Default(value = "true"))]
+  pub enabled: bool,
+  #[derivative(Default(value = "2"))]
+  pub retry_delay_sec: u32,
+  #[derivative(Default(value = "25"))]
+  pub max_retries: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, ts_rs::TS, derivative::Derivative)]
+#[derivative(Default)]
+#[serde(default)]
+pub struct StreamSetting {
+  pub service: StreamService,
+  pub video_encoder: VideoEncoder,
+  pub audio_encoder: AudioEncoder,
+  /// kbps, sent with constant bitrate
+  #[derivative(Default(value = "2500"))]
+  pub video_bitrate: u32,
+  #[derivative(Default(value = "160"))]
+  pub audio_bitrate: u32,
+  /// other settings of the video encoder, as [`RecordingSetting::video_settings`](super::RecordingSetting::video_settings)
+  pub video_settings: Value,
+  pub audio_settings: Value,
+  pub reconnect: ReconnectSetting,
+  /// connect and send without going live, the key gets `?bandwidthtest`
+  pub bandwidth_test: bool,
+}
+
+impl StreamSetting {
+  /// `settings` with the bitrate of the stream, constant unless the encoder is set otherwise
+  fn encoder_settings(settings: &Value, bitrate: u32) -> Value {
+    let mut settings = match settings {
+      Value::Object(_) => settings.clone(),
+      _ => json!({}),
+    };
+    settings["bitrate"] = bitrate.into();
+    if settings.get("rate_control").is_none() {
+      settings["rate_control"] = "CBR".into();
+    }
+    settings
+  }
+
+  pub fn video_encoder_settings(&self) -> Value {
+    Self::encoder_settings(&self.video_settings, self.video_bitrate)
+  }
+
+  pub fn audio_encoder_settings(&self) -> Value {
+    let mut settings = Self::encoder_settings(&self.audio_settings, self.audio_bitrate);
+    // audio encoders only have a bitrate
+    settings.as_object_mut().unwrap().remove("rate_control");
+    settings
+  }
+
+  /// `(retries, delay)` for `obs_output_set_reconnect_settings`
+  pub fn reconnect_settings(&self) -> (i32, i32) {
+    match self.reconnect.enabled {
+      true => (self.reconnect.max_retries as i32, self.reconnect.retry_delay_sec as i32),
+      false => (0, 0),
+    }
+  }
+}
+
+/// An owned `obs_service_t`.
+#[derive(Debug)]
+pub struct Service(*mut obs_service_t);
+
+impl Service {
+  pub fn create(service: &StreamService, name: &str, bandwidth_test: bool) -> Result<Self> {
+    let (id, name) = (CString::new(service.id())?, CString::new(name)?);
+    let settings = data(&service.settings(bandwidth_test))?;
+    let ptr = unsafe { obs_service_create(id.as_ptr(), name.as_ptr(), settings.as_ptr_mut(), std::ptr::null_mut()) };
+    if ptr.is_null() {
+      return Err(Error::NulPointer("obs_service_create"))
+    }
+    Ok(Self(ptr))
+  }
+
+  pub fn as_ptr(&self) -> *mut obs_service_t {
+    self.0
+  }
+}
+
+impl Drop for Service {
+  fn drop(&mut self) {
+    unsafe { obs_service_release(self.0) }
+  }
+}
+
+/// Sends [`OutputEvent::Stats`] every [`STATS_INTERVAL`] while the output is active, stopped on drop.
+#[derive(Debug)]
+struct Monitor {
+  stop: mpsc::Sender<()>,
+  handle: Option<JoinHandle<()>>,
+}
+
+impl Monitor {
+  fn spawn<F: Fn(OutputEvent) + Send + Sync + 'static>(output: &Output, callback: Arc<F>) -> Self {
+    let (stop, receiver) = mpsc::channel();
+    // the monitor does not keep the output alive
+    let weak = output.weak();
+    let (name, flags) = (output.name(), output.flags.clone());
+    let handle = std::thread::spawn(move || {
+      while let Err(mpsc::RecvTimeoutError::Timeout) = receiver.recv_timeout(STATS_INTERVAL) {
+        let Some(stats) = weak.with(|ptr| unsafe { obs_output_active(ptr).then(|| (output_state(ptr, &flags), output_stats(ptr))) }) else { break };
+        if let Some((state, stats)) = stats {
+          callback(OutputEvent::Stats { output: name.clone(), state, stats });
+        }
+      }
+    });
+    Self { stop, handle: Some(handle) }
+  }
+}
+
+impl Drop for Monitor {
+  fn drop(&mut self) {
+    let _ = self.stop.send(());
+    if let Some(handle) = self.handle.take() {
+      let _ = handle.join();
+    }
+  }
+}
+
+/// A stream with its own encoders and service.
+#[derive(Debug)]
+pub struct Streaming {
+  /// joined before the output is released
+  monitor: Option<Monitor>,
+  output: Output,
+  _video_encoder: Encoder,
+  _audio_encoder: Encoder,
+  _service: Service,
+  server: String,
+}
+
+impl Streaming {
+  pub fn output(&self) -> &Output {
+    &self.output
+  }
+
+  pub fn server(&self) -> &str {
+    &self.server
+  }
+
+  pub fn state(&self) -> OutputState {
+    self.output.state()
+  }
+
+  pub fn stats(&self) -> OutputStats {
+    self.output.stats()
+  }
+
+  /// Returns once the connection is started, [`OutputEvent::Started`] is sent when connected
+  /// and [`OutputEvent::Stopped`] if it fails.
+  pub fn start(&mut self) -> Result<()> {
+    self.output.start()
+  }
+
+  pub fn stop(&mut self) {
+    self.output.stop()
+  }
+
+  /// Call `callback` with the connection events and the [`OutputEvent::Stats`] of the stream.
+  pub fn on_event<F: Fn(OutputEvent) + Send + Sync + 'static>(&mut self, callback: F) -> Result<()> {
+    let callback = Arc::new(callback);
+    let output = self.output.name();
+    {
+      let (callback, output) = (callback.clone(), output.clone());
+      self.output.connect("start", move |_| callback(OutputEvent::Started { output: output.clone() }))?;
+    }
+    {
+      let (callback, output) = (callback.clone(), output.clone());
+      self.output.connect("reconnect", move |cd| {
+        let timeout_sec = unsafe { calldata_int(cd, c"timeout_sec") }.unwrap_or_default();
+        callback(OutputEvent::Reconnecting { output: output.clone(), timeout_sec })
+      })?;
+    }
+    {
+      let (callback, output) = (callback.clone(), output.clone());
+      self.output.connect("reconnect_success", move |_| callback(OutputEvent::Reconnected { output: output.clone() }))?;
+    }
+    {
+      let (callback, output) = (callback.clone(), output.clone());
+      self.output.connect("stop", move |cd| {
+        let code = unsafe { calldata_int(cd, c"code") }.unwrap_or_default();
+        let message = match code {
+          0 => None,
+          _ => unsafe { calldata_string(cd, c"last_error") }.filter(|i| !i.is_empty()).or_else(|| stop_reason(code).map(str::to_string)),
+        };
+        info!(output, code, ?message, "stream stopped");
+        callback(OutputEvent::Stopped { output: output.clone(), code, message })
+      })?;
+    }
+    self.monitor = Some(Monitor::spawn(&self.output, callback));
+    Ok(())
+  }
+}
+
+impl Obs {
+  /// Create the encoders of `setting` on the main video and the first audio mix, and an output sending them to the server of `setting.service`.
+  /// Needs `reset_video` and `reset_audio` first, the stream does not connect until `start`.
+  pub fn create_stream(&mut self, name: &str, setting: &StreamSetting) -> Result<Streaming> {
+    if !self.ready() {
+      return Err(Error::NotInitialized)
+    }
+    if setting.service.server().is_empty() {
+      return Err(Error::Output("the stream server is not set".to_string()))
+    }
+    let service = Service::create(&setting.service, &format!("{name} service"), setting.bandwidth_test)?;
+    let video_settings = data(&setting.video_encoder_settings())?;
+    let audio_settings = data(&setting.audio_encoder_settings())?;
+    // e.g. the maximum bitrate of a `rtmp_common` service
+    unsafe { obs_service_apply_encoder_settings(service.as_ptr(), video_settings.as_ptr_mut(), audio_settings.as_ptr_mut()) };
+    let video_encoder = Encoder::video(setting.video_encoder.id(), &format!("{name} video"), &video_settings)?;
+    let audio_encoder = Encoder::audio(setting.audio_encoder.id(), &format!("{name} audio"), &audio_settings, 0)?;
+    let output = Output::create(STREAM_OUTPUT_ID, name, &data(&Value::Null)?)?;
+    let (retries, delay) = setting.reconnect_settings();
+    unsafe {
+      obs_output_set_video_encoder(output.as_ptr(), video_encoder.as_ptr());
+      obs_output_set_audio_encoder(output.as_ptr(), audio_encoder.as_ptr(), 0);
+      obs_output_set_service(output.as_ptr(), service.as_ptr());
+      obs_output_set_reconnect_settings(output.as_ptr(), retries, delay);
+    }
+    let server = setting.service.server().to_string();
+    info!(name, service=setting.service.id(), server, bandwidth_test=setting.bandwidth_test, "stream created");
+    Ok(Streaming { monitor: None, output, _video_encoder: video_encoder, _audio_encoder: audio_encoder, _service: service, server })
+  }
+}
+
+#[test]
+fn test_stream() {
+  let setting: StreamSetting = serde_json::from_value(json!({
+    "service": {"type": "common", "service": "Twitch", "server": "auto", "key": "live_123"},
+    "video_bitrate": 6000, "video_settings": {"preset": "veryfast", "bitrate": 1000},
+    "reconnect": {"max_retries": 5},
+  })).unwrap();
+  assert_eq!(setting.service.id(), "rtmp_common");
+  assert_eq!(setting.service.settings(false), json!({"service": "Twitch", "server": "auto", "key": "live_123", "bwtest": false}));
+  assert_eq!(setting.video_encoder_settings(), json!({"preset": "veryfast", "bitrate": 6000, "rate_control": "CBR"}));
+  assert_eq!(setting.audio_encoder_settings(), json!({"bitrate": 160}));
+  assert_eq!(setting.reconnect_settings(), (5, 2));
+  assert_eq!(StreamSetting { reconnect: ReconnectSetting { enabled: false, ..Default::default() }, ..setting }.reconnect_settings(), (0, 0));
+
+  let service = json!({"type": "rtmp_custom", "settings": {"server": "rtmp://127.0.0.1/live", "key": "abc", "use_auth": true}});
+  assert_eq!(StreamService::from_profile(&service), Some(StreamService::Custom {
+    server: "rtmp://127.0.0.1/live".to_string(), key: "abc".to_string(), use_auth: true, username: String::new(), password: String::new(),
+  }));
+  assert_eq!(StreamService::from_profile(&json!({"type": "whip_custom", "settings": {}})), None);
+
+  let setting = StreamSetting::default();
+  assert_eq!(setting.service, StreamService::default());
+  assert_eq!(setting.service.settings(true)["bwtest"], true);
+  assert_eq!((setting.video_bitrate, setting.audio_bitrate, setting.reconnect.enabled), (2500, 160, true));
+}
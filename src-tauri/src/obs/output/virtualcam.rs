@@ -0,0 +1,166 @@
+//! A virtual camera, the `virtualcam_output` of `linux-v4l2` writing raw frames into a v4l2loopback device.
+//!
+//! ```text
+//! program:          obs_get_video() ──────────────────────────┐
+//! scene or source:  obs_view_set_source -> obs_view_add -> video ┴─> virtualcam_output -> /dev/videoN
+//! ```
+//! The output picks the first free loopback device itself, so only the source is configured here.
+//!
+//! see `plugins/linux-v4l2/v4l2-output.c`
+
+use std::{ffi::CString, path::{Path, PathBuf}};
+
+use obs_wrapper::{obs_sys::{obs_get_audio, obs_get_source_by_name, obs_get_video, obs_output_set_media, obs_scene_from_source, obs_view_add, obs_view_create, obs_view_destroy, obs_view_remove, obs_view_set_source, obs_view_t, video_t}, source::SourceRef, wrapper::PtrWrapper as _};
+use serde_json::Value;
+
+use super::{data, Output, OutputState};
+use crate::obs::{Error, Obs, Result};
+
+pub const VIRTUALCAM_OUTPUT_ID: &str = "virtualcam_output";
+
+/// What the virtual camera shows.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum VirtualCamSource {
+  /// the output of channel 0, what a recording or a stream gets
+  #[default]
+  Program,
+  Scene { name: String },
+  /// a single source without the scene around it, e.g. a camera with its filters
+  Source { name: String },
+}
+
+/// Loopback devices under `sys`, e.g. `/sys` gives `/dev/video10` for `/sys/devices/virtual/video4linux/video10`.
+/// v4l2loopback is the only driver creating virtual video4linux devices.
+pub fn loopback_devices_in(sys: &Path) -> Vec<PathBuf> {
+  let Ok(read_dir) = std::fs::read_dir(sys.join("devices/virtual/video4linux")) else { return Vec::new() };
+  let mut devices = read_dir.filter_map(|i| i.ok())
+    .map(|i| i.file_name().to_string_lossy().to_string())
+    .filter(|i| i.starts_with("video"))
+    .map(|i| Path::new("/dev").join(i))
+    .collect::<Vec<_>>();
+  devices.sort();
+  devices
+}
+
+pub fn loopback_devices() -> Vec<PathBuf> {
+  loopback_devices_in(Path::new("/sys"))
+}
+
+/// A view rendering a single source into its own video, removed on drop.
+#[derive(Debug)]
+struct View {
+  ptr: *mut obs_view_t,
+  video: *mut video_t,
+}
+
+impl View {
+  fn new(source: &SourceRef) -> Result<Self> {
+    unsafe {
+      let ptr = obs_view_create();
+      if ptr.is_null() {
+        return Err(Error::NulPointer("obs_view_create"))
+      }
+      // the view keeps its own reference
+      obs_view_set_source(ptr, 0, source.as_ptr_mut());
+      let video = obs_view_add(ptr);
+      if video.is_null() {
+        obs_view_set_source(ptr, 0, std::ptr::null_mut());
+        obs_view_destroy(ptr);
+        return Err(Error::NulPointer("obs_view_add"))
+      }
+      Ok(Self { ptr, video })
+    }
+  }
+}
+
+impl Drop for View {
+  fn drop(&mut self) {
+    unsafe {
+      obs_view_remove(self.ptr);
+      obs_view_set_source(self.ptr, 0, std::ptr::null_mut());
+      obs_view_destroy(self.ptr);
+    }
+  }
+}
+
+#[derive(Debug)]
+pub struct VirtualCam {
+  output: Output,
+  /// removed after the output, none for the program
+  _view: Option<View>,
+  source: VirtualCamSource,
+}
+
+impl VirtualCam {
+  pub fn output(&self) -> &Output {
+    &self.output
+  }
+
+  pub fn source(&self) -> &VirtualCamSource {
+    &self.source
+  }
+
+  pub fn state(&self) -> OutputState {
+    self.output.state()
+  }
+
+  /// Fails with [`Error::NoLoopbackDevice`] before the output tries to load the module with `pkexec`.
+  pub fn start(&mut self) -> Result<()> {
+    if loopback_devices().is_empty() {
+      return Err(Error::NoLoopbackDevice)
+    }
+    self.output.start()
+  }
+
+  pub fn stop(&mut self) {
+    self.output.stop()
+  }
+}
+
+impl Obs {
+  /// Create a virtual camera showing `source`, scenes and sources are looked up by name.
+  /// Needs `reset_video` first and the `linux-v4l2` module.
+  pub fn create_virtualcam(&mut self, name: &str, source: &VirtualCamSource) -> Result<VirtualCam> {
+    if !cfg!(target_os = "linux") {
+      return Err(Error::Output("the virtual camera is only available on linux".to_string()))
+    }
+    if !self.ready() {
+      return Err(Error::NotInitialized)
+    }
+    let view = match source {
+      VirtualCamSource::Program => None,
+      VirtualCamSource::Scene { name } | VirtualCamSource::Source { name } => {
+        let name_c = CString::new(name.as_str())?;
+        // the reference from `obs_get_source_by_name` is released with the `SourceRef`
+        let found = unsafe { SourceRef::from_raw_unchecked(obs_get_source_by_name(name_c.as_ptr())) }
+          .ok_or_else(|| Error::Output(format!("source `{name}` not found")))?;
+        let is_scene = unsafe { !obs_scene_from_source(found.as_ptr_mut()).is_null() };
+        if matches!(source, VirtualCamSource::Scene { .. }) && !is_scene {
+          return Err(Error::Output(format!("`{name}` is not a scene")))
+        }
+        Some(View::new(&found)?)
+      },
+    };
+    let video = view.as_ref().map_or_else(|| unsafe { obs_get_video() }, |i| i.video);
+    let output = Output::create(VIRTUALCAM_OUTPUT_ID, name, &data(&Value::Null)?)?;
+    unsafe { obs_output_set_media(output.as_ptr(), video, obs_get_audio()) };
+    info!(name, ?source, "virtual camera created");
+    Ok(VirtualCam { output, _view: view, source: source.clone() })
+  }
+}
+
+#[test]
+fn test_virtualcam() {
+  let source: VirtualCamSource = serde_json::from_value(serde_json::json!({"type": "scene", "name": "main"})).unwrap();
+  assert_eq!(source, VirtualCamSource::Scene { name: "main".to_string() });
+  assert_eq!(serde_json::to_value(VirtualCamSource::default()).unwrap(), serde_json::json!({"type": "program"}));
+
+  let dir = crate::testing::TempDir::new("virtualcam");
+  assert!(loopback_devices_in(&dir).is_empty());
+  let video4linux = dir.join("devices/virtual/video4linux");
+  for i in ["video10", "video2", "vbi0"] {
+    std::fs::create_dir_all(video4linux.join(i)).unwrap();
+  }
+  assert_eq!(loopback_devices_in(&dir), [PathBuf::from("/dev/video10"), PathBuf::from("/dev/video2")]);
+}
@@ -0,0 +1,321 @@
+//! Raw audio of a mix or of a single source, as planar float samples.
+//!
+//! ```text
+//! source -> audio capture callback(source, muted) ─┐  before the volume and the mixing
+//! sources -> mix 0..6 -> raw audio callback(mix) ──┴─> callback(frame)
+//!                                                  └─> try_send -> channel, dropped when full
+//! ```
+//! Both run on the audio thread like the video callbacks, see [`raw_video`](super::raw_video).
+//! [`WavWriter`] stores what they deliver, e.g. to listen to a test.
+//!
+//! see `libobs/obs-audio.c` and `libobs/media-io/audio-io.c` `audio_output_connect`
+
+use std::{ffi::c_void, io::{Seek, SeekFrom, Write}, sync::{atomic::{AtomicU64, Ordering}, mpsc, Arc}};
+
+use obs_wrapper::{obs_sys::{audio_convert_info, audio_data, audio_output_get_channels, audio_output_get_info, audio_output_get_sample_rate, obs_add_raw_audio_callback, obs_get_audio, obs_remove_raw_audio_callback, obs_source_add_audio_capture_callback, obs_source_remove_audio_capture_callback, obs_source_t, AUDIO_FORMAT_FLOAT_PLANAR, MAX_AUDIO_MIXES}, source::SourceRef, wrapper::PtrWrapper as _};
+
+use super::{source_name, Error, Obs, Result};
+
+/// Samples of every channel, borrowed from libobs and only valid in the callback.
+#[derive(Debug, Clone)]
+pub struct AudioFrameRef<'a> {
+  pub sample_rate: u32,
+  /// nanoseconds of `os_gettime_ns`, of the first sample
+  pub timestamp: u64,
+  /// only for a source, the samples are captured before the mute
+  pub muted: bool,
+  pub planes: Vec<&'a [f32]>,
+}
+
+impl AudioFrameRef<'_> {
+  pub fn frames(&self) -> usize {
+    self.planes.first().map_or(0, |i| i.len())
+  }
+
+  pub fn to_frame(&self) -> AudioFrame {
+    AudioFrame {
+      sample_rate: self.sample_rate,
+      timestamp: self.timestamp,
+      muted: self.muted,
+      planes: self.planes.iter().map(|i| i.to_vec()).collect(),
+    }
+  }
+}
+
+/// A copied frame, e.g. from [`Obs::raw_audio_channel`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioFrame {
+  pub sample_rate: u32,
+  pub timestamp: u64,
+  pub muted: bool,
+  pub planes: Vec<Vec<f32>>,
+}
+
+impl AudioFrame {
+  pub fn frames(&self) -> usize {
+    self.planes.first().map_or(0, |i| i.len())
+  }
+}
+
+type RawAudioFn = Box<dyn FnMut(&AudioFrameRef) + Send>;
+
+struct Context {
+  sample_rate: u32,
+  channels: usize,
+  callback: RawAudioFn,
+}
+
+impl Context {
+  unsafe fn call(&mut self, data: &audio_data, muted: bool) {
+    let planes = data.data.iter().take(self.channels).take_while(|i| !i.is_null())
+      .map(|i| std::slice::from_raw_parts(*i as *const f32, data.frames as usize))
+      .collect();
+    (self.callback)(&AudioFrameRef { sample_rate: self.sample_rate, timestamp: data.timestamp, muted, planes });
+  }
+}
+
+unsafe extern "C" fn raw_audio_callback(param: *mut c_void, _mix: usize, data: *mut audio_data) {
+  (*(param as *mut Context)).call(&*data, false);
+}
+
+unsafe extern "C" fn audio_capture_callback(param: *mut c_void, _source: *mut obs_source_t, data: *const audio_data, muted: bool) {
+  (*(param as *mut Context)).call(&*data, muted);
+}
+
+#[derive(Debug)]
+enum Target {
+  Mix(usize),
+  Source(SourceRef),
+}
+
+/// A registered raw audio callback, removed on drop.
+pub struct RawAudioCallback {
+  target: Target,
+  context: *mut Context,
+  dropped: Arc<AtomicU64>,
+}
+
+unsafe impl Send for RawAudioCallback {}
+
+impl std::fmt::Debug for RawAudioCallback {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let context = unsafe { &*self.context };
+    f.debug_struct("RawAudioCallback").field("target", &self.target).field("sample_rate", &context.sample_rate)
+      .field("channels", &context.channels).field("dropped", &self.dropped()).finish()
+  }
+}
+
+impl RawAudioCallback {
+  pub fn sample_rate(&self) -> u32 {
+    unsafe { (*self.context).sample_rate }
+  }
+
+  pub fn channels(&self) -> usize {
+    unsafe { (*self.context).channels }
+  }
+
+  /// Frames the receiver of the channel was too slow for.
+  pub fn dropped(&self) -> u64 {
+    self.dropped.load(Ordering::Relaxed)
+  }
+}
+
+impl Drop for RawAudioCallback {
+  fn drop(&mut self) {
+    unsafe {
+      // both return once a running callback is finished
+      match &self.target {
+        Target::Mix(mix) => obs_remove_raw_audio_callback(*mix, Some(raw_audio_callback), self.context as *mut c_void),
+        Target::Source(source) => obs_source_remove_audio_capture_callback(source.as_ptr_mut(), Some(audio_capture_callback), self.context as *mut c_void),
+      }
+      drop(Box::from_raw(self.context));
+    }
+  }
+}
+
+/// Copies into a channel of `capacity`, counting the frames dropped when it is full.
+fn channel(capacity: usize) -> (RawAudioFn, Arc<AtomicU64>, mpsc::Receiver<AudioFrame>) {
+  let (sender, receiver) = mpsc::sync_channel(capacity);
+  let dropped = Arc::new(AtomicU64::new(0));
+  let counter = dropped.clone();
+  let callback = Box::new(move |frame: &AudioFrameRef| {
+    if let Err(mpsc::TrySendError::Full(_)) = sender.try_send(frame.to_frame()) {
+      counter.fetch_add(1, Ordering::Relaxed);
+    }
+  });
+  (callback, dropped, receiver)
+}
+
+impl Obs {
+  /// Call `callback` with the samples of the audio mix `mix` (the track `mix + 1` of OBS Studio) on the audio thread.
+  /// Needs `reset_audio` first.
+  pub fn add_raw_audio_callback<F: FnMut(&AudioFrameRef) + Send + 'static>(&mut self, mix: usize, callback: F) -> Result<RawAudioCallback> {
+    self.add_raw_audio(Target::Mix(mix), Box::new(callback), Default::default())
+  }
+
+  /// Samples of the audio mix `mix` copied into a channel of `capacity`, dropped instead of waiting when it is full.
+  pub fn raw_audio_channel(&mut self, mix: usize, capacity: usize) -> Result<(RawAudioCallback, mpsc::Receiver<AudioFrame>)> {
+    let (callback, dropped, receiver) = channel(capacity);
+    Ok((self.add_raw_audio(Target::Mix(mix), callback, dropped)?, receiver))
+  }
+
+  /// Call `callback` with the samples of `source` before its volume, also when it is muted.
+  pub fn add_source_audio_callback<F: FnMut(&AudioFrameRef) + Send + 'static>(&mut self, source: &SourceRef, callback: F) -> Result<RawAudioCallback> {
+    self.add_raw_audio(Target::Source(source.clone()), Box::new(callback), Default::default())
+  }
+
+  pub fn source_audio_channel(&mut self, source: &SourceRef, capacity: usize) -> Result<(RawAudioCallback, mpsc::Receiver<AudioFrame>)> {
+    let (callback, dropped, receiver) = channel(capacity);
+    Ok((self.add_raw_audio(Target::Source(source.clone()), callback, dropped)?, receiver))
+  }
+
+  fn add_raw_audio(&mut self, target: Target, callback: RawAudioFn, dropped: Arc<AtomicU64>) -> Result<RawAudioCallback> {
+    if !self.ready() {
+      return Err(Error::NotInitialized)
+    }
+    let audio = unsafe { obs_get_audio() };
+    if audio.is_null() {
+      return Err(Error::NulPointer("obs_get_audio"))
+    }
+    if let Target::Mix(mix) = target {
+      if mix >= MAX_AUDIO_MIXES as usize {
+        return Err(Error::Output(format!("audio mix {mix} out of {MAX_AUDIO_MIXES}")))
+      }
+    }
+    let (sample_rate, channels) = unsafe { (audio_output_get_sample_rate(audio), audio_output_get_channels(audio)) };
+    let context = Box::into_raw(Box::new(Context { sample_rate, channels, callback }));
+    unsafe {
+      match &target {
+        Target::Mix(mix) => {
+          // the mixes are already float planar, the conversion only makes sure of it
+          let info = &*audio_output_get_info(audio);
+          let mut conversion: audio_convert_info = std::mem::zeroed();
+          conversion.samples_per_sec = sample_rate;
+          conversion.format = AUDIO_FORMAT_FLOAT_PLANAR;
+          conversion.speakers = info.speakers;
+          obs_add_raw_audio_callback(*mix, &conversion, Some(raw_audio_callback), context as *mut c_void);
+        },
+        Target::Source(source) => {
+          obs_source_add_audio_capture_callback(source.as_ptr_mut(), Some(audio_capture_callback), context as *mut c_void);
+          debug!(source=source_name(source), "audio capture added");
+        },
+      }
+    }
+    Ok(RawAudioCallback { target, context, dropped })
+  }
+}
+
+/// A WAV file of 32 bit float samples, the sizes in the header are written by [`WavWriter::finish`].
+///
+/// RIFF sizes are 32 bit, so a file holds at most 4 GiB, about 3 hours of 48kHz stereo.
+#[derive(Debug)]
+pub struct WavWriter<W: Write + Seek> {
+  inner: W,
+  channels: u16,
+  /// bytes of samples written so far
+  data_size: u64,
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+  /// `RIFF`, an 18 byte `fmt ` as non-PCM formats need `cbSize`, `fact` and the `data` chunk header
+  const HEADER_SIZE: u32 = 58;
+  const FACT_FRAMES_OFFSET: u64 = 46;
+  const DATA_SIZE_OFFSET: u64 = 54;
+
+  pub fn new(mut inner: W, sample_rate: u32, channels: u16) -> std::io::Result<Self> {
+    let block_align = channels * 4;
+    let mut header = Vec::with_capacity(Self::HEADER_SIZE as usize);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&0u32.to_le_bytes());
+    header.extend_from_slice(b"WAVEfmt ");
+    header.extend_from_slice(&18u32.to_le_bytes());
+    // WAVE_FORMAT_IEEE_FLOAT
+    header.extend_from_slice(&3u16.to_le_bytes());
+    header.extend_from_slice(&channels.to_le_bytes());
+    header.extend_from_slice(&sample_rate.to_le_bytes());
+    header.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    header.extend_from_slice(&block_align.to_le_bytes());
+    header.extend_from_slice(&32u16.to_le_bytes());
+    // cbSize, no extension
+    header.extend_from_slice(&0u16.to_le_bytes());
+    // sample frames, required for non-PCM formats
+    header.extend_from_slice(b"fact");
+    header.extend_from_slice(&4u32.to_le_bytes());
+    header.extend_from_slice(&0u32.to_le_bytes());
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&0u32.to_le_bytes());
+    inner.write_all(&header)?;
+    Ok(Self { inner, channels, data_size: 0 })
+  }
+
+  /// Interleave `planes`, a missing channel is written as silence.
+  /// Fails without writing anything once the file would exceed the 4 GiB of RIFF.
+  pub fn write(&mut self, planes: &[&[f32]]) -> std::io::Result<()> {
+    let frames = planes.first().map_or(0, |i| i.len());
+    let size = frames as u64 * self.channels as u64 * 4;
+    if (Self::HEADER_SIZE - 8) as u64 + self.data_size + size > u32::MAX as u64 {
+      return Err(std::io::Error::new(std::io::ErrorKind::FileTooLarge, "a WAV file holds at most 4 GiB"))
+    }
+    let mut data = Vec::with_capacity(size as usize);
+    for frame in 0..frames {
+      for channel in 0..self.channels as usize {
+        let sample = planes.get(channel).and_then(|i| i.get(frame)).copied().unwrap_or_default();
+        data.extend_from_slice(&sample.to_le_bytes());
+      }
+    }
+    self.inner.write_all(&data)?;
+    self.data_size += size;
+    Ok(())
+  }
+
+  pub fn write_frame(&mut self, frame: &AudioFrameRef) -> std::io::Result<()> {
+    self.write(&frame.planes)
+  }
+
+  /// Write the sizes into the header, and return the inner writer.
+  pub fn finish(mut self) -> std::io::Result<W> {
+    // checked by `write`
+    let data_size = self.data_size as u32;
+    let frames = data_size / (self.channels as u32 * 4).max(1);
+    self.inner.seek(SeekFrom::Start(4))?;
+    self.inner.write_all(&(Self::HEADER_SIZE - 8 + data_size).to_le_bytes())?;
+    self.inner.seek(SeekFrom::Start(Self::FACT_FRAMES_OFFSET))?;
+    self.inner.write_all(&frames.to_le_bytes())?;
+    self.inner.seek(SeekFrom::Start(Self::DATA_SIZE_OFFSET))?;
+    self.inner.write_all(&data_size.to_le_bytes())?;
+    self.inner.seek(SeekFrom::End(0))?;
+    self.inner.flush()?;
+    Ok(self.inner)
+  }
+}
+
+#[test]
+fn test_wav() {
+  let left = [0.0, 0.5, -0.5];
+  let frame = AudioFrameRef { sample_rate: 48000, timestamp: 0, muted: false, planes: vec![&left, &[1.0, 1.0, 1.0]] };
+  assert_eq!(frame.frames(), 3);
+  let mut wav = WavWriter::new(std::io::Cursor::new(Vec::new()), 48000, 2).unwrap();
+  wav.write_frame(&frame).unwrap();
+  // a mono plane fills the second channel with silence
+  wav.write(&[&left[..1]]).unwrap();
+  let bytes = wav.finish().unwrap().into_inner();
+  assert_eq!(bytes.len(), 58 + 4 * 2 * 4);
+  assert_eq!(&bytes[..4], b"RIFF");
+  assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()), 50 + 32);
+  assert_eq!(u32::from_le_bytes(bytes[16..20].try_into().unwrap()), 18);
+  assert_eq!(u16::from_le_bytes(bytes[20..22].try_into().unwrap()), 3);
+  assert_eq!(u32::from_le_bytes(bytes[28..32].try_into().unwrap()), 48000 * 8);
+  assert_eq!(u16::from_le_bytes(bytes[36..38].try_into().unwrap()), 0);
+  assert_eq!(&bytes[38..42], b"fact");
+  assert_eq!(u32::from_le_bytes(bytes[46..50].try_into().unwrap()), 4);
+  assert_eq!(&bytes[50..54], b"data");
+  assert_eq!(u32::from_le_bytes(bytes[54..58].try_into().unwrap()), 32);
+  let samples = bytes[58..].chunks(4).map(|i| f32::from_le_bytes(i.try_into().unwrap())).collect::<Vec<_>>();
+  assert_eq!(samples, [0.0, 1.0, 0.5, 1.0, -0.5, 1.0, 0.0, 0.0]);
+
+  // the sizes would overflow the header
+  let mut wav = WavWriter::new(std::io::Cursor::new(Vec::new()), 48000, 2).unwrap();
+  wav.data_size = u32::MAX as u64 - 50 - 8;
+  wav.write(&[&left[..1]]).unwrap();
+  assert_eq!(wav.write(&[&left[..1]]).unwrap_err().kind(), std::io::ErrorKind::FileTooLarge);
+}
@@ -0,0 +1,212 @@
+//! Raw frames of the program, after the GPU rendered and downloaded them.
+//!
+//! ```text
+//! render -> output texture -> (scale, convert) -> video thread -> callback(frame)
+//!                                                              └─> try_send -> channel, dropped when full
+//! ```
+//! Callbacks run on the video thread, a slow one delays every output, so [`Obs::raw_video_channel`]
+//! copies the frame and drops it when the receiver is behind.
+//! libobs only exposes raw frames of the main mix, a view gets them through an output like the virtual camera.
+//!
+//! see `libobs/obs-video.c` `output_frame` and `libobs/media-io/video-io.c` `video_output_connect`
+
+use std::{ffi::c_void, sync::{atomic::{AtomicU64, Ordering}, mpsc, Arc}};
+
+use obs_wrapper::{media::video::VideoFormat, obs_sys::{obs_add_raw_video_callback, obs_get_video_info, obs_remove_raw_video_callback, video_data, video_scale_info}};
+
+use super::{raw_video_format, video_format_from_raw, Error, Obs, Result, VideoSetting};
+
+/// Scale and convert the frames before the callback, a frame of the output format and size otherwise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VideoConversion {
+  pub format: VideoFormat,
+  pub width: u32,
+  pub height: u32,
+}
+
+impl VideoConversion {
+  fn scale_info(&self) -> video_scale_info {
+    // range and colorspace default to the ones of the output
+    let mut info: video_scale_info = unsafe { std::mem::zeroed() };
+    info.format = raw_video_format(self.format) as _;
+    info.width = self.width;
+    info.height = self.height;
+    info
+  }
+}
+
+/// Rows of every plane of a `height` frame, chroma planes of 4:2:0 formats have half of them.
+pub fn plane_heights(format: VideoFormat, height: u32) -> Vec<u32> {
+  let half = height.div_ceil(2);
+  match format {
+    VideoFormat::I420 => vec![height, half, half],
+    VideoFormat::NV12 => vec![height, half],
+    VideoFormat::I444 | VideoFormat::I422 => vec![height; 3],
+    VideoFormat::I40A => vec![height, half, half, height],
+    VideoFormat::I42A | VideoFormat::YUVA => vec![height; 4],
+    VideoFormat::YVYU | VideoFormat::YUY2 | VideoFormat::UYVY | VideoFormat::RGBA | VideoFormat::BGRA
+      | VideoFormat::BGRX | VideoFormat::Y800 | VideoFormat::BGR3 | VideoFormat::AYUV => vec![height],
+    _ => Vec::new(),
+  }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PlaneRef<'a> {
+  /// bytes of a row, including the padding
+  pub linesize: u32,
+  pub data: &'a [u8],
+}
+
+/// A frame borrowed from libobs, only valid in the callback.
+#[derive(Debug, Clone)]
+pub struct VideoFrameRef<'a> {
+  pub format: VideoFormat,
+  pub width: u32,
+  pub height: u32,
+  /// nanoseconds of `os_gettime_ns`, the clock of the audio timestamps too
+  pub timestamp: u64,
+  pub planes: Vec<PlaneRef<'a>>,
+}
+
+impl VideoFrameRef<'_> {
+  pub fn to_frame(&self) -> VideoFrame {
+    VideoFrame {
+      format: self.format,
+      width: self.width,
+      height: self.height,
+      timestamp: self.timestamp,
+      planes: self.planes.iter().map(|i| Plane { linesize: i.linesize, data: i.data.to_vec() }).collect(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plane {
+  pub linesize: u32,
+  pub data: Vec<u8>,
+}
+
+/// A copied frame, e.g. from [`Obs::raw_video_channel`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoFrame {
+  pub format: VideoFormat,
+  pub width: u32,
+  pub height: u32,
+  pub timestamp: u64,
+  pub planes: Vec<Plane>,
+}
+
+type RawVideoFn = Box<dyn FnMut(&VideoFrameRef) + Send>;
+
+struct Context {
+  format: VideoFormat,
+  width: u32,
+  height: u32,
+  callback: RawVideoFn,
+}
+
+unsafe extern "C" fn raw_video_callback(param: *mut c_void, frame: *mut video_data) {
+  let context = &mut *(param as *mut Context);
+  let frame = &*frame;
+  let planes = plane_heights(context.format, context.height).into_iter().enumerate()
+    .take_while(|(i, _)| !frame.data[*i].is_null())
+    .map(|(i, rows)| PlaneRef {
+      linesize: frame.linesize[i],
+      data: std::slice::from_raw_parts(frame.data[i], frame.linesize[i] as usize * rows as usize),
+    })
+    .collect();
+  (context.callback)(&VideoFrameRef { format: context.format, width: context.width, height: context.height, timestamp: frame.timestamp, planes });
+}
+
+/// A registered raw video callback, removed on drop.
+pub struct RawVideoCallback {
+  context: *mut Context,
+  dropped: Arc<AtomicU64>,
+}
+
+unsafe impl Send for RawVideoCallback {}
+
+impl std::fmt::Debug for RawVideoCallback {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let context = unsafe { &*self.context };
+    f.debug_struct("RawVideoCallback").field("format", &context.format)
+      .field("size", &(context.width, context.height)).field("dropped", &self.dropped()).finish()
+  }
+}
+
+impl RawVideoCallback {
+  /// Frames the receiver of [`Obs::raw_video_channel`] was too slow for.
+  pub fn dropped(&self) -> u64 {
+    self.dropped.load(Ordering::Relaxed)
+  }
+}
+
+impl Drop for RawVideoCallback {
+  fn drop(&mut self) {
+    unsafe {
+      // returns once a running callback is finished, it holds the same lock
+      obs_remove_raw_video_callback(Some(raw_video_callback), self.context as *mut c_void);
+      drop(Box::from_raw(self.context));
+    }
+  }
+}
+
+impl Obs {
+  /// Call `callback` with every frame of the program on the video thread, it should return quickly.
+  /// Needs `reset_video` first, which then fails until the callback is dropped.
+  pub fn add_raw_video_callback<F: FnMut(&VideoFrameRef) + Send + 'static>(&mut self, conversion: Option<VideoConversion>, callback: F) -> Result<RawVideoCallback> {
+    self.add_raw_video(conversion, Box::new(callback), Default::default())
+  }
+
+  /// Frames copied into a channel of `capacity`, a frame is dropped instead of waiting when it is full.
+  pub fn raw_video_channel(&mut self, conversion: Option<VideoConversion>, capacity: usize) -> Result<(RawVideoCallback, mpsc::Receiver<VideoFrame>)> {
+    let (sender, receiver) = mpsc::sync_channel(capacity);
+    let dropped = Arc::new(AtomicU64::new(0));
+    let counter = dropped.clone();
+    let callback = Box::new(move |frame: &VideoFrameRef| {
+      if let Err(mpsc::TrySendError::Full(_)) = sender.try_send(frame.to_frame()) {
+        counter.fetch_add(1, Ordering::Relaxed);
+      }
+    });
+    Ok((self.add_raw_video(conversion, callback, dropped)?, receiver))
+  }
+
+  fn add_raw_video(&mut self, conversion: Option<VideoConversion>, callback: RawVideoFn, dropped: Arc<AtomicU64>) -> Result<RawVideoCallback> {
+    if !self.ready() {
+      return Err(Error::NotInitialized)
+    }
+    let mut info = VideoSetting::new();
+    if !unsafe { obs_get_video_info(&mut info.0) } {
+      return Err(Error::Output("video is not reset".to_string()))
+    }
+    let (format, width, height) = match conversion {
+      Some(i) => (i.format, i.width, i.height),
+      None => (video_format_from_raw(info.0.output_format as u32), info.0.output_width, info.0.output_height),
+    };
+    if plane_heights(format, height).is_empty() || width == 0 || height == 0 {
+      return Err(Error::Output(format!("raw video of {format:?} {width}x{height} is not supported")))
+    }
+    let context = Box::into_raw(Box::new(Context { format, width, height, callback }));
+    let scale_info = conversion.map(|i| i.scale_info());
+    unsafe {
+      let scale_info = scale_info.as_ref().map_or(std::ptr::null(), |i| i as *const _);
+      obs_add_raw_video_callback(scale_info, Some(raw_video_callback), context as *mut c_void);
+    }
+    Ok(RawVideoCallback { context, dropped })
+  }
+}
+
+#[test]
+fn test_plane_heights() {
+  assert_eq!(plane_heights(VideoFormat::I420, 1080), [1080, 540, 540]);
+  assert_eq!(plane_heights(VideoFormat::NV12, 719), [719, 360]);
+  assert_eq!(plane_heights(VideoFormat::I40A, 2), [2, 1, 1, 2]);
+  assert_eq!(plane_heights(VideoFormat::BGRA, 4), [4]);
+  assert!(plane_heights(VideoFormat::None, 4).is_empty());
+  assert_eq!(video_format_from_raw(raw_video_format(VideoFormat::NV12)), VideoFormat::NV12);
+  assert_eq!(video_format_from_raw(99), VideoFormat::None);
+  for (value, (format, _)) in super::VIDEO_FORMATS.iter().enumerate() {
+    assert_eq!(raw_video_format(*format), value as u32);
+    assert_eq!(video_format_from_raw(value as u32), *format);
+  }
+}
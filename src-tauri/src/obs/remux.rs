@@ -0,0 +1,222 @@
+//! Remux a finished recording into mp4 or mov without encoding it again,
+//! e.g. a mkv which survives a crash into a file every editor opens.
+//!
+//! ```c
+//! EXPORT bool media_remux_job_create(media_remux_job_t *job, const char *in_filename, const char *out_filename);
+//! EXPORT bool media_remux_job_process(media_remux_job_t job, media_remux_progress_callback callback, void *data);
+//! EXPORT void media_remux_job_destroy(media_remux_job_t job);
+//! ```
+//! see `libobs/media-io/media-remux.c` and `UI/window-remux.cpp`,
+//! the job only uses the ffmpeg libraries so it runs on its own thread.
+
+use std::{cell::Cell, collections::BTreeMap, ffi::{c_void, CString}, path::{Path, PathBuf}, sync::{atomic::{AtomicBool, Ordering}, Arc}, thread::JoinHandle};
+
+use super::{output::filename::find_best_path, Error, Result};
+
+#[allow(non_camel_case_types)]
+pub mod sys {
+  use std::ffi::{c_char, c_void};
+
+  /// `media-io/media-remux.h` is not part of `obs.h`
+  #[repr(C)]
+  pub struct media_remux_job {
+    _private: [u8; 0],
+  }
+  pub type media_remux_job_t = *mut media_remux_job;
+  /// returns false to cancel the job, `percent` goes from 0 to 100
+  pub type media_remux_progress_callback = Option<unsafe extern "C" fn(data: *mut c_void, percent: f32) -> bool>;
+
+  extern "C" {
+    pub fn media_remux_job_create(job: *mut media_remux_job_t, in_filename: *const c_char, out_filename: *const c_char) -> bool;
+    pub fn media_remux_job_process(job: media_remux_job_t, callback: media_remux_progress_callback, data: *mut c_void) -> bool;
+    pub fn media_remux_job_destroy(job: media_remux_job_t);
+  }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[serde(rename_all = "lowercase")]
+pub enum RemuxFormat {
+  #[default]
+  Mp4,
+  Mov,
+}
+
+impl RemuxFormat {
+  pub fn extension(self) -> &'static str {
+    match self {
+      Self::Mp4 => "mp4",
+      Self::Mov => "mov",
+    }
+  }
+}
+
+/// `input` with the extension of `format`, numbered if that file exists already.
+pub fn remux_path(input: &Path, format: RemuxFormat) -> PathBuf {
+  find_best_path(&input.with_extension(format.extension()), true)
+}
+
+/// Remux `input` into `output`, blocking until done.
+/// `progress` is called with the percentage, returning false cancels the job and removes `output`.
+pub fn remux<F: FnMut(f32) -> bool>(input: &Path, output: &Path, progress: F) -> Result<()> {
+  if !input.is_file() {
+    return Err(std::io::Error::new(std::io::ErrorKind::NotFound, input.display().to_string()).into())
+  }
+  if input == output {
+    return Err(Error::Remux(format!("{} could not be remuxed into itself", input.display())))
+  }
+  let input_c = CString::new(input.to_string_lossy().as_bytes())?;
+  let output_c = CString::new(output.to_string_lossy().as_bytes())?;
+
+  struct Context<F> { progress: F, cancelled: Cell<bool> }
+  unsafe extern "C" fn callback<F: FnMut(f32) -> bool>(data: *mut c_void, percent: f32) -> bool {
+    let context = &mut *(data as *mut Context<F>);
+    let next = (context.progress)(percent);
+    context.cancelled.set(!next);
+    next
+  }
+  let mut context = Context { progress, cancelled: Cell::new(false) };
+
+  let success = unsafe {
+    let mut job: sys::media_remux_job_t = std::ptr::null_mut();
+    if !sys::media_remux_job_create(&mut job, input_c.as_ptr(), output_c.as_ptr()) {
+      return Err(Error::Remux(format!("failed to open {}", input.display())))
+    }
+    let success = sys::media_remux_job_process(job, Some(callback::<F>), &mut context as *mut Context<F> as *mut c_void);
+    // closes both files
+    sys::media_remux_job_destroy(job);
+    success
+  };
+  if context.cancelled.get() {
+    let _ = std::fs::remove_file(output);
+    return Err(Error::Cancelled)
+  }
+  match success {
+    true => Ok(()),
+    false => Err(Error::Remux(format!("failed to remux {} into {}", input.display(), output.display()))),
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RemuxEvent {
+  /// sent once per percent
+  Progress { #[ts(type = "number")] id: u64, input: PathBuf, output: PathBuf, percent: f32 },
+  Finished { #[ts(type = "number")] id: u64, input: PathBuf, output: PathBuf },
+  Cancelled { #[ts(type = "number")] id: u64, input: PathBuf, output: PathBuf },
+  Failed { #[ts(type = "number")] id: u64, input: PathBuf, output: PathBuf, message: String },
+}
+
+impl RemuxEvent {
+  /// tauri event name
+  pub fn name(&self) -> &'static str {
+    match self {
+      RemuxEvent::Progress { .. } => "remux-progress",
+      RemuxEvent::Finished { .. } => "remux-finished",
+      RemuxEvent::Cancelled { .. } => "remux-cancelled",
+      RemuxEvent::Failed { .. } => "remux-failed",
+    }
+  }
+}
+
+/// A remux running on its own thread.
+#[derive(Debug)]
+pub struct RemuxJob {
+  cancel: Arc<AtomicBool>,
+  handle: JoinHandle<()>,
+}
+
+impl RemuxJob {
+  /// `callback` gets the progress, then exactly one of finished, cancelled or failed.
+  pub fn spawn<F: FnMut(RemuxEvent) + Send + 'static>(id: u64, input: PathBuf, output: PathBuf, mut callback: F) -> Self {
+    let cancel = Arc::new(AtomicBool::new(false));
+    let handle = {
+      let cancel = cancel.clone();
+      std::thread::spawn(move || {
+        let mut last = None;
+        let result = remux(&input, &output, |percent| {
+          let current = percent.floor() as i32;
+          if last != Some(current) {
+            last = Some(current);
+            callback(RemuxEvent::Progress { id, input: input.clone(), output: output.clone(), percent });
+          }
+          !cancel.load(Ordering::Relaxed)
+        });
+        let (input, output) = (input.clone(), output.clone());
+        callback(match result {
+          Ok(()) => RemuxEvent::Finished { id, input, output },
+          Err(Error::Cancelled) => RemuxEvent::Cancelled { id, input, output },
+          Err(e) => RemuxEvent::Failed { id, input, output, message: e.to_string() },
+        });
+      })
+    };
+    Self { cancel, handle }
+  }
+
+  pub fn cancel(&self) {
+    self.cancel.store(true, Ordering::Relaxed);
+  }
+
+  pub fn is_finished(&self) -> bool {
+    self.handle.is_finished()
+  }
+
+  /// Wait for the thread, after its last event was sent.
+  pub fn join(self) {
+    if self.handle.join().is_err() {
+      warn!("remux thread panicked");
+    }
+  }
+}
+
+/// Running jobs by id, the finished ones are dropped when the next one starts.
+#[derive(Debug, Default)]
+pub struct RemuxJobs {
+  next_id: u64,
+  jobs: BTreeMap<u64, RemuxJob>,
+}
+
+impl RemuxJobs {
+  pub fn start<F: FnMut(RemuxEvent) + Send + 'static>(&mut self, input: PathBuf, output: PathBuf, callback: F) -> u64 {
+    self.jobs.retain(|_, i| !i.is_finished());
+    let id = self.next_id;
+    self.next_id += 1;
+    info!(id, input=%input.display(), output=%output.display(), "remux started");
+    self.jobs.insert(id, RemuxJob::spawn(id, input, output, callback));
+    id
+  }
+
+  /// Returns false if the job is unknown or already finished.
+  pub fn cancel(&mut self, id: u64) -> bool {
+    match self.jobs.get(&id) {
+      Some(job) if !job.is_finished() => { job.cancel(); true },
+      _ => false,
+    }
+  }
+
+  /// Cancel every job and wait for their threads, e.g. before the app exits.
+  pub fn cancel_all(&mut self) {
+    self.jobs.values().for_each(RemuxJob::cancel);
+    std::mem::take(&mut self.jobs).into_values().for_each(RemuxJob::join);
+  }
+}
+
+#[test]
+fn test_remux() {
+  let dir = crate::testing::TempDir::new("remux");
+  let input = dir.join("2024-04-13 10-20-30.mkv");
+  assert!(matches!(remux(&input, &dir.join("a.mp4"), |_| true), Err(Error::Io(e)) if e.kind() == std::io::ErrorKind::NotFound));
+  std::fs::write(&input, "").unwrap();
+  assert!(matches!(remux(&input, &input, |_| true), Err(Error::Remux(_))));
+
+  assert_eq!(remux_path(&input, RemuxFormat::Mp4), dir.join("2024-04-13 10-20-30.mp4"));
+  std::fs::write(dir.join("2024-04-13 10-20-30.mov"), "").unwrap();
+  assert_eq!(remux_path(&input, RemuxFormat::Mov), dir.join("2024-04-13 10-20-30 (2).mov"));
+
+  let (sender, receiver) = std::sync::mpsc::channel();
+  let mut jobs = RemuxJobs::default();
+  let id = jobs.start(dir.join("missing.mkv"), dir.join("missing.mp4"), move |event| sender.send(event).unwrap());
+  assert!(matches!(receiver.recv().unwrap(), RemuxEvent::Failed { id: i, .. } if i == id));
+  jobs.cancel_all();
+  assert!(jobs.jobs.is_empty());
+  assert_eq!(RemuxEvent::Finished { id, input: input.clone(), output: input.clone() }.name(), "remux-finished");
+}
@@ -0,0 +1,148 @@
+//! Groups and nested scenes.
+//!
+//! A group is a private scene owned by a scene item (`obs_scene_add_group2`),
+//! a nested scene is a regular scene added as a source into another one.
+//! Both make the scene graph a tree, and libobs happily renders a cycle until the stack overflows,
+//! so every nesting goes through [`is_reachable`] first.
+
+use std::ffi::{c_void, CString};
+
+use obs_wrapper::{obs_sys::{obs_group_from_source, obs_scene_add, obs_scene_add_group2, obs_scene_enum_items, obs_scene_from_source, obs_scene_t, obs_sceneitem_get_source, obs_sceneitem_group_add_item, obs_sceneitem_group_get_scene, obs_sceneitem_group_remove_item, obs_sceneitem_group_ungroup2, obs_sceneitem_is_group, obs_sceneitem_t, obs_source_t}, source::{scene::{SceneItemRef, SceneRef}, SourceRef}, wrapper::PtrWrapper as _};
+
+use super::{source_name, Error, Obs, Result};
+
+/// Items of `scene`, in rendering order (bottom first).
+pub fn scene_items(scene: &SceneRef) -> Vec<SceneItemRef> {
+  unsafe extern "C" fn callback(_scene: *mut obs_scene_t, item: *mut obs_sceneitem_t, param: *mut c_void) -> bool {
+    let items = &mut *(param as *mut Vec<SceneItemRef>);
+    if let Ok(item) = SceneItemRef::from_raw(item) {
+      items.push(item);
+    }
+    true
+  }
+  let mut items = Vec::new();
+  unsafe { obs_scene_enum_items(scene.as_ptr_mut(), Some(callback), &mut items as *mut _ as *mut c_void) };
+  items
+}
+
+/// Scenes directly under `scene`, either nested scenes or groups.
+pub fn child_scenes(scene: &SceneRef) -> Vec<SceneRef> {
+  scene_items(scene).iter().filter_map(|item| unsafe {
+    let ptr = item.as_ptr_mut();
+    let scene = if obs_sceneitem_is_group(ptr) {
+      obs_sceneitem_group_get_scene(ptr)
+    } else {
+      scene_from_source(obs_sceneitem_get_source(ptr))
+    };
+    SceneRef::from_raw(scene).ok()
+  }).collect()
+}
+
+/// Depth first search from `from`, returns true if `to` is `from` itself or one of its descendants.
+/// Visited nodes are remembered so a graph that already has a cycle still terminates.
+pub fn is_reachable<K: PartialEq + Clone, F: FnMut(&K) -> Vec<K>>(from: &K, to: &K, mut children: F) -> bool {
+  let mut visited = Vec::new();
+  let mut stack = vec![from.clone()];
+  while let Some(node) = stack.pop() {
+    if &node == to {
+      return true
+    }
+    if visited.contains(&node) {
+      continue
+    }
+    stack.extend(children(&node));
+    visited.push(node);
+  }
+  false
+}
+
+/// `obs_scene_from_source` only accepts scenes, groups need `obs_group_from_source`.
+unsafe fn scene_from_source(source: *mut obs_source_t) -> *mut obs_scene_t {
+  match obs_scene_from_source(source) {
+    ptr if ptr.is_null() => obs_group_from_source(source),
+    ptr => ptr,
+  }
+}
+
+impl Obs {
+  /// Add `child` as an item of `parent`,
+  /// refuses if `parent` is `child` itself or nested anywhere inside `child`.
+  pub fn add_scene(&mut self, parent: &SceneRef, child: &SceneRef) -> Result<SceneItemRef> {
+    let key = |scene: &SceneRef| scene.as_ptr() as usize;
+    let mut scenes = vec![(key(child), child.clone())];
+    let reachable = is_reachable(&key(child), &key(parent), |node| {
+      let Some(scene) = scenes.iter().find(|(k, _)| k == node).map(|(_, i)| i.clone()) else { return Vec::new() };
+      let children = child_scenes(&scene);
+      let keys = children.iter().map(key).collect();
+      scenes.extend(children.into_iter().map(|i| (key(&i), i)));
+      keys
+    });
+    if reachable {
+      return Err(Error::SceneCycle { parent: source_name(&parent.as_source()), child: source_name(&child.as_source()) })
+    }
+    unsafe {
+      let ptr = obs_scene_add(parent.as_ptr_mut(), child.as_source().as_ptr_mut());
+      Ok(SceneItemRef::from_raw(ptr)?)
+    }
+  }
+
+  /// Add an empty group named `name` on top of `scene`.
+  pub fn add_group(&mut self, scene: &SceneRef, name: &str) -> Result<SceneItemRef> {
+    let name = CString::new(name)?;
+    unsafe {
+      let ptr = obs_scene_add_group2(scene.as_ptr_mut(), name.as_ptr(), true);
+      Ok(SceneItemRef::from_raw(ptr)?)
+    }
+  }
+
+  /// Scene holding the items of `group`.
+  pub fn group_scene(&self, group: &SceneItemRef) -> Result<SceneRef> {
+    let ptr = unsafe {
+      if !obs_sceneitem_is_group(group.as_ptr_mut()) {
+        return Err(Error::NotAGroup(source_name(&SourceRef::from_raw(obs_sceneitem_get_source(group.as_ptr_mut()))?)))
+      }
+      obs_sceneitem_group_get_scene(group.as_ptr_mut())
+    };
+    Ok(SceneRef::from_raw(ptr)?)
+  }
+
+  /// Move `item` into `group`, both should belong to the same scene.
+  /// The transform of `item` is kept relative to the canvas.
+  pub fn group_add_item(&mut self, group: &SceneItemRef, item: &SceneItemRef) -> Result<()> {
+    self.group_scene(group)?;
+    unsafe { obs_sceneitem_group_add_item(group.as_ptr_mut(), item.as_ptr_mut()) };
+    Ok(())
+  }
+
+  /// Move `item` out of `group` back into the scene holding the group.
+  pub fn group_remove_item(&mut self, group: &SceneItemRef, item: &SceneItemRef) -> Result<()> {
+    self.group_scene(group)?;
+    unsafe { obs_sceneitem_group_remove_item(group.as_ptr_mut(), item.as_ptr_mut()) };
+    Ok(())
+  }
+
+  /// Move every item of `group` into its parent scene and remove the group.
+  pub fn ungroup(&mut self, group: SceneItemRef) -> Result<()> {
+    self.group_scene(&group)?;
+    unsafe { obs_sceneitem_group_ungroup2(group.as_ptr_mut(), true) };
+    Ok(())
+  }
+}
+
+#[test]
+fn test_is_reachable() {
+  use std::collections::HashMap;
+  let graph = HashMap::from([
+    ("main", vec!["intro", "game"]),
+    ("game", vec!["overlay"]),
+    ("overlay", vec!["camera"]),
+    ("loop_a", vec!["loop_b"]),
+    ("loop_b", vec!["loop_a"]),
+  ]);
+  let children = |node: &&str| graph.get(node).cloned().unwrap_or_default();
+  assert!(is_reachable(&"main", &"main", children));
+  assert!(is_reachable(&"main", &"camera", children));
+  assert!(!is_reachable(&"camera", &"main", children));
+  assert!(!is_reachable(&"game", &"intro", children));
+  assert!(!is_reachable(&"loop_a", &"main", children));
+}
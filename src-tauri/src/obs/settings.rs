@@ -11,7 +11,7 @@ pub mod win_game_capture {
   /// #define SETTING_MODE_WINDOW      "window"
   /// #define SETTING_MODE_HOTKEY      "hotkey"
   /// ```
-  #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+  #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize, ts_rs::TS)]
   pub enum CaptureMode {
     /// ```c
     /// #define SETTING_MODE_ANY         "any_fullscreen"
@@ -34,8 +34,9 @@ pub mod win_game_capture {
   ///   WINDOW_PRIORITY_EXE,
   /// };
   /// ```
-  #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde_repr::Serialize_repr, serde_repr::Deserialize_repr)]
+  #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde_repr::Serialize_repr, serde_repr::Deserialize_repr, ts_rs::TS)]
   #[repr(u8)]
+  #[ts(repr(enum))]
   pub enum WindowPriority {
     /// window_priority.WINDOW_PRIORITY_CLASS
     Class = 0,
@@ -54,8 +55,9 @@ pub mod win_game_capture {
   ///   HOOK_RATE_FASTEST
   /// };
   /// ```
-  #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde_repr::Serialize_repr, serde_repr::Deserialize_repr)]
+  #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde_repr::Serialize_repr, serde_repr::Deserialize_repr, ts_rs::TS)]
   #[repr(u8)]
+  #[ts(repr(enum))]
   pub enum HookRate {
     /// hook_rate.HOOK_RATE_SLOW
     Slow = 0,
@@ -72,7 +74,7 @@ pub mod win_game_capture {
   /// #define RGBA10A2_SPACE_SRGB "srgb"
   /// #define RGBA10A2_SPACE_2100PQ "2100pq"
   /// ```
-  #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+  #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize, ts_rs::TS)]
   pub enum Rgba10a2Space {
     /// #define RGBA10A2_SPACE_SRGB "srgb"
     #[default]
@@ -84,7 +86,8 @@ pub mod win_game_capture {
   }
 
 
-  #[derive(Debug, derivative::Derivative, serde::Serialize, serde::Deserialize, PartialEq)]
+  #[derive(Debug, derivative::Derivative, serde::Serialize, serde::Deserialize, PartialEq, ts_rs::TS)]
+  #[ts(rename = "WinGameCaptureSetting")]
   #[derivative(Default)]
   pub struct Setting {
     /// ```c
@@ -192,8 +195,9 @@ pub mod mac_display_capture {
   ///   CROP_INVALID
   /// };
   /// ```
-  #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde_repr::Serialize_repr, serde_repr::Deserialize_repr)]
+  #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde_repr::Serialize_repr, serde_repr::Deserialize_repr, ts_rs::TS)]
   #[repr(u8)]
+  #[ts(repr(enum))]
   pub enum CropMode {
     /// crop_mode.CROP_NONE
     #[default]
@@ -208,7 +212,8 @@ pub mod mac_display_capture {
     Invalid,
   }
 
-  #[derive(Debug, derivative::Derivative, serde::Serialize, serde::Deserialize, PartialEq)]
+  #[derive(Debug, derivative::Derivative, serde::Serialize, serde::Deserialize, PartialEq, ts_rs::TS)]
+  #[ts(rename = "MacDisplayCaptureSetting")]
   #[derivative(Default)]
   #[deprecated(note = "use mac_screen_capture instead")]
   pub struct Setting {
@@ -270,7 +275,8 @@ pub mod mac_window_capture {
   /// TODO enum
   pub const TYPE: &str = "OBS_SOURCE_TYPE_INPUT";
 
-  #[derive(Debug, derivative::Derivative, serde::Serialize, serde::Deserialize, PartialEq)]
+  #[derive(Debug, derivative::Derivative, serde::Serialize, serde::Deserialize, PartialEq, ts_rs::TS)]
+  #[ts(rename = "MacWindowCaptureSetting")]
   #[derivative(Default)]
   #[deprecated(note = "use mac_screen_capture instead")]
   pub struct Setting {
@@ -328,8 +334,9 @@ pub mod mac_screen_capture {
   ///   ScreenCaptureApplicationStream = 2,
   /// } ScreenCaptureStreamType;
   /// ```
-  #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde_repr::Serialize_repr, serde_repr::Deserialize_repr)]
+  #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde_repr::Serialize_repr, serde_repr::Deserialize_repr, ts_rs::TS)]
   #[repr(u8)]
+  #[ts(repr(enum))]
   pub enum ScreenCaptureType {
     /// ScreenCaptureStreamType.ScreenCaptureDisplayStream
     #[default]
@@ -455,7 +462,8 @@ pub mod mac_screen_capture {
     }
   }
 
-  #[derive(Debug, derivative::Derivative, serde::Serialize, serde::Deserialize, PartialEq)]
+  #[derive(Debug, derivative::Derivative, serde::Serialize, serde::Deserialize, PartialEq, ts_rs::TS)]
+  #[ts(rename = "MacScreenCaptureSetting")]
   #[derivative(Default)]
   pub struct RawSetting {
     /// ```c
@@ -586,7 +594,7 @@ pub mod image_source {
   /// static void color_source_defaults_v2(obs_data_t *settings)
   /// static void color_source_defaults_v3(obs_data_t *settings)
   /// ```
-  #[derive(Debug, derivative::Derivative, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
+  #[derive(Debug, derivative::Derivative, Clone, serde::Serialize, serde::Deserialize, PartialEq, ts_rs::TS)]
   #[derivative(Default)]
   pub struct ColorSetting {
     /// obs_data_set_default_int(settings, "color", 0xFFD1D1D1);
@@ -614,7 +622,7 @@ pub mod image_source {
   ///  "WebP Files (*.webp);;"
   ///  "All Files (*.*)";
   //// ```
-  #[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
+  #[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize, PartialEq, ts_rs::TS)]
   pub struct ImageSetting {
     /// obs_properties_add_path(props, "file", obs_module_text("File"), OBS_PATH_FILE, image_filter, path.array);
     pub file: Option<PathBuf>,
@@ -638,3 +646,219 @@ pub mod image_source {
     assert_eq!(setting, setting2);
   }
 }
+
+/// Settings of the x264 encoder, as saved in `recordEncoder.json` and `streamEncoder.json` of a profile.
+/// Only the values different from the defaults are saved.
+/// plugins/obs-x264/obs-x264.c
+/// ```c
+/// static void obs_x264_defaults(obs_data_t *settings)
+/// ```
+pub mod obs_x264 {
+  pub const ID: &str = "obs_x264";
+
+  #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+  #[ts(rename = "X264RateControl")]
+  pub enum RateControl {
+    #[default]
+    #[serde(rename = "CBR")]
+    Cbr,
+    #[serde(rename = "ABR")]
+    Abr,
+    #[serde(rename = "VBR")]
+    Vbr,
+    #[serde(rename = "CRF")]
+    Crf,
+  }
+
+  /// `x264_preset_names`
+  #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+  #[serde(rename_all = "lowercase")]
+  #[ts(rename = "X264Preset")]
+  pub enum Preset {
+    Ultrafast,
+    Superfast,
+    #[default]
+    Veryfast,
+    Faster,
+    Fast,
+    Medium,
+    Slow,
+    Slower,
+    Veryslow,
+    Placebo,
+  }
+
+  /// `x264_profile_names`, empty for the default of x264
+  #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+  #[serde(rename_all = "lowercase")]
+  #[ts(rename = "X264Profile")]
+  pub enum Profile {
+    #[default]
+    #[serde(rename = "")]
+    None,
+    Baseline,
+    Main,
+    High,
+  }
+
+  /// `x264_tune_names`, empty for no tuning
+  #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+  #[serde(rename_all = "lowercase")]
+  #[ts(rename = "X264Tune")]
+  pub enum Tune {
+    #[default]
+    #[serde(rename = "")]
+    None,
+    Film,
+    Animation,
+    Grain,
+    Stillimage,
+    Psnr,
+    Ssim,
+    Fastdecode,
+    Zerolatency,
+  }
+
+  #[derive(Debug, derivative::Derivative, Clone, serde::Serialize, serde::Deserialize, PartialEq, ts_rs::TS)]
+  #[ts(rename = "X264Setting")]
+  #[derivative(Default)]
+  #[serde(default)]
+  pub struct Setting {
+    /// obs_data_set_default_string(settings, "rate_control", "CBR");
+    pub rate_control: RateControl,
+    /// kbps, obs_data_set_default_int(settings, "bitrate", 2500);
+    #[derivative(Default(value="2500"))]
+    pub bitrate: u32,
+    /// obs_data_set_default_bool(settings, "use_bufsize", false);
+    pub use_bufsize: bool,
+    /// kbit, obs_data_set_default_int(settings, "buffer_size", 2500);
+    #[derivative(Default(value="2500"))]
+    pub buffer_size: u32,
+    /// only used by `CRF`, obs_data_set_default_int(settings, "crf", 23);
+    #[derivative(Default(value="23"))]
+    pub crf: u32,
+    /// keyframe interval in seconds, 0 for auto, obs_data_set_default_int(settings, "keyint_sec", 0);
+    pub keyint_sec: u32,
+    /// obs_data_set_default_string(settings, "preset", "veryfast");
+    pub preset: Preset,
+    /// obs_data_set_default_string(settings, "profile", "");
+    pub profile: Profile,
+    /// obs_data_set_default_string(settings, "tune", "");
+    pub tune: Tune,
+    /// space separated `key=value` options passed to x264, obs_data_set_default_string(settings, "x264opts", "");
+    pub x264opts: String,
+    /// obs_data_set_default_bool(settings, "repeat_headers", false);
+    pub repeat_headers: bool,
+  }
+
+  impl Setting {
+    /// `x264opts` as pairs, an option without `=` is a flag
+    pub fn options(&self) -> Vec<(&str, Option<&str>)> {
+      self.x264opts.split_whitespace().map(|i| match i.split_once('=') {
+        Some((key, value)) => (key, Some(value)),
+        None => (i, None),
+      }).collect()
+    }
+  }
+
+  #[test]
+  fn test_serde() {
+    // recordEncoder.json of the advanced output mode
+    let setting: Setting = serde_json::from_str(r#"{"bitrate":6000,"keyint_sec":2,"preset":"faster","profile":"high","tune":"zerolatency","x264opts":"bframes=0 no-mbtree"}"#).unwrap();
+    assert_eq!(setting.rate_control, RateControl::Cbr);
+    assert_eq!((setting.bitrate, setting.keyint_sec, setting.crf), (6000, 2, 23));
+    assert_eq!((setting.preset, setting.profile, setting.tune), (Preset::Faster, Profile::High, Tune::Zerolatency));
+    assert_eq!(setting.options(), [("bframes", Some("0")), ("no-mbtree", None)]);
+
+    let setting: Setting = serde_json::from_str(r#"{"crf":18,"rate_control":"CRF"}"#).unwrap();
+    assert_eq!((setting.rate_control, setting.crf, setting.bitrate), (RateControl::Crf, 18, 2500));
+
+    let json = serde_json::to_string(&Setting::default()).unwrap();
+    println!("{json}");
+    assert_eq!(json, r#"{"rate_control":"CBR","bitrate":2500,"use_bufsize":false,"buffer_size":2500,"crf":23,"keyint_sec":0,"preset":"veryfast","profile":"","tune":"","x264opts":"","repeat_headers":false}"#);
+    assert_eq!(serde_json::from_str::<Setting>("{}").unwrap(), Setting::default());
+  }
+}
+
+/// Settings of the audio encoders of obs-ffmpeg, AAC and Opus share them.
+/// plugins/obs-ffmpeg/obs-ffmpeg-audio-encoders.c
+/// ```c
+/// static void enc_defaults(obs_data_t *settings)
+/// ```
+pub mod ffmpeg_audio {
+  pub const AAC_ID: &str = "ffmpeg_aac";
+  pub const OPUS_ID: &str = "ffmpeg_opus";
+
+  #[derive(Debug, derivative::Derivative, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq, ts_rs::TS)]
+  #[ts(rename = "AudioEncoderSetting")]
+  #[derivative(Default)]
+  #[serde(default)]
+  pub struct Setting {
+    /// kbps, obs_data_set_default_int(settings, "bitrate", 128);
+    #[derivative(Default(value="128"))]
+    pub bitrate: u32,
+  }
+
+  #[test]
+  fn test_serde() {
+    let setting: Setting = serde_json::from_str(r#"{"bitrate":320}"#).unwrap();
+    assert_eq!(setting.bitrate, 320);
+    assert_eq!(serde_json::from_str::<Setting>("{}").unwrap().bitrate, 128);
+    assert_eq!(serde_json::to_string(&Setting::default()).unwrap(), r#"{"bitrate":128}"#);
+  }
+}
+
+/// Settings of the software AV1 encoders of obs-ffmpeg, `ffmpeg_aom_av1` and `ffmpeg_svt_av1`.
+/// plugins/obs-ffmpeg/obs-ffmpeg-av1.c
+/// ```c
+/// static void av1_defaults(obs_data_t *settings)
+/// ```
+pub mod ffmpeg_av1 {
+  pub const AOM_ID: &str = "ffmpeg_aom_av1";
+  pub const SVT_ID: &str = "ffmpeg_svt_av1";
+
+  #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+  #[ts(rename = "Av1RateControl")]
+  pub enum RateControl {
+    #[default]
+    #[serde(rename = "CBR")]
+    Cbr,
+    #[serde(rename = "VBR")]
+    Vbr,
+    #[serde(rename = "CQP")]
+    Cqp,
+  }
+
+  #[derive(Debug, derivative::Derivative, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq, ts_rs::TS)]
+  #[ts(rename = "Av1Setting")]
+  #[derivative(Default)]
+  #[serde(default)]
+  pub struct Setting {
+    /// obs_data_set_default_string(settings, "rate_control", "CBR");
+    pub rate_control: RateControl,
+    /// kbps, obs_data_set_default_int(settings, "bitrate", 2500);
+    #[derivative(Default(value="2500"))]
+    pub bitrate: u32,
+    /// quantizer, only used by `CQP`, obs_data_set_default_int(settings, "cqp", 50);
+    #[derivative(Default(value="50"))]
+    pub cqp: u32,
+    /// keyframe interval in seconds, 0 for auto, obs_data_set_default_int(settings, "keyint_sec", 0);
+    pub keyint_sec: u32,
+    /// speed, higher is faster, obs_data_set_default_int(settings, "preset", 8);
+    #[derivative(Default(value="8"))]
+    pub preset: u32,
+    /// space separated `key=value` options passed to ffmpeg, obs_data_set_default_string(settings, "ffmpeg_opts", "");
+    pub ffmpeg_opts: String,
+  }
+
+  #[test]
+  fn test_serde() {
+    let setting: Setting = serde_json::from_str(r#"{"bitrate":4000,"keyint_sec":2,"preset":10}"#).unwrap();
+    assert_eq!((setting.rate_control, setting.bitrate, setting.keyint_sec, setting.preset), (RateControl::Cbr, 4000, 2, 10));
+    let setting: Setting = serde_json::from_str(r#"{"rate_control":"CQP","cqp":30}"#).unwrap();
+    assert_eq!((setting.rate_control, setting.cqp), (RateControl::Cqp, 30));
+    let json = serde_json::to_string(&Setting::default()).unwrap();
+    println!("{json}");
+    assert_eq!(json, r#"{"rate_control":"CBR","bitrate":2500,"cqp":50,"keyint_sec":0,"preset":8,"ffmpeg_opts":""}"#);
+  }
+}
@@ -0,0 +1,394 @@
+//! Scene collections and profiles in the OBS setting folder.
+//!
+//! ```text
+//! global.ini                        [Basic] SceneCollection, SceneCollectionFile, Profile, ProfileDir
+//! user.ini                          same keys, since OBS Studio 31
+//! basic/scenes/{file}.json          a scene collection, `name` inside
+//! basic/profiles/{dir}/basic.ini    a profile, `[General] Name` inside
+//! ```
+//! Collections and profiles are addressed by their display name, like the OBS Studio menus do.
+
+pub mod ini;
+pub mod config;
+pub mod watch;
+pub mod backup;
+
+use std::path::{Path, PathBuf};
+
+use self::{config::AppConfig, ini::Ini};
+use crate::obs::{collection::{with_suffix, SceneCollection, SceneOrder, SourceData, SCENE_ID}, output::{services::{audio_codec, video_codec, StreamCheck}, stream::StreamService}};
+
+pub type Result<T, E=Error> = std::result::Result<T, E>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+  #[error("io error: {0}")]
+  Io(#[from] std::io::Error),
+  #[error("convert json error")]
+  Json(#[from] serde_json::Error),
+  #[error("obs error: {0}")]
+  Obs(#[from] crate::obs::Error),
+  #[error("OBS Studio is running, close it first")]
+  ObsRunning,
+  #[error("not found: {0}")]
+  NotFound(String),
+  #[error("already exists: {0}")]
+  AlreadyExists(String),
+  #[error("{0} is currently in use")]
+  InUse(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+  SceneCollection,
+  Profile,
+}
+
+/// `user.ini` if present, as OBS Studio 31 moved the user settings there.
+pub fn config_file(setting_dir: &Path) -> PathBuf {
+  match setting_dir.join("user.ini") {
+    path if path.exists() => path,
+    _ => setting_dir.join("global.ini"),
+  }
+}
+
+pub fn scenes_dir(setting_dir: &Path) -> PathBuf {
+  setting_dir.join("basic/scenes")
+}
+
+pub fn profiles_dir(setting_dir: &Path) -> PathBuf {
+  setting_dir.join("basic/profiles")
+}
+
+/// Whether an OBS Studio process is running, which would overwrite our changes on exit.
+/// Fails when the process list could not be read, callers should not write then.
+pub fn is_obs_running() -> std::io::Result<bool> {
+  #[cfg(target_os = "windows")] {
+    let output = std::process::Command::new("tasklist").args(["/FI", "IMAGENAME eq obs64.exe", "/NH"]).output()?;
+    if !output.status.success() {
+      return Err(std::io::Error::other(format!("tasklist exited with {}", output.status)))
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).contains("obs64.exe"))
+  }
+  #[cfg(not(target_os = "windows"))] {
+    // the process is `OBS` in the macOS app bundle, `obs` elsewhere
+    for name in ["obs", "OBS"] {
+      let status = std::process::Command::new("pgrep").args(["-x", name]).output()?.status;
+      // 1 is no match, anything else is an error of pgrep
+      match status.code() {
+        Some(0) => return Ok(true),
+        Some(1) => continue,
+        _ => return Err(std::io::Error::other(format!("pgrep exited with {status}"))),
+      }
+    }
+    Ok(false)
+  }
+}
+
+/// Refuses a write while OBS Studio runs, or when that could not be checked.
+pub fn ensure_obs_not_running() -> Result<()> {
+  let running = is_obs_running().map_err(|e| {
+    std::io::Error::new(e.kind(), format!("could not check whether OBS Studio is running: {e}"))
+  })?;
+  match running {
+    true => Err(Error::ObsRunning),
+    false => Ok(()),
+  }
+}
+
+/// ```c
+/// bool GetFileSafeName(const char *name, std::string &file)
+/// ```
+pub fn safe_file_name(name: &str) -> String {
+  name.chars().map(|c| match c {
+    c if c.is_alphanumeric() || c == '-' || c == '_' || c == '.' => c,
+    _ => '_',
+  }).collect()
+}
+
+/// `name`, then `name_2`, `name_3`... until `exists` is false.
+fn unique_name<F: Fn(&str) -> bool>(name: &str, exists: F) -> String {
+  let mut result = name.to_string();
+  let mut idx = 2;
+  while exists(&result) {
+    result = format!("{name}_{idx}");
+    idx += 1;
+  }
+  result
+}
+
+/// Display name of the active collection or profile.
+pub fn current(setting_dir: &Path, kind: Kind) -> Result<Option<String>> {
+  let config = AppConfig::load(setting_dir)?;
+  Ok(match kind {
+    Kind::SceneCollection => config.basic.scene_collection,
+    Kind::Profile => config.basic.profile,
+  })
+}
+
+fn set_current(setting_dir: &Path, kind: Kind, name: &str, file: &str) -> Result<()> {
+  let mut config = AppConfig::load(setting_dir)?;
+  let basic = &mut config.basic;
+  let (name_field, file_field) = match kind {
+    Kind::SceneCollection => (&mut basic.scene_collection, &mut basic.scene_collection_file),
+    Kind::Profile => (&mut basic.profile, &mut basic.profile_dir),
+  };
+  *name_field = Some(name.to_string());
+  *file_field = Some(file.to_string());
+  config.save(setting_dir)
+}
+
+fn ensure_not_current(setting_dir: &Path, kind: Kind, name: &str) -> Result<()> {
+  match current(setting_dir, kind)? {
+    Some(current) if current == name => Err(Error::InUse(name.to_string())),
+    _ => Ok(()),
+  }
+}
+
+/// `(display name, path)` of every scene collection, backups excluded.
+pub fn list_scene_collections(setting_dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+  let mut result = Vec::new();
+  let Ok(read_dir) = std::fs::read_dir(scenes_dir(setting_dir)) else { return Ok(result) };
+  for entry in read_dir {
+    let path = entry?.path();
+    if path.extension().map_or(true, |i| i != "json") { continue }
+    match SceneCollection::from_file(&path) {
+      Ok(collection) => result.push((collection.name, path)),
+      Err(e) => warn!(path=%path.display(), error=%e, "skip invalid scene collection"),
+    }
+  }
+  result.sort();
+  Ok(result)
+}
+
+pub fn find_scene_collection(setting_dir: &Path, name: &str) -> Result<PathBuf> {
+  list_scene_collections(setting_dir)?.into_iter().find(|(n, _)| n == name).map(|(_, path)| path)
+    .ok_or_else(|| Error::NotFound(name.to_string()))
+}
+
+fn new_collection_path(setting_dir: &Path, name: &str) -> Result<PathBuf> {
+  if list_scene_collections(setting_dir)?.iter().any(|(n, _)| n == name) {
+    return Err(Error::AlreadyExists(name.to_string()))
+  }
+  let dir = scenes_dir(setting_dir);
+  std::fs::create_dir_all(&dir)?;
+  let file = unique_name(&safe_file_name(name), |i| dir.join(format!("{i}.json")).exists());
+  Ok(dir.join(format!("{file}.json")))
+}
+
+fn write_collection(path: &Path, collection: &SceneCollection) -> Result<()> {
+  backup::save_json(path, &serde_json::to_string_pretty(collection)?, &Default::default())?;
+  Ok(())
+}
+
+/// A collection with a single empty scene, like `OBSBasic::CreateDefaultScene`.
+pub fn create_scene_collection(setting_dir: &Path, name: &str) -> Result<PathBuf> {
+  ensure_obs_not_running()?;
+  let path = new_collection_path(setting_dir, name)?;
+  let scene = SourceData { name: "Scene".to_string(), id: SCENE_ID.to_string(), versioned_id: Some(SCENE_ID.to_string()), ..Default::default() };
+  let collection = SceneCollection {
+    name: name.to_string(),
+    current_scene: Some(scene.name.clone()),
+    current_program_scene: Some(scene.name.clone()),
+    scene_order: vec![SceneOrder { name: scene.name.clone() }],
+    sources: vec![scene],
+    ..Default::default()
+  };
+  write_collection(&path, &collection)?;
+  Ok(path)
+}
+
+pub fn duplicate_scene_collection(setting_dir: &Path, name: &str, new_name: &str) -> Result<PathBuf> {
+  ensure_obs_not_running()?;
+  let mut collection = SceneCollection::from_file(find_scene_collection(setting_dir, name)?)?;
+  let path = new_collection_path(setting_dir, new_name)?;
+  collection.name = new_name.to_string();
+  write_collection(&path, &collection)?;
+  Ok(path)
+}
+
+/// Rename the collection and its file, following it in the config if it is the active one.
+pub fn rename_scene_collection(setting_dir: &Path, name: &str, new_name: &str) -> Result<PathBuf> {
+  ensure_obs_not_running()?;
+  let old_path = find_scene_collection(setting_dir, name)?;
+  let mut collection = SceneCollection::from_file(&old_path)?;
+  let path = new_collection_path(setting_dir, new_name)?;
+  collection.name = new_name.to_string();
+  write_collection(&path, &collection)?;
+  std::fs::remove_file(&old_path)?;
+  let _ = std::fs::remove_file(with_suffix(&old_path, ".bak"));
+  if current(setting_dir, Kind::SceneCollection)?.as_deref() == Some(name) {
+    let file = path.file_stem().unwrap_or_default().to_string_lossy();
+    set_current(setting_dir, Kind::SceneCollection, new_name, &file)?;
+  }
+  Ok(path)
+}
+
+/// Refuses to delete the active collection, as OBS Studio would fail to start without it.
+pub fn delete_scene_collection(setting_dir: &Path, name: &str) -> Result<()> {
+  ensure_obs_not_running()?;
+  ensure_not_current(setting_dir, Kind::SceneCollection, name)?;
+  let path = find_scene_collection(setting_dir, name)?;
+  std::fs::remove_file(&path)?;
+  let _ = std::fs::remove_file(with_suffix(&path, ".bak"));
+  Ok(())
+}
+
+/// `(display name, folder)` of every profile.
+pub fn list_profiles(setting_dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+  let mut result = Vec::new();
+  let Ok(read_dir) = std::fs::read_dir(profiles_dir(setting_dir)) else { return Ok(result) };
+  for entry in read_dir {
+    let path = entry?.path();
+    let Ok(content) = std::fs::read_to_string(path.join("basic.ini")) else { continue };
+    let name = Ini::parse(&content).get("General", "Name").map(str::to_string)
+      .unwrap_or_else(|| path.file_name().unwrap_or_default().to_string_lossy().to_string());
+    result.push((name, path));
+  }
+  result.sort();
+  Ok(result)
+}
+
+fn find_profile(setting_dir: &Path, name: &str) -> Result<PathBuf> {
+  list_profiles(setting_dir)?.into_iter().find(|(n, _)| n == name).map(|(_, path)| path)
+    .ok_or_else(|| Error::NotFound(name.to_string()))
+}
+
+fn new_profile_dir(setting_dir: &Path, name: &str) -> Result<PathBuf> {
+  if list_profiles(setting_dir)?.iter().any(|(n, _)| n == name) {
+    return Err(Error::AlreadyExists(name.to_string()))
+  }
+  let dir = profiles_dir(setting_dir);
+  let file = unique_name(&safe_file_name(name), |i| dir.join(i).exists());
+  Ok(dir.join(file))
+}
+
+fn set_profile_name(dir: &Path, name: &str) -> Result<()> {
+  let path = dir.join("basic.ini");
+  let mut ini = match std::fs::read_to_string(&path) {
+    Ok(content) => Ini::parse(&content),
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ini::default(),
+    Err(e) => return Err(e.into()),
+  };
+  ini.set("General", "Name", name);
+  std::fs::write(path, ini.to_string())?;
+  Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+  for entry in walkdir::WalkDir::new(from) {
+    let entry = entry.map_err(std::io::Error::from)?;
+    let target = to.join(entry.path().strip_prefix(from).unwrap_or(entry.path()));
+    if entry.file_type().is_dir() {
+      std::fs::create_dir_all(&target)?;
+    } else {
+      std::fs::copy(entry.path(), &target)?;
+    }
+  }
+  Ok(())
+}
+
+/// An empty profile, OBS Studio fills in the defaults when it is first used.
+pub fn create_profile(setting_dir: &Path, name: &str) -> Result<PathBuf> {
+  ensure_obs_not_running()?;
+  let dir = new_profile_dir(setting_dir, name)?;
+  std::fs::create_dir_all(&dir)?;
+  set_profile_name(&dir, name)?;
+  Ok(dir)
+}
+
+pub fn duplicate_profile(setting_dir: &Path, name: &str, new_name: &str) -> Result<PathBuf> {
+  ensure_obs_not_running()?;
+  let from = find_profile(setting_dir, name)?;
+  let dir = new_profile_dir(setting_dir, new_name)?;
+  copy_dir(&from, &dir)?;
+  set_profile_name(&dir, new_name)?;
+  Ok(dir)
+}
+
+pub fn rename_profile(setting_dir: &Path, name: &str, new_name: &str) -> Result<PathBuf> {
+  ensure_obs_not_running()?;
+  let from = find_profile(setting_dir, name)?;
+  let dir = new_profile_dir(setting_dir, new_name)?;
+  std::fs::rename(&from, &dir)?;
+  set_profile_name(&dir, new_name)?;
+  if current(setting_dir, Kind::Profile)?.as_deref() == Some(name) {
+    let file = dir.file_name().unwrap_or_default().to_string_lossy();
+    set_current(setting_dir, Kind::Profile, new_name, &file)?;
+  }
+  Ok(dir)
+}
+
+pub fn delete_profile(setting_dir: &Path, name: &str) -> Result<()> {
+  ensure_obs_not_running()?;
+  ensure_not_current(setting_dir, Kind::Profile, name)?;
+  std::fs::remove_dir_all(find_profile(setting_dir, name)?)?;
+  Ok(())
+}
+
+/// What the profile `name` streams, from its `service.json`, `basic.ini` and `streamEncoder.json`.
+pub fn stream_check(setting_dir: &Path, name: &str) -> Result<StreamCheck> {
+  let dir = find_profile(setting_dir, name)?;
+  let service = serde_json::from_str(&std::fs::read_to_string(dir.join("service.json"))?)?;
+  let service = StreamService::from_profile(&service).ok_or_else(|| Error::NotFound(format!("rtmp service of {name}")))?;
+  let ini = Ini::parse(&std::fs::read_to_string(dir.join("basic.ini")).unwrap_or_default());
+  let encoder = std::fs::read_to_string(dir.join("streamEncoder.json")).ok()
+    .and_then(|i| serde_json::from_str(&i).ok()).unwrap_or_default();
+  Ok(stream_check_from(service, &ini, &encoder))
+}
+
+/// The defaults are the ones of `UI/window-basic-main.cpp` `InitBasicConfigDefaults`.
+fn stream_check_from(service: StreamService, ini: &Ini, stream_encoder: &serde_json::Value) -> StreamCheck {
+  let number = |section: &str, key: &str, default: u32| ini.get(section, key).and_then(|i| i.parse().ok()).unwrap_or(default);
+  let string = |section: &str, key: &str, default: &'static str| ini.get(section, key).unwrap_or(default).to_string();
+  let base_size = (number("Video", "BaseCX", 1920), number("Video", "BaseCY", 1080));
+  let output_size = (number("Video", "OutputCX", base_size.0), number("Video", "OutputCY", base_size.1));
+  let fps = match ini.get("Video", "FPSType") {
+    Some("1") => number("Video", "FPSInt", 30) as f64,
+    Some("2") => number("Video", "FPSNum", 30) as f64 / number("Video", "FPSDen", 1).max(1) as f64,
+    _ => ini.get("Video", "FPSCommon").and_then(|i| i.parse().ok()).unwrap_or(30.),
+  };
+  if ini.get("Output", "Mode") == Some("Advanced") {
+    let track = number("AdvOut", "TrackIndex", 1);
+    StreamCheck {
+      service,
+      video_codec: video_codec(&string("AdvOut", "Encoder", "obs_x264")).to_string(),
+      audio_codec: audio_codec(&string("AdvOut", "AudioEncoder", "ffmpeg_aac")).to_string(),
+      video_bitrate: stream_encoder.get("bitrate").and_then(serde_json::Value::as_u64).unwrap_or(2500) as u32,
+      audio_bitrate: number("AdvOut", &format!("Track{track}Bitrate"), 160),
+      output_size,
+      fps,
+    }
+  } else {
+    StreamCheck {
+      service,
+      video_codec: video_codec(&string("SimpleOutput", "StreamEncoder", "x264")).to_string(),
+      audio_codec: audio_codec(&string("SimpleOutput", "StreamAudioEncoder", "aac")).to_string(),
+      video_bitrate: number("SimpleOutput", "VBitrate", 2500),
+      audio_bitrate: number("SimpleOutput", "ABitrate", 160),
+      output_size,
+      fps,
+    }
+  }
+}
+
+#[test]
+fn test_stream_check() {
+  let service = StreamService::Common { service: "Twitch".to_string(), server: "auto".to_string(), key: String::new() };
+  let ini = Ini::parse("[Video]\nBaseCX=2560\nBaseCY=1440\nFPSType=2\nFPSNum=30000\nFPSDen=1001\n[SimpleOutput]\nVBitrate=6000\nStreamEncoder=nvenc_hevc\n");
+  let check = stream_check_from(service.clone(), &ini, &serde_json::Value::Null);
+  assert_eq!((check.video_codec.as_str(), check.audio_codec.as_str(), check.video_bitrate, check.audio_bitrate), ("hevc", "aac", 6000, 160));
+  assert_eq!(check.output_size, (2560, 1440));
+  assert!((check.fps - 29.97).abs() < 0.01);
+
+  let ini = Ini::parse("[Output]\nMode=Advanced\n[AdvOut]\nEncoder=ffmpeg_svt_av1\nAudioEncoder=ffmpeg_opus\nTrackIndex=2\nTrack2Bitrate=192\n[Video]\nFPSCommon=60\n");
+  let check = stream_check_from(service, &ini, &serde_json::json!({"bitrate": 8000}));
+  assert_eq!((check.video_codec.as_str(), check.audio_codec.as_str(), check.video_bitrate, check.audio_bitrate), ("av1", "opus", 8000, 192));
+  assert_eq!((check.output_size, check.fps), ((1920, 1080), 60.));
+}
+
+#[test]
+fn test_safe_file_name() {
+  assert_eq!(safe_file_name("My Stream: 2/2"), "My_Stream__2_2");
+  assert_eq!(unique_name("a", |i| i == "a" || i == "a_2"), "a_3");
+}
@@ -0,0 +1,194 @@
+//! Versioned snapshots of scene collections, beyond the single `.json.bak` kept by OBS Studio.
+//!
+//! ```text
+//! basic/scenes/Untitled.json
+//! basic/scenes/.backups/Untitled/1713000000000.json    snapshot, named by unix milliseconds
+//! ```
+
+use std::{collections::BTreeMap, path::{Path, PathBuf}, time::{Duration, SystemTime, UNIX_EPOCH}};
+
+use super::{Error, Result};
+use crate::obs::{collection::{diff::{diff, Change}, save_json_safe, SceneCollection}, Obs};
+
+pub const BACKUP_DIR: &str = ".backups";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RetentionPolicy {
+  /// newest snapshots to keep
+  pub max_count: usize,
+  /// snapshots older than this are removed, the newest one is always kept
+  pub max_age: Option<Duration>,
+}
+
+impl Default for RetentionPolicy {
+  fn default() -> Self {
+    Self { max_count: 50, max_age: Some(Duration::from_secs(30 * 24 * 3600)) }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+pub struct Snapshot {
+  /// unix milliseconds, also the file name
+  #[ts(type = "number")]
+  pub id: u64,
+  pub path: PathBuf,
+  #[ts(type = "number")]
+  pub size: u64,
+  /// number of changes since the previous snapshot by change type, e.g. `source_added`,
+  /// empty for the oldest one
+  pub summary: BTreeMap<String, usize>,
+}
+
+/// `.backups/{stem}` next to the collection
+pub fn backup_dir(collection: &Path) -> PathBuf {
+  let stem = collection.file_stem().unwrap_or_default();
+  collection.with_file_name(BACKUP_DIR).join(stem)
+}
+
+fn now_millis() -> u64 {
+  SystemTime::now().duration_since(UNIX_EPOCH).map(|i| i.as_millis() as u64).unwrap_or_default()
+}
+
+/// ids of the snapshots of `collection`, oldest first
+fn snapshot_ids(collection: &Path) -> Vec<u64> {
+  let Ok(read_dir) = std::fs::read_dir(backup_dir(collection)) else { return Vec::new() };
+  let mut ids = read_dir.filter_map(|i| {
+    let path = i.ok()?.path();
+    if path.extension()? != "json" { return None }
+    path.file_stem()?.to_str()?.parse().ok()
+  }).collect::<Vec<u64>>();
+  ids.sort();
+  ids
+}
+
+fn snapshot_path(collection: &Path, id: u64) -> PathBuf {
+  backup_dir(collection).join(format!("{id}.json"))
+}
+
+/// Copy the current content of `collection` into a new snapshot, unless it equals the latest one,
+/// then drop the snapshots out of `policy`. Returns the id of the snapshot holding the current content.
+pub fn snapshot(collection: &Path, policy: &RetentionPolicy) -> Result<u64> {
+  let content = std::fs::read(collection)?;
+  let ids = snapshot_ids(collection);
+  if let Some(&latest) = ids.last() {
+    if std::fs::read(snapshot_path(collection, latest)).ok().as_ref() == Some(&content) {
+      return Ok(latest)
+    }
+  }
+  std::fs::create_dir_all(backup_dir(collection))?;
+  // two saves within the same millisecond
+  let id = now_millis().max(ids.last().map_or(0, |i| i + 1));
+  std::fs::write(snapshot_path(collection, id), content)?;
+  let mut ids = ids;
+  ids.push(id);
+  for id in expired(&ids, now_millis(), policy) {
+    std::fs::remove_file(snapshot_path(collection, id))?;
+  }
+  Ok(id)
+}
+
+/// Save the live scene graph of `obs` into `collection` and snapshot the result.
+pub fn save(obs: &Obs, collection: &Path, policy: &RetentionPolicy) -> Result<u64> {
+  obs.save_scene_collection(collection)?;
+  snapshot(collection, policy)
+}
+
+/// Write `content` into `collection` with [`save_json_safe`] and snapshot the result,
+/// every write of a collection file should go through here or [`save`].
+pub fn save_json(collection: &Path, content: &str, policy: &RetentionPolicy) -> Result<u64> {
+  save_json_safe(collection, content)?;
+  snapshot(collection, policy)
+}
+
+/// Snapshots to remove, `ids` sorted oldest first.
+pub fn expired(ids: &[u64], now: u64, policy: &RetentionPolicy) -> Vec<u64> {
+  let Some((newest, older)) = ids.split_last() else { return Vec::new() };
+  let keep_from = older.len().saturating_sub(policy.max_count.saturating_sub(1));
+  older.iter().enumerate().filter(|(idx, id)| {
+    let too_old = policy.max_age.map_or(false, |age| now.saturating_sub(**id) > age.as_millis() as u64);
+    *idx < keep_from || too_old
+  }).map(|(_, id)| *id).filter(|id| id != newest).collect()
+}
+
+fn summary(changes: &[Change]) -> BTreeMap<String, usize> {
+  let mut result = BTreeMap::new();
+  for change in changes {
+    let kind = serde_json::to_value(change).ok().and_then(|i| i["type"].as_str().map(str::to_string)).unwrap_or_default();
+    *result.entry(kind).or_default() += 1;
+  }
+  result
+}
+
+/// Snapshots of `collection`, newest first, each with a summary of its changes from the previous one.
+pub fn list_snapshots(collection: &Path) -> Result<Vec<Snapshot>> {
+  let mut result = Vec::new();
+  let mut previous: Option<SceneCollection> = None;
+  for id in snapshot_ids(collection) {
+    let path = snapshot_path(collection, id);
+    let size = std::fs::metadata(&path)?.len();
+    let current = SceneCollection::from_file(&path).ok();
+    let summary = match (&previous, &current) {
+      (Some(previous), Some(current)) => summary(&diff(previous, current)),
+      _ => BTreeMap::new(),
+    };
+    result.push(Snapshot { id, path, size, summary });
+    previous = current;
+  }
+  result.reverse();
+  Ok(result)
+}
+
+/// Write snapshot `id` back to `collection`, after a snapshot of the current content so it could be undone.
+pub fn restore(collection: &Path, id: u64, policy: &RetentionPolicy) -> Result<()> {
+  let path = snapshot_path(collection, id);
+  if !path.exists() {
+    return Err(Error::NotFound(path.display().to_string()))
+  }
+  let content = std::fs::read_to_string(&path)?;
+  // refuse to restore something OBS Studio could not load
+  serde_json::from_str::<SceneCollection>(&content)?;
+  if collection.exists() {
+    snapshot(collection, policy)?;
+  }
+  save_json_safe(collection, &content)?;
+  info!(collection=%collection.display(), id, "snapshot restored");
+  Ok(())
+}
+
+#[test]
+fn test_expired() {
+  let day = 24 * 3600 * 1000;
+  let policy = RetentionPolicy { max_count: 3, max_age: Some(Duration::from_millis(10 * day)) };
+  assert!(expired(&[], 0, &policy).is_empty());
+  assert_eq!(expired(&[1, 2, 3, 4, 5], 6, &policy), [1, 2]);
+  assert_eq!(expired(&[1, 20 * day, 25 * day], 26 * day, &policy), [1]);
+  // the newest one is kept even if too old
+  assert_eq!(expired(&[1, 2], 100 * day, &policy), [1]);
+}
+
+#[test]
+fn test_snapshot() {
+  let dir = crate::testing::TempDir::new("backup");
+  let collection = dir.join("Untitled.json");
+  let policy = RetentionPolicy::default();
+  std::fs::write(&collection, r#"{"name":"Untitled","sources":[]}"#).unwrap();
+  let first = snapshot(&collection, &policy).unwrap();
+  assert_eq!(snapshot(&collection, &policy).unwrap(), first);
+  std::fs::write(&collection, r#"{"name":"Untitled","sources":[{"name":"Color","id":"color_source"}]}"#).unwrap();
+  let second = snapshot(&collection, &policy).unwrap();
+  assert!(second > first);
+
+  let snapshots = list_snapshots(&collection).unwrap();
+  assert_eq!(snapshots.iter().map(|i| i.id).collect::<Vec<_>>(), [second, first]);
+  assert_eq!(snapshots[0].summary, BTreeMap::from([("source_added".to_string(), 1)]));
+  assert!(snapshots[1].summary.is_empty());
+
+  // every save keeps a snapshot
+  save_json(&collection, r#"{"name":"Untitled","sources":[]}"#, &policy).unwrap();
+  save_json(&collection, r#"{"name":"Untitled","sources":[],"scene_order":[]}"#, &policy).unwrap();
+  assert_eq!(list_snapshots(&collection).unwrap().len(), 4);
+
+  restore(&collection, first, &policy).unwrap();
+  assert_eq!(std::fs::read_to_string(&collection).unwrap(), r#"{"name":"Untitled","sources":[]}"#);
+  assert!(restore(&collection, 42, &policy).is_err());
+}
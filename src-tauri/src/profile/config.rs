@@ -0,0 +1,231 @@
+//! Typed view of the application config, `global.ini` (or `user.ini` since OBS Studio 31).
+//!
+//! ```ini
+//! [General]
+//! LastVersion=503447552
+//! FirstRun=true
+//! Language=en-US
+//!
+//! [Basic]
+//! Profile=Untitled
+//! ProfileDir=Untitled
+//! SceneCollection=Untitled
+//! SceneCollectionFile=Untitled
+//!
+//! [BasicWindow]
+//! geometry=AdnQywADAAAAAAA...
+//! ```
+//! Every key is optional, OBS Studio fills in its defaults on start.
+//! Only the typed fields that changed are written back, the rest of the file is kept as is.
+//!
+//! see `UI/obs-app.cpp` `InitGlobalConfigDefaults`
+
+use std::{collections::BTreeMap, path::Path};
+
+use super::{config_file, ini::Ini, Result};
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GeneralConfig {
+  /// `LIBOBS_API_VER` of the last OBS Studio run, see [`AppConfig::last_version_string`]
+  pub last_version: Option<u32>,
+  pub first_run: Option<bool>,
+  pub language: Option<String>,
+  /// `CurrentTheme3`
+  pub current_theme: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct BasicConfig {
+  /// display name of the active profile
+  pub profile: Option<String>,
+  /// folder of the active profile under `basic/profiles`
+  pub profile_dir: Option<String>,
+  /// display name of the active scene collection
+  pub scene_collection: Option<String>,
+  /// file of the active scene collection under `basic/scenes`, without `.json`
+  pub scene_collection_file: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AppConfig {
+  pub general: GeneralConfig,
+  pub basic: BasicConfig,
+  /// `[Hotkeys]`, each binding is a json object like `{"bindings":[{"key":"OBS_KEY_F1"}]}`
+  pub hotkeys: BTreeMap<String, serde_json::Value>,
+  /// `[BasicWindow]`, geometry, dock state and other UI state, kept verbatim
+  pub window: BTreeMap<String, String>,
+  #[serde(skip)]
+  ini: Ini,
+}
+
+/// A value stored in an ini entry, the same way `util/config-file.c` reads and writes it.
+trait IniValue: Sized + PartialEq {
+  fn from_ini(value: &str) -> Option<Self>;
+  fn to_ini(&self) -> String;
+}
+
+impl IniValue for String {
+  fn from_ini(value: &str) -> Option<Self> { Some(value.to_string()) }
+  fn to_ini(&self) -> String { self.clone() }
+}
+
+impl IniValue for u32 {
+  fn from_ini(value: &str) -> Option<Self> { value.parse().ok() }
+  fn to_ini(&self) -> String { self.to_string() }
+}
+
+impl IniValue for bool {
+  /// `config_get_bool`, `true` or a non zero number
+  fn from_ini(value: &str) -> Option<Self> {
+    match value {
+      _ if value.eq_ignore_ascii_case("true") => Some(true),
+      _ if value.eq_ignore_ascii_case("false") => Some(false),
+      _ => value.parse::<i64>().ok().map(|i| i != 0),
+    }
+  }
+  fn to_ini(&self) -> String { self.to_string() }
+}
+
+impl IniValue for serde_json::Value {
+  /// not every value is json, e.g. an empty binding, those are kept as a string
+  fn from_ini(value: &str) -> Option<Self> {
+    Some(serde_json::from_str(value).unwrap_or_else(|_| value.into()))
+  }
+  fn to_ini(&self) -> String {
+    match self {
+      serde_json::Value::String(s) => s.clone(),
+      _ => self.to_string(),
+    }
+  }
+}
+
+fn get<T: IniValue>(ini: &Ini, section: &str, key: &str) -> Option<T> {
+  ini.get(section, key).and_then(T::from_ini)
+}
+
+/// Write `value` unless the file already holds the same value, maybe spelled differently.
+fn put<T: IniValue>(ini: &mut Ini, section: &str, key: &str, value: Option<&T>) {
+  if get::<T>(ini, section, key).as_ref() == value { return }
+  match value {
+    Some(value) => ini.set(section, key, &value.to_ini()),
+    None => { ini.remove(section, key); },
+  }
+}
+
+fn put_map<T: IniValue>(ini: &mut Ini, section: &str, map: &BTreeMap<String, T>) {
+  let removed = ini.section(section).into_iter()
+    .filter(|(key, _)| !map.contains_key(*key))
+    .map(|(key, _)| key.to_string()).collect::<Vec<_>>();
+  for key in removed {
+    ini.remove(section, &key);
+  }
+  for (key, value) in map {
+    put(ini, section, key, Some(value));
+  }
+}
+
+fn get_map<T: IniValue>(ini: &Ini, section: &str) -> BTreeMap<String, T> {
+  ini.section(section).into_iter()
+    .filter_map(|(key, value)| Some((key.to_string(), T::from_ini(value)?)))
+    .collect()
+}
+
+impl AppConfig {
+  pub fn parse(content: &str) -> Self {
+    let ini = Ini::parse(content);
+    Self {
+      general: GeneralConfig {
+        last_version: get(&ini, "General", "LastVersion"),
+        first_run: get(&ini, "General", "FirstRun"),
+        language: get(&ini, "General", "Language"),
+        current_theme: get(&ini, "General", "CurrentTheme3"),
+      },
+      basic: BasicConfig {
+        profile: get(&ini, "Basic", "Profile"),
+        profile_dir: get(&ini, "Basic", "ProfileDir"),
+        scene_collection: get(&ini, "Basic", "SceneCollection"),
+        scene_collection_file: get(&ini, "Basic", "SceneCollectionFile"),
+      },
+      hotkeys: get_map(&ini, "Hotkeys"),
+      window: get_map(&ini, "BasicWindow"),
+      ini,
+    }
+  }
+
+  /// The config of `setting_dir`, empty if there is none yet.
+  pub fn load(setting_dir: &Path) -> Result<Self> {
+    match std::fs::read_to_string(config_file(setting_dir)) {
+      Ok(content) => Ok(Self::parse(&content)),
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+      Err(e) => Err(e.into()),
+    }
+  }
+
+  pub fn save(&self, setting_dir: &Path) -> Result<()> {
+    std::fs::write(config_file(setting_dir), self.to_string())?;
+    Ok(())
+  }
+
+  /// The underlying file with the typed fields applied.
+  pub fn to_ini(&self) -> Ini {
+    let mut ini = self.ini.clone();
+    let general = &self.general;
+    put(&mut ini, "General", "LastVersion", general.last_version.as_ref());
+    put(&mut ini, "General", "FirstRun", general.first_run.as_ref());
+    put(&mut ini, "General", "Language", general.language.as_ref());
+    put(&mut ini, "General", "CurrentTheme3", general.current_theme.as_ref());
+    let basic = &self.basic;
+    put(&mut ini, "Basic", "Profile", basic.profile.as_ref());
+    put(&mut ini, "Basic", "ProfileDir", basic.profile_dir.as_ref());
+    put(&mut ini, "Basic", "SceneCollection", basic.scene_collection.as_ref());
+    put(&mut ini, "Basic", "SceneCollectionFile", basic.scene_collection_file.as_ref());
+    put_map(&mut ini, "Hotkeys", &self.hotkeys);
+    put_map(&mut ini, "BasicWindow", &self.window);
+    ini
+  }
+
+  /// `LastVersion` as `major.minor.patch`, see `MAKE_SEMANTIC_VERSION`
+  pub fn last_version_string(&self) -> Option<String> {
+    let version = self.general.last_version?;
+    Some(format!("{}.{}.{}", version >> 24, (version >> 16) & 0xff, version & 0xffff))
+  }
+
+  /// File name of the active scene collection under `basic/scenes`.
+  pub fn scene_collection_file_name(&self) -> Option<String> {
+    self.basic.scene_collection_file.as_ref().map(|i| format!("{i}.json"))
+  }
+}
+
+impl std::fmt::Display for AppConfig {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    self.to_ini().fmt(f)
+  }
+}
+
+#[test]
+fn test_app_config() {
+  let content = "\u{feff}[General]\r\nLastVersion=503447552\r\nFirstRun=1\r\n\r\n[Basic]\r\nProfile=Main\r\nProfileDir=Main\r\nSceneCollection=Stream\r\nSceneCollectionFile=Stream\r\n\r\n[Hotkeys]\r\nOBSBasic.StartStreaming={\r\n\r\n[BasicWindow]\r\ngeometry=AdnQywADAAAAAAA=\r\n";
+  let mut config = AppConfig::parse(content);
+  assert_eq!(config.general.last_version, Some(503447552));
+  assert_eq!(config.last_version_string().as_deref(), Some("30.2.0"));
+  // `1` is read as true but not rewritten
+  assert_eq!(config.general.first_run, Some(true));
+  assert_eq!(config.general.language, None);
+  assert_eq!(config.basic.profile_dir.as_deref(), Some("Main"));
+  assert_eq!(config.scene_collection_file_name().as_deref(), Some("Stream.json"));
+  assert_eq!(config.hotkeys["OBSBasic.StartStreaming"], "{");
+  assert_eq!(config.window["geometry"], "AdnQywADAAAAAAA=");
+  assert_eq!(config.to_string(), content);
+
+  config.basic.scene_collection = Some("Podcast".to_string());
+  config.basic.scene_collection_file = Some("Podcast".to_string());
+  config.general.first_run = None;
+  config.hotkeys.insert("OBSBasic.StartRecording".to_string(), serde_json::json!({"bindings": []}));
+  let config = AppConfig::parse(&config.to_string());
+  assert_eq!(config.basic.scene_collection.as_deref(), Some("Podcast"));
+  assert_eq!(config.general.first_run, None);
+  assert_eq!(config.hotkeys["OBSBasic.StartRecording"], serde_json::json!({"bindings": []}));
+  assert!(config.to_string().ends_with("geometry=AdnQywADAAAAAAA=\r\n"));
+
+  assert_eq!(AppConfig::parse("").to_string(), "");
+}
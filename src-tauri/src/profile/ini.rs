@@ -0,0 +1,166 @@
+//! Minimal ini editor for the config files of OBS Studio (`global.ini`, `user.ini`, `basic.ini`),
+//! editing a key keeps every other line, comment and ordering untouched.
+//!
+//! see `deps/libobs/util/config-file.c`
+
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Line {
+  Section(String),
+  Entry { key: String, value: String },
+  /// blank lines and comments
+  Other(String),
+}
+
+const BOM: char = '\u{feff}';
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Ini {
+  lines: Vec<Line>,
+  /// files written on Windows start with a BOM and use CRLF, both kept on save
+  bom: bool,
+  crlf: bool,
+}
+
+impl Ini {
+  pub fn parse(content: &str) -> Self {
+    let bom = content.starts_with(BOM);
+    let content = content.trim_start_matches(BOM);
+    let crlf = content.contains("\r\n");
+    // `lines` drops the `\r` of `\r\n`
+    let lines = content.lines().map(|line| {
+      let trimmed = line.trim();
+      if let Some(name) = trimmed.strip_prefix('[').and_then(|i| i.strip_suffix(']')) {
+        return Line::Section(name.trim().to_string())
+      }
+      match trimmed.split_once('=') {
+        Some((key, value)) if !trimmed.starts_with(';') && !trimmed.starts_with('#') => {
+          Line::Entry { key: key.trim().to_string(), value: value.trim().to_string() }
+        },
+        _ => Line::Other(line.to_string()),
+      }
+    }).collect();
+    Self { lines, bom, crlf }
+  }
+
+  pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+    let mut current = None;
+    for line in &self.lines {
+      match line {
+        Line::Section(name) => current = Some(name.as_str()),
+        Line::Entry { key: k, value } if current == Some(section) && k == key => return Some(value),
+        _ => {},
+      }
+    }
+    None
+  }
+
+  /// Replace the value of `key`, or append it to `section` (created if needed).
+  pub fn set(&mut self, section: &str, key: &str, value: &str) {
+    let mut current = None;
+    let mut insert_at = None;
+    for (idx, line) in self.lines.iter_mut().enumerate() {
+      match line {
+        Line::Section(name) => current = Some(name.clone()),
+        Line::Entry { key: k, value: v } if current.as_deref() == Some(section) && k == key => {
+          *v = value.to_string();
+          return
+        },
+        _ => {},
+      }
+      if current.as_deref() == Some(section) && !matches!(line, Line::Other(s) if s.trim().is_empty()) {
+        insert_at = Some(idx + 1);
+      }
+    }
+    let entry = Line::Entry { key: key.to_string(), value: value.to_string() };
+    match insert_at {
+      Some(idx) => self.lines.insert(idx, entry),
+      None => {
+        if !self.lines.is_empty() {
+          self.lines.push(Line::Other(String::new()));
+        }
+        self.lines.push(Line::Section(section.to_string()));
+        self.lines.push(entry);
+      },
+    }
+  }
+
+  /// Remove `key` from `section`, returns the old value.
+  pub fn remove(&mut self, section: &str, key: &str) -> Option<String> {
+    let mut current = None;
+    let idx = self.lines.iter().position(|line| match line {
+      Line::Section(name) => { current = Some(name.clone()); false },
+      Line::Entry { key: k, .. } => current.as_deref() == Some(section) && k == key,
+      Line::Other(_) => false,
+    })?;
+    match self.lines.remove(idx) {
+      Line::Entry { value, .. } => Some(value),
+      _ => None,
+    }
+  }
+
+  /// Entries of `section` in file order.
+  pub fn section(&self, section: &str) -> Vec<(&str, &str)> {
+    let mut current = None;
+    self.lines.iter().filter_map(|line| match line {
+      Line::Section(name) => { current = Some(name.as_str()); None },
+      Line::Entry { key, value } if current == Some(section) => Some((key.as_str(), value.as_str())),
+      _ => None,
+    }).collect()
+  }
+}
+
+impl Ini {
+  /// Every entry by section, for the frontend.
+  pub fn to_map(&self) -> BTreeMap<String, BTreeMap<String, String>> {
+    let mut result = BTreeMap::<String, BTreeMap<String, String>>::new();
+    let mut current = String::new();
+    for line in &self.lines {
+      match line {
+        Line::Section(name) => current = name.clone(),
+        Line::Entry { key, value } => { result.entry(current.clone()).or_default().insert(key.clone(), value.clone()); },
+        Line::Other(_) => {},
+      }
+    }
+    result
+  }
+}
+
+impl std::fmt::Display for Ini {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let newline = if self.crlf { "\r\n" } else { "\n" };
+    if self.bom {
+      write!(f, "{BOM}")?;
+    }
+    for line in &self.lines {
+      match line {
+        Line::Section(name) => write!(f, "[{name}]{newline}")?,
+        Line::Entry { key, value } => write!(f, "{key}={value}{newline}")?,
+        Line::Other(s) => write!(f, "{s}{newline}")?,
+      }
+    }
+    Ok(())
+  }
+}
+
+#[test]
+fn test_ini() {
+  let content = "[General]\nName=Untitled\n\n[Basic]\nProfile=Untitled\nSceneCollection=Untitled\n";
+  let mut ini = Ini::parse(content);
+  assert_eq!(ini.get("Basic", "Profile"), Some("Untitled"));
+  assert_eq!(ini.get("Basic", "Name"), None);
+  assert_eq!(ini.to_string(), content);
+  ini.set("Basic", "SceneCollection", "Stream");
+  ini.set("General", "LastVersion", "1");
+  ini.set("Video", "BaseCX", "1920");
+  assert_eq!(ini.to_string(), "[General]\nName=Untitled\nLastVersion=1\n\n[Basic]\nProfile=Untitled\nSceneCollection=Stream\n\n[Video]\nBaseCX=1920\n");
+  assert_eq!(ini.remove("General", "Name").as_deref(), Some("Untitled"));
+  assert_eq!(ini.remove("General", "Name"), None);
+  assert_eq!(ini.section("Basic"), [("Profile", "Untitled"), ("SceneCollection", "Stream")]);
+
+  let content = "\u{feff}[General]\r\nName=Untitled\r\n\r\n[Basic]\r\nProfile=Untitled\r\n";
+  let ini = Ini::parse(content);
+  assert_eq!(ini.get("General", "Name"), Some("Untitled"));
+  assert_eq!(ini.to_string(), content);
+}
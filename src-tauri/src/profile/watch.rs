@@ -0,0 +1,162 @@
+//! Watch the OBS setting folder and report what changed in scene collections and profiles,
+//! so the frontend does not have to poll `list_profile`.
+//!
+//! OBS Studio saves a collection as `.tmp` then renames it (see `save_json_safe`),
+//! so writes are debounced and only the files reported by the watcher are parsed again.
+
+use std::{collections::{BTreeMap, HashMap}, path::{Path, PathBuf}, time::Duration};
+
+use notify_debouncer_mini::{new_debouncer, notify::{RecommendedWatcher, RecursiveMode}, DebounceEventResult, Debouncer};
+
+use super::{ini::Ini, profiles_dir, scenes_dir, Result};
+use crate::obs::collection::SceneCollection;
+
+pub const DEBOUNCE: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProfileEvent {
+  CollectionAdded { file: String, collection: SceneCollection },
+  CollectionChanged { file: String, collection: SceneCollection },
+  CollectionRemoved { file: String },
+  /// `basic.ini` of a profile changed, `config` is empty and `name` none if the profile was removed
+  ProfileChanged { dir: String, name: Option<String>, config: BTreeMap<String, BTreeMap<String, String>> },
+}
+
+impl ProfileEvent {
+  /// tauri event name
+  pub fn name(&self) -> &'static str {
+    match self {
+      ProfileEvent::CollectionAdded { .. } => "collection-added",
+      ProfileEvent::CollectionChanged { .. } => "collection-changed",
+      ProfileEvent::CollectionRemoved { .. } => "collection-removed",
+      ProfileEvent::ProfileChanged { .. } => "profile-changed",
+    }
+  }
+}
+
+/// Last parsed content of every scene collection, to tell an added file from a changed one
+/// and to skip writes that did not change anything.
+#[derive(Debug, Default)]
+pub struct WatchState {
+  setting_dir: PathBuf,
+  /// by file name
+  collections: HashMap<String, SceneCollection>,
+}
+
+impl WatchState {
+  /// `setting_dir` should be the path the watcher reports, see [`ProfileWatcher::new`].
+  pub fn new<P: AsRef<Path>>(setting_dir: P) -> Self {
+    let setting_dir = setting_dir.as_ref().to_path_buf();
+    let mut collections = HashMap::new();
+    if let Ok(read_dir) = std::fs::read_dir(scenes_dir(&setting_dir)) {
+      for entry in read_dir.filter_map(|i| i.ok()) {
+        if let Ok(collection) = SceneCollection::from_file(entry.path()) {
+          collections.insert(entry.file_name().to_string_lossy().to_string(), collection);
+        }
+      }
+    }
+    Self { setting_dir, collections }
+  }
+
+  /// Event for a path reported by the watcher, if it is a collection or a profile that really changed.
+  pub fn update(&mut self, path: &Path) -> Option<ProfileEvent> {
+    let file_name = path.file_name()?.to_string_lossy().to_string();
+    // not canonicalized, it fails once the folder is removed
+    let parent = path.parent()?;
+    if parent == scenes_dir(&self.setting_dir) {
+      // backups and temporary files of `save_json_safe`
+      if !file_name.ends_with(".json") { return None }
+      if !path.exists() {
+        self.collections.remove(&file_name)?;
+        return Some(ProfileEvent::CollectionRemoved { file: file_name })
+      }
+      let collection = match SceneCollection::from_file(path) {
+        Ok(collection) => collection,
+        Err(e) => {
+          // half written, wait for the next event
+          debug!(path=%path.display(), error=%e, "skip scene collection");
+          return None
+        },
+      };
+      return match self.collections.insert(file_name.clone(), collection.clone()) {
+        None => Some(ProfileEvent::CollectionAdded { file: file_name, collection }),
+        Some(old) if old != collection => Some(ProfileEvent::CollectionChanged { file: file_name, collection }),
+        Some(_) => None,
+      }
+    }
+    if parent == profiles_dir(&self.setting_dir) && !path.exists() {
+      return Some(ProfileEvent::ProfileChanged { dir: file_name, name: None, config: BTreeMap::new() })
+    }
+    // the removal of the whole profile is reported for its folder above
+    if file_name == "basic.ini" && parent.parent() == Some(profiles_dir(&self.setting_dir).as_path()) && parent.exists() {
+      let dir = parent.file_name()?.to_string_lossy().to_string();
+      let ini = std::fs::read_to_string(path).map(|i| Ini::parse(&i)).unwrap_or_default();
+      let name = ini.get("General", "Name").map(str::to_string);
+      return Some(ProfileEvent::ProfileChanged { dir, name, config: ini.to_map() })
+    }
+    None
+  }
+}
+
+pub struct ProfileWatcher {
+  _debouncer: Debouncer<RecommendedWatcher>,
+}
+
+impl ProfileWatcher {
+  /// Start watching `setting_dir`, `callback` is called from the watcher thread.
+  pub fn new<P: AsRef<Path>, F: FnMut(ProfileEvent) + Send + 'static>(setting_dir: P, mut callback: F) -> Result<Self> {
+    let setting_dir = setting_dir.as_ref();
+    for dir in [scenes_dir(setting_dir), profiles_dir(setting_dir)] {
+      std::fs::create_dir_all(dir)?;
+    }
+    // the watcher reports resolved paths, e.g. `/private/var` for `/var` on macOS
+    let setting_dir = setting_dir.canonicalize()?;
+    let mut state = WatchState::new(&setting_dir);
+    let mut debouncer = new_debouncer(DEBOUNCE, move |result: DebounceEventResult| {
+      match result {
+        Ok(events) => for event in events {
+          if let Some(event) = state.update(&event.path) {
+            callback(event);
+          }
+        },
+        Err(e) => warn!(error=%e, "watch error"),
+      }
+    }).map_err(std::io::Error::other)?;
+    for dir in [scenes_dir(&setting_dir), profiles_dir(&setting_dir)] {
+      debouncer.watcher().watch(&dir, RecursiveMode::Recursive).map_err(std::io::Error::other)?;
+    }
+    info!(setting_dir=%setting_dir.display(), "watching");
+    Ok(Self { _debouncer: debouncer })
+  }
+}
+
+#[test]
+fn test_watch_state() {
+  let dir = crate::testing::TempDir::new("watch");
+  std::fs::create_dir_all(scenes_dir(&dir)).unwrap();
+  std::fs::create_dir_all(profiles_dir(&dir).join("Untitled")).unwrap();
+  let collection = scenes_dir(&dir).join("Untitled.json");
+  std::fs::write(&collection, r#"{"name":"Untitled"}"#).unwrap();
+  let mut state = WatchState::new(&dir);
+
+  assert_eq!(state.update(&collection), None);
+  std::fs::write(&collection, r#"{"name":"Untitled","current_scene":"Scene"}"#).unwrap();
+  assert!(matches!(state.update(&collection), Some(ProfileEvent::CollectionChanged { file, .. }) if file == "Untitled.json"));
+  assert_eq!(state.update(&scenes_dir(&dir).join("Untitled.json.bak")), None);
+
+  let added = scenes_dir(&dir).join("New.json");
+  std::fs::write(&added, r#"{"name":"New"}"#).unwrap();
+  assert!(matches!(state.update(&added), Some(ProfileEvent::CollectionAdded { collection, .. }) if collection.name == "New"));
+  std::fs::remove_file(&added).unwrap();
+  assert_eq!(state.update(&added), Some(ProfileEvent::CollectionRemoved { file: "New.json".to_string() }));
+
+  let basic = profiles_dir(&dir).join("Untitled/basic.ini");
+  std::fs::write(&basic, "[General]\nName=Untitled\n").unwrap();
+  assert!(matches!(state.update(&basic), Some(ProfileEvent::ProfileChanged { name: Some(name), .. }) if name == "Untitled"));
+  std::fs::remove_dir_all(profiles_dir(&dir).join("Untitled")).unwrap();
+  assert_eq!(state.update(&basic), None);
+  assert_eq!(state.update(&profiles_dir(&dir).join("Untitled")), Some(ProfileEvent::ProfileChanged {
+    dir: "Untitled".to_string(), name: None, config: BTreeMap::new(),
+  }));
+}
@@ -0,0 +1,46 @@
+//! Fixtures shared by the tests.
+
+use std::path::{Path, PathBuf};
+
+/// `myobs-test-{name}-{pid}` under the temp folder, removed with its content when dropped,
+/// so a failed assert leaves nothing behind for the next run.
+#[derive(Debug)]
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+  /// Emptied first, in case a killed run could not clean up.
+  pub fn new(name: &str) -> Self {
+    let path = std::env::temp_dir().join(format!("myobs-test-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    Self(path)
+  }
+}
+
+impl std::ops::Deref for TempDir {
+  type Target = Path;
+  fn deref(&self) -> &Path {
+    &self.0
+  }
+}
+
+impl AsRef<Path> for TempDir {
+  fn as_ref(&self) -> &Path {
+    &self.0
+  }
+}
+
+impl Drop for TempDir {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_dir_all(&self.0);
+  }
+}
+
+#[test]
+fn test_temp_dir() {
+  let dir = TempDir::new("temp-dir");
+  std::fs::write(dir.join("a.txt"), "").unwrap();
+  let path = dir.to_path_buf();
+  drop(dir);
+  assert!(!path.exists());
+}
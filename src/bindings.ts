@@ -0,0 +1,985 @@
+// Generated by `cargo test` from `src-tauri/src/bindings.rs`, do not edit.
+import { invoke } from "@tauri-apps/api/core"
+import { listen, type UnlistenFn } from "@tauri-apps/api/event"
+
+export type AudioEncoder = "ffmpeg_aac" | "ffmpeg_opus";
+
+export type AudioEncoderSetting = { 
+/**
+ * kbps, obs_data_set_default_int(settings, "bitrate", 128);
+ */
+bitrate: number, };
+
+export type Av1RateControl = "CBR" | "VBR" | "CQP";
+
+export type Av1Setting = { 
+/**
+ * obs_data_set_default_string(settings, "rate_control", "CBR");
+ */
+rate_control: Av1RateControl, 
+/**
+ * kbps, obs_data_set_default_int(settings, "bitrate", 2500);
+ */
+bitrate: number, 
+/**
+ * quantizer, only used by `CQP`, obs_data_set_default_int(settings, "cqp", 50);
+ */
+cqp: number, 
+/**
+ * keyframe interval in seconds, 0 for auto, obs_data_set_default_int(settings, "keyint_sec", 0);
+ */
+keyint_sec: number, 
+/**
+ * speed, higher is faster, obs_data_set_default_int(settings, "preset", 8);
+ */
+preset: number, 
+/**
+ * space separated `key=value` options passed to ffmpeg, obs_data_set_default_string(settings, "ffmpeg_opts", "");
+ */
+ffmpeg_opts: string, };
+
+/**
+ * A resolution and frame rate with a lower bitrate limit than the service.
+ */
+export type BitrateLimit = { 
+/**
+ * e.g. `1280x720`
+ */
+res: string, fps: number, "max bitrate": number, };
+
+/**
+ * ```c
+ * #define SETTING_MODE_ANY         "any_fullscreen"
+ * #define SETTING_MODE_WINDOW      "window"
+ * #define SETTING_MODE_HOTKEY      "hotkey"
+ * ```
+ */
+export type CaptureMode = "any_fullscreen" | "window" | "hotkey";
+
+export type Change = { "type": "source_added", name: string, } | { "type": "source_removed", name: string, } | { "type": "source_renamed", from: string, to: string, } | { "type": "property_changed", source: string | null, key: string, old: JsonValue | null, new: JsonValue | null, } | { "type": "setting_changed", source: string, filter: string | null, key: string, old: JsonValue | null, new: JsonValue | null, } | { "type": "item_added", scene: string, item: string, } | { "type": "item_removed", scene: string, item: string, } | { "type": "item_moved", scene: string, item: string, from: Transform, to: Transform, } | { "type": "item_changed", scene: string, item: string, key: string, old: JsonValue | null, new: JsonValue | null, } | { "type": "filter_added", source: string, filter: string, } | { "type": "filter_removed", source: string, filter: string, } | { "type": "filters_reordered", source: string, from: Array<string>, to: Array<string>, } | { "type": "scene_order_changed", from: Array<string>, to: Array<string>, };
+
+/**
+ * ```c
+ * static void color_source_defaults_v1(obs_data_t *settings)
+ * static void color_source_defaults_v2(obs_data_t *settings)
+ * static void color_source_defaults_v3(obs_data_t *settings)
+ * ```
+ */
+export type ColorSetting = { 
+/**
+ * obs_data_set_default_int(settings, "color", 0xFFD1D1D1);
+ */
+color: number, 
+/**
+ * obs_data_set_default_int(settings, "width", 1920);
+ */
+width: number, 
+/**
+ * obs_data_set_default_int(settings, "height", 1080);
+ */
+height: number, };
+
+export type CommandError = { code: ErrorCode, message: string, details?: JsonValue, 
+/**
+ * messages of the underlying errors, outermost first
+ */
+sources: Array<string>, };
+
+export type Conflict = { 
+/**
+ * e.g. `sources[Color].settings.color`
+ */
+path: string, base: JsonValue | null, ours: JsonValue | null, theirs: JsonValue | null, };
+
+/**
+ * `RecFormat2` of a profile
+ */
+export type Container = "mkv" | "mp4" | "fragmented_mp4" | "mov" | "flv";
+
+/**
+ * ```c
+ * enum crop_mode {
+ *   CROP_NONE,
+ *   CROP_MANUAL,
+ *   CROP_TO_WINDOW,
+ *   CROP_TO_WINDOW_AND_MANUAL,
+ *   CROP_INVALID
+ * };
+ * ```
+ */
+export enum CropMode { "None" = 0, "Manual", "ToWindow", "ToWindowAndManual", "Invalid" }
+
+export type DanglingItem = { scene: string, item: string, source_uuid: string | null, };
+
+export type ErrorCode = "utf8" | "nul" | "json" | "io" | "not_found" | "permission_denied" | "already_exists" | "zip" | "obs_code" | "nul_pointer" | "obs" | "obs_not_initialized" | "reset_audio" | "module_not_loaded" | "scene_cycle" | "not_a_group" | "output" | "remux" | "cancelled" | "no_loopback_device" | "obs_running" | "in_use" | "window" | "tauri";
+
+export type ExportReport = { bundle: string, 
+/**
+ * number of files packed
+ */
+files: number, 
+/**
+ * referenced files that do not exist, kept as is in the bundle
+ */
+missing: Array<FileRef>, };
+
+export type FileRef = { path: string, source: string, 
+/**
+ * set when the path is in a filter of `source`
+ */
+filter: string | null, key: string, 
+/**
+ * position in `FILE_LIST_KEYS` lists
+ */
+index: number | null, };
+
+/**
+ * ```c
+ * enum hook_rate {
+ *   HOOK_RATE_SLOW,
+ *   HOOK_RATE_NORMAL,
+ *   HOOK_RATE_FAST,
+ *   HOOK_RATE_FASTEST
+ * };
+ * ```
+ */
+export enum HookRate { "Slow" = 0, "Normal", "Fast", "Fastest" }
+
+/**
+ * file filter
+ * ```c
+ *  "BMP Files (*.bmp);;"
+ *  "Targa Files (*.tga);;"
+ *  "PNG Files (*.png);;"
+ *  "JPEG Files (*.jpeg *.jpg);;"
+ * #ifdef _WIN32
+ *  "JXR Files (*.jxr);;"
+ * #endif
+ *  "GIF Files (*.gif);;"
+ *  "PSD Files (*.psd);;"
+ *  "WebP Files (*.webp);;"
+ *  "All Files (*.*)";
+ */
+export type ImageSetting = { 
+/**
+ * obs_properties_add_path(props, "file", obs_module_text("File"), OBS_PATH_FILE, image_filter, path.array);
+ */
+file: string | null, 
+/**
+ * obs_data_set_default_bool(settings, "unload", false);
+ */
+unload: boolean, 
+/**
+ * obs_data_set_default_bool(settings, "linear_alpha", false);
+ */
+linear_alpha: boolean, };
+
+export type ImportReport = { collection: string, files: number, };
+
+export type JsonValue = number | string | boolean | Array<JsonValue> | { [key in string]?: JsonValue } | null;
+
+export type KV = { name: string, value: JsonValue, };
+
+/**
+ * Ordered by severity, a filter keeps the lines at or above it.
+ */
+export type LogLevel = "error" | "warn" | "info" | "debug" | "trace";
+
+export type LogLine = { 
+/**
+ * `None` before the first leveled line
+ */
+level: LogLevel | null, text: string, };
+
+export type LogSession = { 
+/**
+ * file name, also the start time of the session
+ */
+name: string, path: string, size: number, current: boolean, };
+
+export type MacDisplayCaptureSetting = { 
+/**
+ * ```objc
+ * NSNumber *screen = [[NSScreen mainScreen] deviceDescription][@"NSScreenNumber"];
+ * CFUUIDRef display_uuid = CGDisplayCreateUUIDFromDisplayID((CGDirectDisplayID) screen.intValue);
+ * CFStringRef uuid_string = CFUUIDCreateString(kCFAllocatorDefault, display_uuid);
+ * obs_data_set_default_string(settings, "display_uuid", CFStringGetCStringPtr(uuid_string, kCFStringEncodingUTF8));
+ * ```
+ */
+display_uuid: string, 
+/**
+ * ```c
+ * obs_data_set_default_bool(settings, "show_cursor", true);
+ * ```
+ */
+show_cursor: boolean, 
+/**
+ * ```c
+ * obs_data_set_default_int(settings, "crop_mode", CROP_NONE);
+ * ```
+ */
+crop_mode: CropMode, 
+/**
+ * ```c
+ * obs_data_set_default_int(settings, "window", kCGNullWindowID);
+ * ```
+ * https://developer.apple.com/documentation/coregraphics/kcgnullwindowid
+ */
+window: number, 
+/**
+ * ```c
+ * obs_data_set_default_bool(settings, "show_empty_names", false);
+ * ```
+ */
+show_empty_names: boolean, };
+
+export type MacScreenCaptureSetting = { 
+/**
+ * ```c
+ * CGDirectDisplayID initial_display = 0;
+ * CFUUIDRef display_uuid = CGDisplayCreateUUIDFromDisplayID(initial_display);
+ * CFStringRef uuid_string = CFUUIDCreateString(kCFAllocatorDefault, display_uuid);
+ * obs_data_set_default_string(settings, "display_uuid", CFStringGetCStringPtr(uuid_string, kCFStringEncodingUTF8));
+ * CFRelease(uuid_string);
+ * CFRelease(display_uuid);
+ * ```
+ * only used when `type_` is `DisplayStream | ApplicationStream`
+ */
+display_uuid: string, 
+/**
+ * ```c
+ * obs_data_set_default_string(settings, "application", NULL);
+ * ```
+ * only used when `type_` is `ApplicationStream`
+ */
+application: string | null, 
+/**
+ * ```c
+ * obs_data_set_default_int(settings, "type", ScreenCaptureDisplayStream);
+ * ```
+ */
+type: ScreenCaptureType, 
+/**
+ * ```c
+ * obs_data_set_default_int(settings, "window", kCGNullWindowID);
+ * ```
+ * https://developer.apple.com/documentation/coregraphics/kcgnullwindowid
+ * only used when `type_` is `WindowStream`
+ */
+window: number, 
+/**
+ * ```c
+ * obs_data_set_default_bool(settings, "show_cursor", true);
+ * ```
+ */
+show_cursor: boolean, 
+/**
+ * ```c
+ * obs_data_set_default_bool(settings, "hide_obs", false);
+ * ```
+ * only used when `type_` is `DisplayStream`
+ */
+hide_obs: boolean, 
+/**
+ * ```c
+ * obs_data_set_default_bool(settings, "show_empty_names", false);
+ * ```
+ * only used when `type_` is `WindowStream`
+ */
+show_empty_names: boolean, 
+/**
+ * ```c
+ * obs_data_set_default_bool(settings, "show_hidden_windows", false);
+ * ```
+ * only used when `type_` is `WindowStream | ApplicationStream`
+ */
+show_hidden_windows: boolean, };
+
+export type MacWindowCaptureSetting = { 
+/**
+ * ```c
+ * obs_data_set_default_bool(settings, "show_shadow", false);
+ * ```
+ */
+show_shadow: boolean, 
+/**
+ * ```c
+ * obs_data_set_default_int(settings, "window", kCGNullWindowID);
+ * ```
+ * https://developer.apple.com/documentation/coregraphics/kcgnullwindowid
+ */
+window: number, 
+/**
+ * ```c
+ * obs_data_set_default_bool(settings, "show_empty_names", false);
+ * ```
+ */
+show_empty_names: boolean, };
+
+export type MergeResult = { 
+/**
+ * conflicting values are resolved to `ours`
+ */
+merged: SceneCollection, conflicts: Array<Conflict>, };
+
+/**
+ * A source, filter or transition whose type is not registered by any loaded module.
+ */
+export type MissingSource = { name: string, id: string, 
+/**
+ * the source a filter is attached to
+ */
+parent: string | null, };
+
+export type OutputEvent = { "type": "file_finished", output: string, path: string, } | { "type": "started", output: string, } | { "type": "stopped", output: string, code: number, message: string | null, } | { "type": "reconnecting", output: string, timeout_sec: number, } | { "type": "reconnected", output: string, } | { "type": "stats", output: string, state: OutputState, stats: OutputStats, } | { "type": "replay_saved", output: string, path: string, };
+
+export type OutputState = "stopped" | "starting" | "active" | "paused" | "reconnecting" | "stopping";
+
+/**
+ * Counters of an active output, see the stats dock of OBS Studio.
+ */
+export type OutputStats = { total_frames: number, 
+/**
+ * frames skipped because of network congestion
+ */
+dropped_frames: number, total_bytes: number, 
+/**
+ * 0 to 1, how full the send buffer is
+ */
+congestion: number, connect_time_ms: number, };
+
+export type ProfileEvent = { "type": "collection_added", file: string, collection: SceneCollection, } | { "type": "collection_changed", file: string, collection: SceneCollection, } | { "type": "collection_removed", file: string, } | { "type": "profile_changed", dir: string, name: string | null, config: { [key in string]?: { [key in string]?: string } }, };
+
+export type ProfileResult = { scenes: Array<KV>, profiles: Array<KV>, 
+/**
+ * files failed to read or parse, with the error message as value
+ */
+errors: Array<KV>, 
+/**
+ * folder name of the active profile, as in `profiles`
+ */
+active_profile: string | null, 
+/**
+ * file name of the active scene collection, as in `scenes`
+ */
+active_scene_collection: string | null, };
+
+/**
+ * Settings OBS Studio applies or enforces when "Ignore streaming service setting recommendations" is off.
+ */
+export type Recommended = { 
+/**
+ * keyframe interval in seconds
+ */
+keyint: number | null, profile: string | null, bframes: number | null, 
+/**
+ * kbps
+ */
+"max video bitrate": number | null, "max audio bitrate": number | null, x264opts: string | null, 
+/**
+ * output type, `rtmp_output` by default
+ */
+output: string | null, 
+/**
+ * e.g. `1920x1080`, any when empty
+ */
+"supported resolutions": Array<string>, "max fps": number | null, "bitrate matrix": Array<BitrateLimit>, };
+
+/**
+ * `Output.Reconnect`, `Output.RetryDelay` and `Output.MaxRetries` of `basic.ini`
+ */
+export type ReconnectSetting = { enabled: boolean, retry_delay_sec: number, max_retries: number, };
+
+export type RecordingSetting = { 
+/**
+ * folder of the files, created if missing
+ */
+directory: string, 
+/**
+ * file name without extension, see [`filename`]
+ */
+format: string, container: Container, 
+/**
+ * otherwise spaces of the name are replaced with `_`
+ */
+allow_spaces: boolean, 
+/**
+ * replace an existing file instead of adding a number to the name
+ */
+allow_overwrite: boolean, split: SplitFile | null, 
+/**
+ * remux every finished file into this format, see [`remux`](super::remux)
+ */
+remux: RemuxFormat | null, video_encoder: VideoEncoder, audio_encoder: AudioEncoder, 
+/**
+ * settings of the video encoder, its defaults when empty,
+ * see [`obs_x264::Setting`](super::settings::obs_x264::Setting) and [`ffmpeg_av1::Setting`](super::settings::ffmpeg_av1::Setting)
+ */
+video_settings: JsonValue, 
+/**
+ * see [`ffmpeg_audio::Setting`](super::settings::ffmpeg_audio::Setting)
+ */
+audio_settings: JsonValue, };
+
+export type Relinked = { from: string, to: string, source: string, 
+/**
+ * set when the path is in a filter of `source`
+ */
+filter: string | null, key: string, 
+/**
+ * position in `FILE_LIST_KEYS` lists
+ */
+index: number | null, };
+
+export type RemuxEvent = { "type": "progress", id: number, input: string, output: string, percent: number, } | { "type": "finished", id: number, input: string, output: string, } | { "type": "cancelled", id: number, input: string, output: string, } | { "type": "failed", id: number, input: string, output: string, message: string, };
+
+export type RemuxFormat = "mp4" | "mov";
+
+export type ReplayBufferSetting = { 
+/**
+ * folder of the saved replays, created if missing
+ */
+directory: string, 
+/**
+ * file name without extension, formatted when saved, see [`filename`](super::filename)
+ */
+format: string, container: Container, allow_spaces: boolean, 
+/**
+ * length of a replay
+ */
+max_time_sec: number, 
+/**
+ * memory the buffer could use, older data is dropped first
+ */
+max_size_mb: number, video_encoder: VideoEncoder, audio_encoder: AudioEncoder, 
+/**
+ * as [`RecordingSetting::video_settings`](super::RecordingSetting::video_settings)
+ */
+video_settings: JsonValue, audio_settings: JsonValue, };
+
+/**
+ * ```c
+ * #define RGBA10A2_SPACE_SRGB "srgb"
+ * #define RGBA10A2_SPACE_2100PQ "2100pq"
+ * ```
+ */
+export type Rgba10a2Space = "srgb" | "2100pq";
+
+export type SceneCollection = { name: string, current_scene?: string, current_program_scene?: string, current_transition?: string, 
+/**
+ * milliseconds
+ */
+transition_duration?: number, scene_order: Array<SceneOrder>, sources: Array<SourceData>, groups: Array<SourceData>, transitions: Array<TransitionData>, } & ({ [key in string]?: number | string | boolean | Array<JsonValue> | { [key in string]?: JsonValue } | null });
+
+export type SceneOrder = { name: string, };
+
+/**
+ * ```c
+ * typedef enum {
+ *   ScreenCaptureDisplayStream = 0,
+ *   ScreenCaptureWindowStream = 1,
+ *   ScreenCaptureApplicationStream = 2,
+ * } ScreenCaptureStreamType;
+ * ```
+ */
+export enum ScreenCaptureType { "DisplayStream" = 0, "WindowStream", "ApplicationStream" }
+
+export type Server = { name: string, url: string, };
+
+export type Service = { name: string, 
+/**
+ * listed first by OBS Studio, the others are behind "Show all"
+ */
+common: boolean, 
+/**
+ * former names, a profile could still refer to
+ */
+alt_names: Array<string>, 
+/**
+ * `RTMP`, `RTMPS`, `HLS`, `SRT`, `RIST` or `WHIP`, guessed from the server urls when missing
+ */
+protocol: string | null, servers: Array<Server>, 
+/**
+ * `h264` only when missing
+ */
+"supported video codecs": Array<string>, 
+/**
+ * `aac` only when missing
+ */
+"supported audio codecs": Array<string>, recommended: Recommended, stream_key_link: string | null, more_info_link: string | null, };
+
+export type ServiceIssue = { "type": "unknown_service", service: string, } | { "type": "unknown_server", server: string, } | { "type": "unsupported_output", output: string, } | { "type": "unsupported_video_codec", codec: string, supported: Array<string>, } | { "type": "unsupported_audio_codec", codec: string, supported: Array<string>, } | { "type": "video_bitrate_too_high", bitrate: number, max: number, } | { "type": "audio_bitrate_too_high", bitrate: number, max: number, } | { "type": "unsupported_resolution", resolution: string, supported: Array<string>, } | { "type": "fps_too_high", fps: number, max: number, };
+
+export type Services = { format_version: number, services: Array<Service>, };
+
+export type Snapshot = { 
+/**
+ * unix milliseconds, also the file name
+ */
+id: number, path: string, size: number, 
+/**
+ * number of changes since the previous snapshot by change type, e.g. `source_added`,
+ * empty for the oldest one
+ */
+summary: { [key in string]?: number }, };
+
+/**
+ * ```c
+ * static obs_data_t *obs_save_source(obs_source_t *source)
+ * ```
+ */
+export type SourceData = { name: string, uuid?: string, 
+/**
+ * unversioned id, e.g. `color_source`
+ */
+id: string, 
+/**
+ * e.g. `color_source_v3`, preferred over `id` when loading
+ */
+versioned_id?: string, settings: { [key in string]?: JsonValue }, enabled?: boolean, muted?: boolean, volume?: number, balance?: number, 
+/**
+ * bit mask of the audio tracks
+ */
+mixers?: number, 
+/**
+ * sync offset in nanoseconds
+ */
+sync?: number, 
+/**
+ * `enum obs_monitoring_type`
+ */
+monitoring_type?: number, filters: Array<SourceData>, } & ({ [key in string]?: number | string | boolean | Array<JsonValue> | { [key in string]?: JsonValue } | null });
+
+/**
+ * When `ffmpeg_muxer` starts a new file, the next name comes from the same format.
+ */
+export type SplitFile = { "type": "time", seconds: number, } | { "type": "size", megabytes: number, } | { "type": "manual" };
+
+/**
+ * `service.json` of a profile
+ */
+export type StreamService = { "type": "custom", 
+/**
+ * e.g. `rtmp://127.0.0.1/live`
+ */
+server: string, key: string, use_auth: boolean, username: string, password: string, } | { "type": "common", 
+/**
+ * name of the service, e.g. `Twitch`
+ */
+service: string, 
+/**
+ * url of one of its servers, `auto` for the ones picking the closest
+ */
+server: string, key: string, };
+
+export type StreamSetting = { service: StreamService, video_encoder: VideoEncoder, audio_encoder: AudioEncoder, 
+/**
+ * kbps, sent with constant bitrate
+ */
+video_bitrate: number, audio_bitrate: number, 
+/**
+ * other settings of the video encoder, as [`RecordingSetting::video_settings`](super::RecordingSetting::video_settings)
+ */
+video_settings: JsonValue, audio_settings: JsonValue, reconnect: ReconnectSetting, 
+/**
+ * connect and send without going live, the key gets `?bandwidthtest`
+ */
+bandwidth_test: boolean, };
+
+export type Transform = { pos: Vec2, scale: Vec2, 
+/**
+ * degrees, clockwise
+ */
+rot: number, };
+
+export type TransitionData = { name: string, id: string, settings: { [key in string]?: JsonValue }, } & ({ [key in string]?: number | string | boolean | Array<JsonValue> | { [key in string]?: JsonValue } | null });
+
+export type ValidationReport = { missing_files: Array<FileRef>, 
+/**
+ * empty if types were not checked
+ */
+missing_types: Array<MissingSource>, duplicate_names: Array<string>, dangling_items: Array<DanglingItem>, };
+
+export type Vec2 = { x: number, y: number, };
+
+export type VideoEncoder = "obs_x264" | "ffmpeg_openh264" | "ffmpeg_aom_av1" | "ffmpeg_svt_av1";
+
+/**
+ * What the virtual camera shows.
+ */
+export type VirtualCamSource = { "type": "program" } | { "type": "scene", name: string, } | { "type": "source", name: string, };
+
+export type WinGameCaptureSetting = { 
+/**
+ * ```c
+ * #define SETTING_MODE                 "capture_mode"
+ * obs_data_set_default_string(settings, SETTING_MODE, SETTING_MODE_ANY);
+ * ```
+ */
+capture_mode: CaptureMode, 
+/**
+ * ```c
+ * #define SETTING_CAPTURE_WINDOW       "window"
+ * obs_data_get_string(settings, SETTING_CAPTURE_WINDOW);
+ * ```
+ */
+window: string | null, 
+/**
+ * ```c
+ * #define SETTING_WINDOW_PRIORITY      "priority"
+ * obs_data_set_default_int(settings, SETTING_WINDOW_PRIORITY, (int)WINDOW_PRIORITY_EXE);
+ * ```
+ */
+priority: WindowPriority, 
+/**
+ * ```c
+ * #define SETTING_COMPATIBILITY        "sli_compatibility"
+ * obs_data_set_default_bool(settings, SETTING_COMPATIBILITY, false);
+ * ```
+ */
+sli_compatibility: boolean, 
+/**
+ * ```c
+ * #define SETTING_CURSOR               "capture_cursor"
+ * obs_data_set_default_bool(settings, SETTING_CURSOR, true);
+ * ```
+ */
+capture_cursor: boolean, 
+/**
+ * ```c
+ * #define SETTING_TRANSPARENCY         "allow_transparency"
+ * obs_data_set_default_bool(settings, SETTING_TRANSPARENCY, false);
+ * ```
+ */
+allow_transparency: boolean, 
+/**
+ * ```c
+ * #define SETTING_PREMULTIPLIED_ALPHA  "premultiplied_alpha"
+ * obs_data_set_default_bool(settings, SETTING_PREMULTIPLIED_ALPHA, false);
+ * ```
+ */
+premultiplied_alpha: boolean, 
+/**
+ * ```c
+ * #define SETTING_LIMIT_FRAMERATE      "limit_framerate"
+ * obs_data_set_default_bool(settings, SETTING_LIMIT_FRAMERATE, false);
+ * ```
+ */
+limit_framerate: boolean, 
+/**
+ * ```c
+ * #define SETTING_CAPTURE_OVERLAYS     "capture_overlays"
+ * obs_data_set_default_bool(settings, SETTING_CAPTURE_OVERLAYS, false);
+ * ```
+ */
+capture_overlays: boolean, 
+/**
+ * ```c
+ * #define SETTING_ANTI_CHEAT_HOOK      "anti_cheat_hook"
+ * obs_data_set_default_bool(settings, SETTING_ANTI_CHEAT_HOOK, true);
+ * ```
+ */
+anti_cheat_hook: boolean, 
+/**
+ * ```c
+ * #define SETTING_HOOK_RATE            "hook_rate"
+ * obs_data_set_default_int(settings, SETTING_HOOK_RATE, (int)HOOK_RATE_NORMAL);
+ * ```
+ */
+hook_rate: HookRate, 
+/**
+ * ```c
+ * #define SETTING_RGBA10A2_SPACE       "rgb10a2_space"
+ * obs_data_set_default_string(settings, SETTING_RGBA10A2_SPACE, RGBA10A2_SPACE_SRGB);
+ * ```
+ */
+rgb10a2_space: Rgba10a2Space, 
+/**
+ * ```c
+ * #define SETTINGS_COMPAT_INFO         "compat_info"
+ * ```
+ * TODO: might be property
+ */
+compat_info: string | null, };
+
+/**
+ * ```c
+ * enum window_priority {
+ *   WINDOW_PRIORITY_CLASS,
+ *   WINDOW_PRIORITY_TITLE,
+ *   WINDOW_PRIORITY_EXE,
+ * };
+ * ```
+ */
+export enum WindowPriority { "Class" = 0, "Title", "Exe" }
+
+/**
+ * `x264_preset_names`
+ */
+export type X264Preset = "ultrafast" | "superfast" | "veryfast" | "faster" | "fast" | "medium" | "slow" | "slower" | "veryslow" | "placebo";
+
+/**
+ * `x264_profile_names`, empty for the default of x264
+ */
+export type X264Profile = "" | "baseline" | "main" | "high";
+
+export type X264RateControl = "CBR" | "ABR" | "VBR" | "CRF";
+
+export type X264Setting = { 
+/**
+ * obs_data_set_default_string(settings, "rate_control", "CBR");
+ */
+rate_control: X264RateControl, 
+/**
+ * kbps, obs_data_set_default_int(settings, "bitrate", 2500);
+ */
+bitrate: number, 
+/**
+ * obs_data_set_default_bool(settings, "use_bufsize", false);
+ */
+use_bufsize: boolean, 
+/**
+ * kbit, obs_data_set_default_int(settings, "buffer_size", 2500);
+ */
+buffer_size: number, 
+/**
+ * only used by `CRF`, obs_data_set_default_int(settings, "crf", 23);
+ */
+crf: number, 
+/**
+ * keyframe interval in seconds, 0 for auto, obs_data_set_default_int(settings, "keyint_sec", 0);
+ */
+keyint_sec: number, 
+/**
+ * obs_data_set_default_string(settings, "preset", "veryfast");
+ */
+preset: X264Preset, 
+/**
+ * obs_data_set_default_string(settings, "profile", "");
+ */
+profile: X264Profile, 
+/**
+ * obs_data_set_default_string(settings, "tune", "");
+ */
+tune: X264Tune, 
+/**
+ * space separated `key=value` options passed to x264, obs_data_set_default_string(settings, "x264opts", "");
+ */
+x264opts: string, 
+/**
+ * obs_data_set_default_bool(settings, "repeat_headers", false);
+ */
+repeat_headers: boolean, };
+
+/**
+ * `x264_tune_names`, empty for no tuning
+ */
+export type X264Tune = "" | "film" | "animation" | "grain" | "stillimage" | "psnr" | "ssim" | "fastdecode" | "zerolatency";
+
+export function greet(name: string): Promise<string> {
+  return invoke("greet", { name })
+}
+
+export function listProfile(folder?: string | null): Promise<ProfileResult> {
+  return invoke("list_profile", { folder })
+}
+
+export function validateSceneCollection(file: string): Promise<ValidationReport> {
+  return invoke("validate_scene_collection", { file })
+}
+
+export function relinkSceneCollection(file: string, dir: string): Promise<Array<Relinked>> {
+  return invoke("relink_scene_collection", { file, dir })
+}
+
+export function diffSceneCollection(old: string, new_: string): Promise<Array<Change>> {
+  return invoke("diff_scene_collection", { old, new: new_ })
+}
+
+export function mergeSceneCollection(base: string, ours: string, theirs: string, output?: string | null): Promise<MergeResult> {
+  return invoke("merge_scene_collection", { base, ours, theirs, output })
+}
+
+export function exportSceneBundle(name: string, bundle: string, folder?: string | null): Promise<ExportReport> {
+  return invoke("export_scene_bundle", { name, bundle, folder })
+}
+
+export function importSceneBundle(bundle: string, targetDir: string, overwrite?: boolean | null, folder?: string | null): Promise<ImportReport> {
+  return invoke("import_scene_bundle", { bundle, targetDir, overwrite, folder })
+}
+
+export function createSceneCollection(name: string, folder?: string | null): Promise<string> {
+  return invoke("create_scene_collection", { name, folder })
+}
+
+export function duplicateSceneCollection(name: string, newName: string, folder?: string | null): Promise<string> {
+  return invoke("duplicate_scene_collection", { name, newName, folder })
+}
+
+export function renameSceneCollection(name: string, newName: string, folder?: string | null): Promise<string> {
+  return invoke("rename_scene_collection", { name, newName, folder })
+}
+
+export function deleteSceneCollection(name: string, folder?: string | null): Promise<void> {
+  return invoke("delete_scene_collection", { name, folder })
+}
+
+export function createProfile(name: string, folder?: string | null): Promise<string> {
+  return invoke("create_profile", { name, folder })
+}
+
+export function duplicateProfile(name: string, newName: string, folder?: string | null): Promise<string> {
+  return invoke("duplicate_profile", { name, newName, folder })
+}
+
+export function renameProfile(name: string, newName: string, folder?: string | null): Promise<string> {
+  return invoke("rename_profile", { name, newName, folder })
+}
+
+export function deleteProfile(name: string, folder?: string | null): Promise<void> {
+  return invoke("delete_profile", { name, folder })
+}
+
+export function listSceneBackups(name: string, folder?: string | null): Promise<Array<Snapshot>> {
+  return invoke("list_scene_backups", { name, folder })
+}
+
+export function backupSceneCollection(name: string, folder?: string | null): Promise<number> {
+  return invoke("backup_scene_collection", { name, folder })
+}
+
+export function saveSceneCollection(name: string, folder?: string | null): Promise<number> {
+  return invoke("save_scene_collection", { name, folder })
+}
+
+export function restoreSceneBackup(name: string, id: number, folder?: string | null): Promise<void> {
+  return invoke("restore_scene_backup", { name, id, folder })
+}
+
+export function watchProfile(folder?: string | null): Promise<void> {
+  return invoke("watch_profile", { folder })
+}
+
+export function unwatchProfile(): Promise<void> {
+  return invoke("unwatch_profile")
+}
+
+export function listLogSessions(): Promise<Array<LogSession>> {
+  return invoke("list_log_sessions")
+}
+
+export function readLog(name: string, level?: LogLevel | null): Promise<Array<LogLine>> {
+  return invoke("read_log", { name, level })
+}
+
+export function exportCurrentLog(target: string): Promise<string> {
+  return invoke("export_current_log", { target })
+}
+
+export function startRecording(setting: RecordingSetting): Promise<string> {
+  return invoke("start_recording", { setting })
+}
+
+export function stopRecording(): Promise<void> {
+  return invoke("stop_recording")
+}
+
+export function pauseRecording(): Promise<void> {
+  return invoke("pause_recording")
+}
+
+export function resumeRecording(): Promise<void> {
+  return invoke("resume_recording")
+}
+
+export function splitRecording(): Promise<void> {
+  return invoke("split_recording")
+}
+
+export function getRecordingState(): Promise<OutputState> {
+  return invoke("get_recording_state")
+}
+
+export function remuxRecording(input: string, output?: string | null, format?: RemuxFormat | null): Promise<number> {
+  return invoke("remux_recording", { input, output, format })
+}
+
+export function cancelRemux(id: number): Promise<boolean> {
+  return invoke("cancel_remux", { id })
+}
+
+export function startStream(setting: StreamSetting): Promise<void> {
+  return invoke("start_stream", { setting })
+}
+
+export function stopStream(): Promise<void> {
+  return invoke("stop_stream")
+}
+
+export function getStreamState(): Promise<OutputState> {
+  return invoke("get_stream_state")
+}
+
+export function startReplayBuffer(setting: ReplayBufferSetting): Promise<void> {
+  return invoke("start_replay_buffer", { setting })
+}
+
+export function stopReplayBuffer(): Promise<void> {
+  return invoke("stop_replay_buffer")
+}
+
+export function saveReplayBuffer(): Promise<void> {
+  return invoke("save_replay_buffer")
+}
+
+export function getReplayBufferState(): Promise<OutputState> {
+  return invoke("get_replay_buffer_state")
+}
+
+export function startVirtualcam(source: VirtualCamSource): Promise<void> {
+  return invoke("start_virtualcam", { source })
+}
+
+export function stopVirtualcam(): Promise<void> {
+  return invoke("stop_virtualcam")
+}
+
+export function getVirtualcamState(): Promise<OutputState> {
+  return invoke("get_virtualcam_state")
+}
+
+export function listStreamServices(): Promise<Services> {
+  return invoke("list_stream_services")
+}
+
+export function validateProfileService(name: string, folder?: string | null): Promise<Array<ServiceIssue>> {
+  return invoke("validate_profile_service", { name, folder })
+}
+
+export function createDisplay(): Promise<void> {
+  return invoke("create_display")
+}
+
+export type Events = {
+  "collection-added": Extract<ProfileEvent, { type: "collection_added" }>
+  "collection-changed": Extract<ProfileEvent, { type: "collection_changed" }>
+  "collection-removed": Extract<ProfileEvent, { type: "collection_removed" }>
+  "profile-changed": Extract<ProfileEvent, { type: "profile_changed" }>
+  "recording-file-finished": Extract<OutputEvent, { type: "file_finished" }>
+  "output-started": Extract<OutputEvent, { type: "started" }>
+  "output-stopped": Extract<OutputEvent, { type: "stopped" }>
+  "output-reconnecting": Extract<OutputEvent, { type: "reconnecting" }>
+  "output-reconnected": Extract<OutputEvent, { type: "reconnected" }>
+  "output-stats": Extract<OutputEvent, { type: "stats" }>
+  "replay-saved": Extract<OutputEvent, { type: "replay_saved" }>
+  "remux-progress": Extract<RemuxEvent, { type: "progress" }>
+  "remux-finished": Extract<RemuxEvent, { type: "finished" }>
+  "remux-cancelled": Extract<RemuxEvent, { type: "cancelled" }>
+  "remux-failed": Extract<RemuxEvent, { type: "failed" }>
+}
+
+export function onEvent<E extends keyof Events>(event: E, handler: (payload: Events[E]) => void): Promise<UnlistenFn> {
+  return listen<Events[E]>(event, (e) => handler(e.payload))
+}
+
+export const SAVE_REPLAY_REQUEST = "save-replay-request"